@@ -0,0 +1,18 @@
+-- Migration to add challenge submissions and scoring
+-- Each user may hold a single submission per challenge; resubmitting replaces the URL.
+-- Scoring is done by admins and feeds users.points / user_stats.challenges_taken.
+
+CREATE TABLE challenge_submissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    challenge_id INTEGER NOT NULL REFERENCES challenges(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    submission_url VARCHAR(512) NOT NULL,
+    score INTEGER,
+    scored_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(challenge_id, user_id)
+);
+
+CREATE INDEX idx_challenge_submissions_challenge_id ON challenge_submissions(challenge_id);
+CREATE INDEX idx_challenge_submissions_user_id ON challenge_submissions(user_id);
@@ -0,0 +1,15 @@
+-- Migration to add a points ledger
+-- Every change to users.points is recorded here with a timestamp so we can chart
+-- and window points over time. users.points stays as the cached running total.
+
+CREATE TABLE points_history (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    delta INTEGER NOT NULL,
+    reason VARCHAR(255) NOT NULL,
+    challenge_id INTEGER REFERENCES challenges(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_points_history_user_id_created_at ON points_history(user_id, created_at);
+CREATE INDEX idx_points_history_created_at ON points_history(created_at);
@@ -0,0 +1,5 @@
+-- Migration to let admins track which contact messages have been dealt with
+
+ALTER TABLE contact_messages ADD COLUMN handled BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX idx_contact_messages_created_at ON contact_messages(created_at DESC);
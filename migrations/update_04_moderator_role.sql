@@ -0,0 +1,7 @@
+-- Migration to add a moderator role between user and admin
+-- Moderators can manage resources and challenges; role changes stay admin-only.
+
+ALTER TABLE users DROP CONSTRAINT users_role_check;
+
+ALTER TABLE users
+ADD CONSTRAINT users_role_check CHECK (role IN ('user', 'moderator', 'admin'));
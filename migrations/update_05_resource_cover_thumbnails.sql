@@ -0,0 +1,2 @@
+-- Thumbnail generated alongside an uploaded resource cover image
+ALTER TABLE resources ADD COLUMN cover_thumbnail VARCHAR(512);
@@ -0,0 +1,3 @@
+-- Topic tags for resources (e.g. "NLP", "Computer Vision").
+-- Stored as a TEXT[] so sqlx can bind/decode them as Vec<String> without a join table.
+ALTER TABLE resources ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
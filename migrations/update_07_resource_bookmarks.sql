@@ -0,0 +1,10 @@
+-- Migration to let members bookmark resources for later
+
+CREATE TABLE resource_bookmarks (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    resource_id INTEGER NOT NULL REFERENCES resources(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, resource_id)
+);
+
+CREATE INDEX idx_resource_bookmarks_resource_id ON resource_bookmarks(resource_id);
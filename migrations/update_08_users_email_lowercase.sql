@@ -0,0 +1,12 @@
+-- Migration to make user emails case-insensitive
+-- Emails are stored trimmed and lowercased by the app; this brings existing rows in line
+-- and enforces uniqueness on LOWER(email) so "User@x.com" and "user@x.com" collide.
+--
+-- NOTE: the UPDATE fails on the existing users_email_key if two accounts differ only by
+-- case. Find them first with:
+--   SELECT LOWER(TRIM(email)), COUNT(*) FROM users GROUP BY 1 HAVING COUNT(*) > 1;
+-- and merge or rename the duplicates by hand before applying this migration.
+
+UPDATE users SET email = LOWER(TRIM(email)) WHERE email <> LOWER(TRIM(email));
+
+CREATE UNIQUE INDEX users_email_lower_key ON users (LOWER(email));
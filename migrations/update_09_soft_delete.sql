@@ -0,0 +1,6 @@
+-- Migration to soft-delete resources and challenges
+-- Deleting now sets deleted_at instead of removing the row, so submissions keep their
+-- challenge and an accidental delete can be restored. Reads filter deleted_at IS NULL.
+
+ALTER TABLE resources ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE challenges ADD COLUMN deleted_at TIMESTAMPTZ;
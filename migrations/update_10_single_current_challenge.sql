@@ -0,0 +1,8 @@
+-- Migration to let admins pin exactly one current challenge
+-- Keep only the newest flagged challenge before enforcing at most one is_current row.
+
+UPDATE challenges SET is_current = false
+WHERE is_current
+  AND id <> (SELECT id FROM challenges WHERE is_current ORDER BY created_at DESC, id DESC LIMIT 1);
+
+CREATE UNIQUE INDEX challenges_single_current ON challenges (is_current) WHERE is_current;
@@ -0,0 +1,4 @@
+-- Migration to let members hide their public profile (GET /users/:id)
+-- Profiles are public by default.
+
+ALTER TABLE users ADD COLUMN profile_public BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,4 @@
+-- Migration to let admins attach a specific quote to a resource
+-- Resources without one keep showing a random visible quote.
+
+ALTER TABLE resources ADD COLUMN quote_id INTEGER REFERENCES quotes(id) ON DELETE SET NULL;
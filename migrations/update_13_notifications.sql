@@ -0,0 +1,15 @@
+-- Migration to add in-app notifications (e.g. "your submission was scored")
+-- `kind` says what happened, `payload` carries the details the frontend needs to render it.
+
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    read BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_notifications_user_id ON notifications(user_id, created_at DESC);
+-- Keeps the unread count cheap
+CREATE INDEX idx_notifications_unread ON notifications(user_id) WHERE NOT read;
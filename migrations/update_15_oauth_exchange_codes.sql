@@ -0,0 +1,12 @@
+-- Migration to add one-time codes for finishing Google sign-in
+-- The OAuth callback redirects to the frontend with only `code`; the frontend trades it
+-- for the token at POST /auth/exchange. Codes are deleted when used and expire quickly.
+
+CREATE TABLE oauth_exchange_codes (
+    code VARCHAR(64) PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_oauth_exchange_codes_expires_at ON oauth_exchange_codes(expires_at);
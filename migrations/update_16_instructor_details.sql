@@ -0,0 +1,5 @@
+-- Migration to add an optional bio and job title for resource instructors
+-- e.g. title "Senior ML Engineer @ X". Existing resources simply have neither.
+
+ALTER TABLE resources ADD COLUMN instructor_title VARCHAR(255);
+ALTER TABLE resources ADD COLUMN instructor_bio TEXT;
@@ -0,0 +1,9 @@
+-- Migration to record which admin or moderator created each resource and challenge
+-- Rows created before this migration, and those whose creator's account was deleted,
+-- have NULL.
+
+ALTER TABLE resources ADD COLUMN created_by UUID REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE challenges ADD COLUMN created_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_resources_created_by ON resources(created_by);
+CREATE INDEX idx_challenges_created_by ON challenges(created_by);
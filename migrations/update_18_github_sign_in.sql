@@ -0,0 +1,5 @@
+-- Migration for GitHub sign-in
+-- GitHub account ids are stored like google_id: one per user, and each GitHub account
+-- belongs to at most one user.
+
+ALTER TABLE users ADD COLUMN github_id VARCHAR(255) UNIQUE;
@@ -0,0 +1,6 @@
+-- Migration to serve /leaderboards from the leaderboards table
+-- Seed the board the endpoint used to hard-code so existing clients keep seeing it.
+
+INSERT INTO leaderboards (title)
+SELECT 'Top Users'
+WHERE NOT EXISTS (SELECT 1 FROM leaderboards);
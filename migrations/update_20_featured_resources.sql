@@ -0,0 +1,3 @@
+-- Migration to let admins spotlight resources on the home page
+
+ALTER TABLE resources ADD COLUMN featured BOOLEAN NOT NULL DEFAULT FALSE;
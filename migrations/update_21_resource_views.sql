@@ -0,0 +1,13 @@
+-- Migration to track how many members open each resource
+-- Only signed-in views count, once per member, so bots and refreshes don't inflate it.
+
+ALTER TABLE resources ADD COLUMN view_count INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE resource_views (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    resource_id INTEGER NOT NULL REFERENCES resources(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, resource_id)
+);
+
+CREATE INDEX idx_resource_views_resource_id ON resource_views(resource_id);
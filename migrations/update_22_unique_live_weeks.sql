@@ -0,0 +1,26 @@
+-- Migration to let the database enforce one live challenge per week number
+-- The app already checks this, but rows written before that check (or by hand) may share
+-- a week. Per week, the current challenge or else the oldest keeps its number; the rest
+-- move to fresh numbers after the highest week in use, so nothing is hidden or lost.
+-- Review them afterwards with:
+--   SELECT id, week, title FROM challenges WHERE deleted_at IS NULL ORDER BY week;
+
+WITH duplicates AS (
+    SELECT id, ROW_NUMBER() OVER (
+        PARTITION BY week ORDER BY is_current DESC, created_at, id
+    ) AS position
+    FROM challenges
+    WHERE deleted_at IS NULL
+),
+moved AS (
+    SELECT id, ROW_NUMBER() OVER (ORDER BY id) AS offset_by
+    FROM duplicates
+    WHERE position > 1
+)
+UPDATE challenges c
+SET week = (SELECT COALESCE(MAX(week), 0) FROM challenges) + moved.offset_by,
+    updated_at = NOW()
+FROM moved
+WHERE c.id = moved.id;
+
+CREATE UNIQUE INDEX challenges_live_week_key ON challenges (week) WHERE deleted_at IS NULL;
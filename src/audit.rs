@@ -0,0 +1,28 @@
+use serde_json::Value;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+// Audit actions
+pub const GOOGLE_ACCOUNT_LINKED: &str = "google_account_linked";
+pub const GITHUB_ACCOUNT_LINKED: &str = "github_account_linked";
+pub const ADMIN_BOOTSTRAPPED: &str = "admin_bootstrapped";
+
+// Runs on the executor making the change, so a rollback drops the entry as well
+pub async fn record<'e, E>(
+    executor: E,
+    actor_id: Option<Uuid>,
+    action: &str,
+    details: Value,
+) -> Result<(), sqlx::Error>
+where
+    E: PgExecutor<'e>,
+{
+    sqlx::query("INSERT INTO audit_log (actor_id, action, details) VALUES ($1, $2, $3)")
+        .bind(actor_id)
+        .bind(action)
+        .bind(details)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
@@ -1,9 +1,15 @@
 use axum::{
     async_trait,
     extract::{FromRef, FromRequestParts},
-    http::{header::AUTHORIZATION, request::Parts},
+    http::{
+        HeaderValue,
+        header::{AUTHORIZATION, COOKIE},
+        request::Parts,
+    },
+};
+use jsonwebtoken::{
+    DecodingKey, EncodingKey, Header, Validation, decode, encode, errors::ErrorKind,
 };
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
@@ -12,21 +18,84 @@ use uuid::Uuid;
 
 use crate::error::AppError;
 
+// HS256 secrets shorter than the hash output are easier to brute force
+pub const MIN_JWT_SECRET_LEN: usize = 32;
+
+// Checked by AppConfig::from_env, so a bad secret stops startup instead of failing the
+// first authenticated request
+pub fn validate_jwt_secret(secret: Option<&str>) -> anyhow::Result<()> {
+    let Some(secret) = secret.filter(|secret| !secret.is_empty()) else {
+        anyhow::bail!("JWT_SECRET must be set");
+    };
+    if secret.len() < MIN_JWT_SECRET_LEN {
+        anyhow::bail!(
+            "JWT_SECRET is {} bytes long; use at least {} random bytes",
+            secret.len(),
+            MIN_JWT_SECRET_LEN
+        );
+    }
+
+    Ok(())
+}
+
+// Used when JWT_ISSUER / JWT_AUDIENCE aren't set
+pub const DEFAULT_ISSUER: &str = "uj-ai-club-backend";
+pub const DEFAULT_AUDIENCE: &str = "uj-ai-club";
+
 static KEYS: Lazy<Keys> = Lazy::new(|| {
     let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-    Keys::new(secret.as_bytes())
+    let issuer = env::var("JWT_ISSUER")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_ISSUER.to_string());
+    let audience = env::var("JWT_AUDIENCE")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_AUDIENCE.to_string());
+    let legacy_until = env::var("JWT_LEGACY_TOKENS_UNTIL")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .and_then(|value| match chrono::DateTime::parse_from_rfc3339(&value) {
+            Ok(until) => Some(until.timestamp()),
+            Err(e) => {
+                tracing::warn!("Ignoring invalid JWT_LEGACY_TOKENS_UNTIL {}: {}", value, e);
+                None
+            }
+        });
+    Keys::new(secret.as_bytes(), issuer, audience, legacy_until)
 });
 
 struct Keys {
     encoding: EncodingKey,
     decoding: DecodingKey,
+    issuer: String,
+    audience: String,
+    // Tokens from another service sharing the secret fail on iss/aud
+    validation: Validation,
+    legacy_validation: Validation,
+    // Last expiry accepted on tokens minted before iss/aud existed; None rejects them all
+    legacy_until: Option<i64>,
 }
 
 impl Keys {
-    fn new(secret: &[u8]) -> Self {
+    fn new(secret: &[u8], issuer: String, audience: String, legacy_until: Option<i64>) -> Self {
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&issuer]);
+        validation.set_audience(&[&audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+
+        // LegacyClaims insists on iss/aud being absent instead
+        let mut legacy_validation = Validation::default();
+        legacy_validation.validate_aud = false;
+
         Self {
             encoding: EncodingKey::from_secret(secret),
             decoding: DecodingKey::from_secret(secret),
+            issuer,
+            audience,
+            validation,
+            legacy_validation,
+            legacy_until,
         }
     }
 }
@@ -35,30 +104,218 @@ impl Keys {
 pub struct Claims {
     pub sub: String,
     pub exp: i64,
+    pub iss: String,
+    pub aud: String,
+}
+
+// The claims tokens carried before iss/aud were added
+#[derive(Deserialize)]
+struct LegacyClaims {
+    sub: String,
+    exp: i64,
+    iss: Option<serde_json::Value>,
+    aud: Option<serde_json::Value>,
 }
 
+// How long a freshly minted token stays valid, unless the login asked to be remembered
+pub const TOKEN_LIFETIME_SECS: i64 = 24 * 60 * 60;
+
 impl Claims {
     pub fn new(user_id: Uuid) -> Self {
+        Self::with_lifetime(user_id, TOKEN_LIFETIME_SECS)
+    }
+
+    pub fn with_lifetime(user_id: Uuid, lifetime_secs: i64) -> Self {
         Self {
             sub: user_id.to_string(),
-            exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp(),
+            exp: (chrono::Utc::now() + chrono::Duration::seconds(lifetime_secs)).timestamp(),
+            iss: KEYS.issuer.clone(),
+            aud: KEYS.audience.clone(),
         }
     }
+
+    // Seconds until the token expires
+    pub fn expires_in(&self) -> i64 {
+        (self.exp - chrono::Utc::now().timestamp()).max(0)
+    }
 }
 
-pub fn create_token(user_id: Uuid) -> Result<String, AppError> {
-    encode(&Header::default(), &Claims::new(user_id), &KEYS.encoding)
-        .map_err(|e| AppError::InternalError(e.into()))
+// Returns the claims alongside the token so callers can report its expiry
+pub fn create_token(user_id: Uuid) -> Result<(String, Claims), AppError> {
+    create_token_with_lifetime(user_id, TOKEN_LIFETIME_SECS)
 }
 
+pub fn create_token_with_lifetime(
+    user_id: Uuid,
+    lifetime_secs: i64,
+) -> Result<(String, Claims), AppError> {
+    let claims = Claims::with_lifetime(user_id, lifetime_secs);
+    let token = encode(&Header::default(), &claims, &KEYS.encoding)
+        .map_err(|e| AppError::InternalError(e.into()))?;
+
+    Ok((token, claims))
+}
+
+// Cookie login and signup may also put the token in, see AppConfig::auth_cookie
+pub const AUTH_COOKIE: &str = "auth_token";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SameSite {
+    #[default]
+    Lax,
+    Strict,
+    None,
+}
+
+impl SameSite {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "lax" => Some(Self::Lax),
+            "strict" => Some(Self::Strict),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Lax => "Lax",
+            Self::Strict => "Strict",
+            Self::None => "None",
+        }
+    }
+}
+
+// Set-Cookie value carrying a token; pass the token's remaining lifetime as max_age so
+// the cookie expires along with it. An empty token with max_age 0 clears the cookie.
+pub fn auth_cookie(token: &str, max_age: i64, same_site: SameSite) -> HeaderValue {
+    HeaderValue::from_str(&format!(
+        "{AUTH_COOKIE}={token}; Path=/; Max-Age={max_age}; HttpOnly; Secure; SameSite={}",
+        same_site.as_str()
+    ))
+    .expect("JWTs are valid header values")
+}
+
+// Role hierarchy: every role a user may hold, and which roles each extractor accepts
+pub const ROLES: &[&str] = &["user", "moderator", "admin"];
+pub const MODERATOR_ROLES: &[&str] = &["moderator", "admin"];
+pub const ADMIN_ROLES: &[&str] = &["admin"];
+
 pub struct AuthUser {
     pub user_id: Uuid,
 }
 
+// For routes that also serve anonymous callers: None without credentials, but a
+// token that is sent must still be valid
+pub struct OptionalAuthUser(pub Option<AuthUser>);
+
+pub struct ModeratorUser {
+    pub user_id: Uuid,
+}
+
 pub struct AdminUser {
     pub user_id: Uuid,
 }
 
+// Browsers attach the auth cookie to cross-site form posts, but can't add a custom header
+// to one, and CORS preflights scripts that try. Cookie-authenticated writes must send it.
+pub const CSRF_HEADER: &str = "x-requested-with";
+
+// The Authorization header wins when present; otherwise the auth cookie is used
+fn request_token(parts: &Parts) -> Result<Option<&str>, AppError> {
+    if let Some(authorization) = parts.headers.get(AUTHORIZATION) {
+        return authorization
+            .to_str()
+            .map_err(|_| AppError::AuthError)?
+            .strip_prefix("Bearer ")
+            .map(Some)
+            .ok_or(AppError::AuthError);
+    }
+
+    let token = parts
+        .headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find_map(|(name, value)| (name == AUTH_COOKIE).then_some(value));
+
+    if token.is_some() && !parts.method.is_safe() && !parts.headers.contains_key(CSRF_HEADER) {
+        return Err(AppError::CrossSiteRequest);
+    }
+
+    Ok(token)
+}
+
+fn token_error(e: jsonwebtoken::errors::Error) -> AppError {
+    match e.kind() {
+        ErrorKind::ExpiredSignature => AppError::TokenExpired,
+        _ => AppError::TokenInvalid,
+    }
+}
+
+fn user_id_from_token(parts: &Parts) -> Result<Uuid, AppError> {
+    let token = request_token(parts)?.ok_or(AppError::AuthError)?;
+
+    let subject = match decode::<Claims>(token, &KEYS.decoding, &KEYS.validation) {
+        Ok(token_data) => token_data.claims.sub,
+        // Claims without iss/aud don't deserialize, so legacy tokens land here
+        Err(e)
+            if matches!(
+                e.kind(),
+                ErrorKind::Json(_) | ErrorKind::MissingRequiredClaim(_)
+            ) =>
+        {
+            legacy_subject(token)?
+        }
+        Err(e) => return Err(token_error(e)),
+    };
+
+    Uuid::parse_str(&subject).map_err(|_| AppError::TokenInvalid)
+}
+
+// Sessions from before iss/aud were added keep working if they expire by
+// JWT_LEGACY_TOKENS_UNTIL, so rolling that change out doesn't sign everyone out
+fn legacy_subject(token: &str) -> Result<String, AppError> {
+    let until = KEYS.legacy_until.ok_or(AppError::TokenInvalid)?;
+    let claims = decode::<LegacyClaims>(token, &KEYS.decoding, &KEYS.legacy_validation)
+        .map_err(token_error)?
+        .claims;
+
+    if claims.iss.is_some() || claims.aud.is_some() || claims.exp > until {
+        return Err(AppError::TokenInvalid);
+    }
+
+    Ok(claims.sub)
+}
+
+async fn user_id_with_role<S>(
+    parts: &Parts,
+    state: &S,
+    allowed_roles: &[&str],
+) -> Result<Uuid, AppError>
+where
+    PgPool: FromRef<S>,
+{
+    let user_id = user_id_from_token(parts)?;
+
+    let pool = PgPool::from_ref(state);
+
+    let user_role: (String,) = sqlx::query_as("SELECT role FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| AppError::InternalError(e.into()))?
+        .ok_or(AppError::AuthError)?;
+
+    if !allowed_roles.contains(&user_role.0.as_str()) {
+        return Err(AppError::AuthError);
+    }
+
+    Ok(user_id)
+}
+
 #[async_trait]
 impl<S> FromRequestParts<S> for AuthUser
 where
@@ -67,59 +324,55 @@ where
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let bearer = parts
-            .headers
-            .get(AUTHORIZATION)
-            .ok_or(AppError::AuthError)?
-            .to_str()
-            .map_err(|_| AppError::AuthError)?
-            .strip_prefix("Bearer ")
-            .ok_or(AppError::AuthError)?;
-
-        let token_data = decode::<Claims>(bearer, &KEYS.decoding, &Validation::default())
-            .map_err(|_| AppError::AuthError)?;
-
-        let user_id = Uuid::parse_str(&token_data.claims.sub).map_err(|_| AppError::AuthError)?;
+        let user_id = user_id_from_token(parts)?;
 
         Ok(Self { user_id })
     }
 }
 
 #[async_trait]
-impl<S> FromRequestParts<S> for AdminUser
+impl<S> FromRequestParts<S> for OptionalAuthUser
 where
     S: Send + Sync,
-    PgPool: axum::extract::FromRef<S>,
 {
     type Rejection = AppError;
 
-    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let bearer = parts
-            .headers
-            .get(AUTHORIZATION)
-            .ok_or(AppError::AuthError)?
-            .to_str()
-            .map_err(|_| AppError::AuthError)?
-            .strip_prefix("Bearer ")
-            .ok_or(AppError::AuthError)?;
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if request_token(parts)?.is_none() {
+            return Ok(Self(None));
+        }
 
-        let token_data = decode::<Claims>(bearer, &KEYS.decoding, &Validation::default())
-            .map_err(|_| AppError::AuthError)?;
+        let user_id = user_id_from_token(parts)?;
 
-        let user_id = Uuid::parse_str(&token_data.claims.sub).map_err(|_| AppError::AuthError)?;
+        Ok(Self(Some(AuthUser { user_id })))
+    }
+}
 
-        let pool = PgPool::from_ref(state);
+#[async_trait]
+impl<S> FromRequestParts<S> for ModeratorUser
+where
+    S: Send + Sync,
+    PgPool: FromRef<S>,
+{
+    type Rejection = AppError;
 
-        let user_role: (String,) = sqlx::query_as("SELECT role FROM users WHERE id = $1")
-            .bind(user_id)
-            .fetch_optional(&pool)
-            .await
-            .map_err(|e| AppError::InternalError(e.into()))?
-            .ok_or(AppError::AuthError)?;
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user_id = user_id_with_role(parts, state, MODERATOR_ROLES).await?;
 
-        if user_role.0 != "admin" {
-            return Err(AppError::AuthError);
-        }
+        Ok(Self { user_id })
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminUser
+where
+    S: Send + Sync,
+    PgPool: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user_id = user_id_with_role(parts, state, ADMIN_ROLES).await?;
 
         Ok(Self { user_id })
     }
@@ -0,0 +1,587 @@
+use axum::http::HeaderValue;
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth::{SameSite, validate_jwt_secret};
+use crate::mailer::{Mailer, SmtpMailer};
+use crate::oauth::OAuthProvider;
+use crate::parse_allowed_origins;
+use crate::validation::{PasswordPolicy, allowed_frontend_url, canonical_email, url_origin};
+
+// Where users land after Google sign-in unless FRONTEND_URL says otherwise
+pub const DEFAULT_FRONTEND_URL: &str = "https://aiclub-uj.com";
+
+// Everything create_app needs to know about its environment. main.rs reads it with
+// `AppConfig::from_env()`; tests build one directly.
+#[derive(Clone)]
+pub struct AppConfig {
+    // Sign-in providers by the name used in /auth/oauth/:provider; "google" is always there
+    pub oauth_providers: HashMap<String, OAuthConfig>,
+    // Upper bound on each call to a provider, connecting included
+    pub oauth_timeout: Duration,
+    // Whether a first OAuth sign-in may attach itself to an existing account with the
+    // same email. Off by default since the account owner never confirms the link.
+    pub allow_oauth_account_linking: bool,
+    // Where the OAuth callback sends users, used only if its origin is on the allowlist
+    pub frontend_url: String,
+    // Origins (scheme://host[:port]) the OAuth callback may redirect to
+    pub frontend_allowed_origins: Vec<String>,
+    // Hosts submissions may link to; empty means unrestricted
+    pub submission_allowed_domains: Vec<String>,
+    // Falls back to a logging no-op mailer when unset; from_env builds one from SmtpConfig
+    pub mailer: Option<Arc<dyn Mailer>>,
+    pub admin_notification_email: Option<String>,
+    pub password_policy: PasswordPolicy,
+    // Work factor for new password hashes, see parse_bcrypt_cost
+    pub bcrypt_cost: u32,
+    pub storage: StorageConfig,
+    // Local upload directory, served at /uploads; relative paths resolve against the
+    // working directory
+    pub uploads_dir: PathBuf,
+    pub metrics_enabled: bool,
+    // Only honour X-Real-IP when running behind our own proxy, otherwise clients could spoof it
+    pub trust_proxy_headers: bool,
+    // Email availability checks allowed per IP per minute
+    pub email_check_rate_limit: u32,
+    // Contact form submissions allowed per IP per hour
+    pub contact_rate_limit: u32,
+    // Form field real frontends leave empty; submissions that fill it are dropped silently
+    pub contact_honeypot_field: Option<String>,
+    // Concurrent /ws/leaderboard connections per instance
+    pub leaderboard_ws_max_connections: usize,
+    // How long a top-10 board is served from memory; zero disables the cache
+    pub leaderboard_cache_ttl: Duration,
+    pub allowed_origins: Vec<HeaderValue>,
+    pub allow_any_origin: bool,
+    pub body_limits: BodyLimits,
+    pub contact_limits: ContactLimits,
+    // Lets a fresh deployment promote its first admin, see handlers::admin_bootstrap
+    pub admin_bootstrap: Option<AdminBootstrapConfig>,
+    // When set, login and signup also hand out the token as an HttpOnly cookie
+    pub auth_cookie: Option<SameSite>,
+    // Token lifetime for logins that ask to be remembered, see parse_remember_me_lifetime
+    pub remember_me_lifetime: Duration,
+    // Most resources GET /resources/featured returns
+    pub featured_resources_limit: u32,
+}
+
+#[derive(Clone)]
+pub struct OAuthConfig {
+    pub provider: OAuthProvider,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+    pub auth_url: String,
+    pub token_url: String,
+    pub userinfo_url: String,
+}
+
+#[derive(Clone)]
+pub struct AdminBootstrapConfig {
+    // Canonical email of the account to promote
+    pub email: String,
+    // Shared secret the caller has to present
+    pub token: String,
+}
+
+#[derive(Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    pub credentials: Option<(String, String)>,
+    pub from: String,
+}
+
+#[derive(Clone)]
+pub enum StorageConfig {
+    // Files under `AppConfig::uploads_dir`
+    Local,
+    S3 {
+        bucket: String,
+        region: String,
+        endpoint: Option<String>,
+        credentials: Option<(String, String)>,
+        public_url: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimits {
+    pub max_body_bytes: usize,
+    pub max_upload_bytes: usize,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 1024 * 1024,
+            max_upload_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl BodyLimits {
+    // MAX_BODY_BYTES applies to every route, MAX_UPLOAD_BYTES to the multipart upload routes
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |name: &str, default: usize| match env::var(name) {
+            Ok(value) if !value.trim().is_empty() => value.trim().parse().unwrap_or_else(|_| {
+                tracing::warn!("Invalid {} value {:?}, using default", name, value);
+                default
+            }),
+            _ => default,
+        };
+
+        Self {
+            max_body_bytes: read("MAX_BODY_BYTES", defaults.max_body_bytes),
+            max_upload_bytes: read("MAX_UPLOAD_BYTES", defaults.max_upload_bytes),
+        }
+    }
+}
+
+// Longest name and message (in characters) the contact form accepts
+#[derive(Debug, Clone, Copy)]
+pub struct ContactLimits {
+    pub max_name_len: usize,
+    pub max_message_len: usize,
+}
+
+impl Default for ContactLimits {
+    fn default() -> Self {
+        Self {
+            max_name_len: 100,
+            max_message_len: 5000,
+        }
+    }
+}
+
+impl ContactLimits {
+    // Reads CONTACT_MAX_NAME_LENGTH and CONTACT_MAX_MESSAGE_LENGTH
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |name: &str, default: usize| match env::var(name) {
+            Ok(value) if !value.trim().is_empty() => match value.trim().parse() {
+                Ok(len) if len > 0 => len,
+                _ => {
+                    tracing::warn!("Invalid {} value {:?}, using default", name, value);
+                    default
+                }
+            },
+            _ => default,
+        };
+
+        Self {
+            max_name_len: read("CONTACT_MAX_NAME_LENGTH", defaults.max_name_len),
+            max_message_len: read("CONTACT_MAX_MESSAGE_LENGTH", defaults.max_message_len),
+        }
+    }
+}
+
+// Certificate chain and private key (both PEM) for serving HTTPS directly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+impl TlsConfig {
+    // Reads TLS_CERT_PATH and TLS_KEY_PATH; None means plain HTTP
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        Self::from_lookup(|name| env::var(name).ok())
+    }
+
+    // Setting only one of the two is a mistake worth refusing to start over
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<Option<Self>> {
+        let read = |name: &str| lookup(name).filter(|value| !value.trim().is_empty());
+
+        match (read("TLS_CERT_PATH"), read("TLS_KEY_PATH")) {
+            (Some(cert_path), Some(key_path)) => Ok(Some(Self {
+                cert_path: PathBuf::from(cert_path.trim()),
+                key_path: PathBuf::from(key_path.trim()),
+            })),
+            (None, None) => Ok(None),
+            (Some(_), None) => anyhow::bail!("TLS_CERT_PATH is set but TLS_KEY_PATH is not"),
+            (None, Some(_)) => anyhow::bail!("TLS_KEY_PATH is set but TLS_CERT_PATH is not"),
+        }
+    }
+}
+
+impl SmtpConfig {
+    // Reads SMTP_HOST and friends; None means email is not configured
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        Self::from_lookup(|name| env::var(name).ok())
+    }
+
+    // A host without a usable sender or port is refused rather than failing on first send
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<Option<Self>> {
+        let read = |name: &str| {
+            lookup(name)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let Some(host) = read("SMTP_HOST") else {
+            return Ok(None);
+        };
+
+        let port = read("SMTP_PORT")
+            .map(|port| {
+                port.parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid SMTP_PORT {port:?}"))
+            })
+            .transpose()?;
+
+        let from = read("SMTP_FROM")
+            .ok_or_else(|| anyhow::anyhow!("SMTP_FROM must be set when SMTP_HOST is set"))?;
+        from.parse::<lettre::message::Mailbox>()
+            .map_err(|e| anyhow::anyhow!("Invalid SMTP_FROM {from:?}: {e}"))?;
+
+        let credentials = match (lookup("SMTP_USERNAME"), lookup("SMTP_PASSWORD")) {
+            (Some(username), Some(password)) => Some((username, password)),
+            _ => None,
+        };
+
+        Ok(Some(Self {
+            host,
+            port,
+            credentials,
+            from,
+        }))
+    }
+}
+
+impl OAuthConfig {
+    pub fn google(client_id: String, client_secret: String, redirect_uri: String) -> Self {
+        Self {
+            provider: OAuthProvider::Google,
+            client_id,
+            client_secret,
+            redirect_uri,
+            auth_url: "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
+            token_url: "https://oauth2.googleapis.com/token".to_string(),
+            userinfo_url: "https://www.googleapis.com/oauth2/v3/userinfo".to_string(),
+        }
+    }
+
+    // Verified addresses are read from `{userinfo_url}/emails`, see oauth::fetch_user_info
+    pub fn github(client_id: String, client_secret: String, redirect_uri: String) -> Self {
+        Self {
+            provider: OAuthProvider::GitHub,
+            client_id,
+            client_secret,
+            redirect_uri,
+            auth_url: "https://github.com/login/oauth/authorize".to_string(),
+            token_url: "https://github.com/login/oauth/access_token".to_string(),
+            userinfo_url: "https://api.github.com/user".to_string(),
+        }
+    }
+}
+
+// The bcrypt crate rejects costs below 4. Above 14 a single hash takes seconds, which
+// turns every login into an easy way to tie up the server.
+const BCRYPT_COST_RANGE: std::ops::RangeInclusive<u32> = 4..=14;
+
+// Reads BCRYPT_COST. Each step doubles the time a hash takes (about 250ms at the default
+// of 12 on a typical server core), and signup, login and password changes all pay it.
+// Unset, unparsable or out-of-range values fall back to the default.
+pub fn parse_bcrypt_cost(value: Option<&str>) -> u32 {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return bcrypt::DEFAULT_COST;
+    };
+
+    match value.parse() {
+        Ok(cost) if BCRYPT_COST_RANGE.contains(&cost) => cost,
+        _ => {
+            tracing::warn!(
+                "Invalid BCRYPT_COST {:?} (expected {}-{}), using {}",
+                value,
+                BCRYPT_COST_RANGE.start(),
+                BCRYPT_COST_RANGE.end(),
+                bcrypt::DEFAULT_COST
+            );
+            bcrypt::DEFAULT_COST
+        }
+    }
+}
+
+const DEFAULT_REMEMBER_ME_LIFETIME: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+// A year is already longer than anyone should stay signed in on a shared machine, and
+// keeps the expiry well inside what the token timestamps can hold.
+pub const MAX_REMEMBER_ME_LIFETIME: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+// Reads REMEMBER_ME_LIFETIME_SECS. Unset, unparsable, zero or over-a-year values fall back
+// to the 30-day default.
+pub fn parse_remember_me_lifetime(value: Option<&str>) -> Duration {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return DEFAULT_REMEMBER_ME_LIFETIME;
+    };
+
+    match value.parse().map(Duration::from_secs) {
+        Ok(lifetime) if !lifetime.is_zero() && lifetime <= MAX_REMEMBER_ME_LIFETIME => lifetime,
+        _ => {
+            tracing::warn!(
+                "Invalid REMEMBER_ME_LIFETIME_SECS {:?} (expected 1-{}), using {}",
+                value,
+                MAX_REMEMBER_ME_LIFETIME.as_secs(),
+                DEFAULT_REMEMBER_ME_LIFETIME.as_secs()
+            );
+            DEFAULT_REMEMBER_ME_LIFETIME
+        }
+    }
+}
+
+// Reads ALLOW_OAUTH_ACCOUNT_LINKING, falling back to ALLOW_GOOGLE_ACCOUNT_LINKING, its name
+// from when Google was the only provider
+pub fn oauth_account_linking(lookup: impl Fn(&str) -> Option<String>) -> bool {
+    let value = match lookup("ALLOW_OAUTH_ACCOUNT_LINKING").filter(|value| !value.is_empty()) {
+        Some(value) => value,
+        None => match lookup("ALLOW_GOOGLE_ACCOUNT_LINKING") {
+            Some(value) => {
+                tracing::warn!(
+                    "ALLOW_GOOGLE_ACCOUNT_LINKING is deprecated, use ALLOW_OAUTH_ACCOUNT_LINKING"
+                );
+                value
+            }
+            None => return false,
+        },
+    };
+
+    value == "true" || value == "1"
+}
+
+// The configured allowlist, or else the origin of the frontend URL itself. Warns once at
+// startup if the URL is off the list, since the OAuth callback then sends every sign-in
+// to the default frontend.
+pub fn frontend_allowed_origins(frontend_url: &str, configured: Vec<String>) -> Vec<String> {
+    let origins = if configured.is_empty() {
+        url_origin(frontend_url)
+            .or_else(|| url_origin(DEFAULT_FRONTEND_URL))
+            .into_iter()
+            .collect()
+    } else {
+        configured
+    };
+
+    if allowed_frontend_url(frontend_url, &origins).is_none() {
+        tracing::warn!(
+            "FRONTEND_URL {:?} is not in FRONTEND_ALLOWED_ORIGINS, OAuth sign-ins will be sent to {}",
+            frontend_url,
+            DEFAULT_FRONTEND_URL
+        );
+    }
+
+    origins
+}
+
+fn flag(name: &str) -> bool {
+    env::var(name)
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+}
+
+fn non_empty(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn credentials(username: &str, password: &str) -> Option<(String, String)> {
+    match (env::var(username), env::var(password)) {
+        (Ok(username), Ok(password)) => Some((username, password)),
+        _ => None,
+    }
+}
+
+impl AppConfig {
+    // Defaults for everything but the Google client: no other sign-in providers, local
+    // storage, no SMTP, no metrics
+    pub fn new(google: OAuthConfig) -> Self {
+        Self {
+            oauth_providers: HashMap::from([("google".to_string(), google)]),
+            oauth_timeout: Duration::from_secs(10),
+            allow_oauth_account_linking: false,
+            frontend_url: DEFAULT_FRONTEND_URL.to_string(),
+            frontend_allowed_origins: url_origin(DEFAULT_FRONTEND_URL).into_iter().collect(),
+            submission_allowed_domains: Vec::new(),
+            mailer: None,
+            admin_notification_email: None,
+            password_policy: PasswordPolicy::default(),
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+            storage: StorageConfig::Local,
+            uploads_dir: PathBuf::from("uploads"),
+            metrics_enabled: false,
+            trust_proxy_headers: false,
+            email_check_rate_limit: 10,
+            contact_rate_limit: 5,
+            contact_honeypot_field: None,
+            leaderboard_ws_max_connections: 100,
+            leaderboard_cache_ttl: Duration::from_secs(30),
+            allowed_origins: Vec::new(),
+            allow_any_origin: false,
+            body_limits: BodyLimits::default(),
+            contact_limits: ContactLimits::default(),
+            admin_bootstrap: None,
+            auth_cookie: None,
+            remember_me_lifetime: DEFAULT_REMEMBER_ME_LIFETIME,
+            featured_resources_limit: 6,
+        }
+    }
+
+    // Panics when a required variable is missing and errors on a weak JWT_SECRET or an
+    // unusable SMTP setup, so a misconfigured server fails at startup
+    pub fn from_env() -> anyhow::Result<Self> {
+        validate_jwt_secret(env::var("JWT_SECRET").ok().as_deref())?;
+
+        let google = OAuthConfig::google(
+            env::var("GOOGLE_CLIENT_ID").expect("GOOGLE_CLIENT_ID must be set"),
+            env::var("GOOGLE_CLIENT_SECRET").expect("GOOGLE_CLIENT_SECRET must be set"),
+            env::var("GOOGLE_REDIRECT_URI").expect("GOOGLE_REDIRECT_URI must be set"),
+        );
+        let defaults = Self::new(google);
+
+        // GitHub sign-in is offered only when its client is configured
+        let mut oauth_providers = defaults.oauth_providers.clone();
+        if let (Some(client_id), Some(client_secret), Some(redirect_uri)) = (
+            non_empty("GITHUB_CLIENT_ID"),
+            non_empty("GITHUB_CLIENT_SECRET"),
+            non_empty("GITHUB_REDIRECT_URI"),
+        ) {
+            oauth_providers.insert(
+                "github".to_string(),
+                OAuthConfig::github(client_id, client_secret, redirect_uri),
+            );
+        }
+
+        // Comma-separated origins; unset allows just FRONTEND_URL's own origin
+        let frontend_allowed_origins: Vec<String> = env::var("FRONTEND_ALLOWED_ORIGINS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|origin| !origin.trim().is_empty())
+            .filter_map(|origin| {
+                let parsed = url_origin(origin);
+                if parsed.is_none() {
+                    tracing::warn!("Ignoring invalid frontend origin: {}", origin);
+                }
+                parsed
+            })
+            .collect();
+
+        // Comma-separated list of hosts submissions may link to
+        let submission_allowed_domains = env::var("SUBMISSION_ALLOWED_DOMAINS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|d| d.trim().trim_start_matches('.').to_lowercase())
+            .filter(|d| !d.is_empty())
+            .collect();
+
+        let mailer = match SmtpConfig::from_env()? {
+            Some(smtp) => {
+                let mailer = SmtpMailer::new(&smtp.host, smtp.port, smtp.credentials, &smtp.from)
+                    .map_err(|e| anyhow::anyhow!("Invalid SMTP configuration: {e}"))?;
+                Some(Arc::new(mailer) as Arc<dyn Mailer>)
+            }
+            None => None,
+        };
+
+        // Uploads go to the local UPLOADS_DIR (uploads/ by default) unless STORAGE_BACKEND=s3
+        let use_s3 = env::var("STORAGE_BACKEND")
+            .map(|backend| backend.eq_ignore_ascii_case("s3"))
+            .unwrap_or(false);
+        let storage = if use_s3 {
+            StorageConfig::S3 {
+                bucket: env::var("S3_BUCKET").expect("S3_BUCKET must be set when using S3"),
+                region: env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                endpoint: non_empty("S3_ENDPOINT"),
+                credentials: credentials("S3_ACCESS_KEY_ID", "S3_SECRET_ACCESS_KEY"),
+                public_url: env::var("S3_PUBLIC_URL")
+                    .expect("S3_PUBLIC_URL must be set when using S3"),
+            }
+        } else {
+            StorageConfig::Local
+        };
+
+        // Both are needed; the endpoint stays disabled otherwise
+        let admin_bootstrap = match (
+            non_empty("BOOTSTRAP_ADMIN_EMAIL"),
+            non_empty("BOOTSTRAP_ADMIN_TOKEN"),
+        ) {
+            (Some(email), Some(token)) => Some(AdminBootstrapConfig {
+                email: canonical_email(&email),
+                token,
+            }),
+            _ => None,
+        };
+
+        let frontend_url = non_empty("FRONTEND_URL").unwrap_or(defaults.frontend_url.clone());
+
+        // AUTH_COOKIE_SAME_SITE only matters once AUTH_COOKIE is on
+        let auth_cookie = flag("AUTH_COOKIE").then(|| match non_empty("AUTH_COOKIE_SAME_SITE") {
+            Some(value) => SameSite::parse(&value).unwrap_or_else(|| {
+                tracing::warn!("Invalid AUTH_COOKIE_SAME_SITE {:?}, using Lax", value);
+                SameSite::Lax
+            }),
+            None => SameSite::Lax,
+        });
+
+        Ok(Self {
+            oauth_providers,
+            oauth_timeout: env::var("OAUTH_TIMEOUT_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.oauth_timeout),
+            allow_oauth_account_linking: oauth_account_linking(|name| env::var(name).ok()),
+            frontend_allowed_origins: self::frontend_allowed_origins(
+                &frontend_url,
+                frontend_allowed_origins,
+            ),
+            frontend_url,
+            submission_allowed_domains,
+            mailer,
+            admin_notification_email: non_empty("ADMIN_NOTIFICATION_EMAIL"),
+            password_policy: PasswordPolicy::from_env(),
+            bcrypt_cost: parse_bcrypt_cost(env::var("BCRYPT_COST").ok().as_deref()),
+            storage,
+            uploads_dir: non_empty("UPLOADS_DIR")
+                .map(PathBuf::from)
+                .unwrap_or(defaults.uploads_dir.clone()),
+            metrics_enabled: flag("METRICS_ENABLED"),
+            trust_proxy_headers: flag("TRUST_PROXY_HEADERS"),
+            email_check_rate_limit: env::var("EMAIL_CHECK_RATE_LIMIT")
+                .ok()
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(defaults.email_check_rate_limit),
+            contact_rate_limit: env::var("CONTACT_RATE_LIMIT")
+                .ok()
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(defaults.contact_rate_limit),
+            contact_honeypot_field: non_empty("CONTACT_HONEYPOT_FIELD"),
+            leaderboard_ws_max_connections: env::var("LEADERBOARD_WS_MAX_CONNECTIONS")
+                .ok()
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(defaults.leaderboard_ws_max_connections),
+            leaderboard_cache_ttl: env::var("LEADERBOARD_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.leaderboard_cache_ttl),
+            allowed_origins: parse_allowed_origins(
+                &env::var("ALLOWED_ORIGINS").unwrap_or_default(),
+            ),
+            allow_any_origin: flag("CORS_ALLOW_ANY_ORIGIN"),
+            body_limits: BodyLimits::from_env(),
+            contact_limits: ContactLimits::from_env(),
+            admin_bootstrap,
+            auth_cookie,
+            remember_me_lifetime: parse_remember_me_lifetime(
+                env::var("REMEMBER_ME_LIFETIME_SECS").ok().as_deref(),
+            ),
+            featured_resources_limit: env::var("FEATURED_RESOURCES_LIMIT")
+                .ok()
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(defaults.featured_resources_limit),
+        })
+    }
+}
@@ -0,0 +1,89 @@
+use sqlx::postgres::PgPoolOptions;
+use std::time::Duration;
+
+const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+const DEFAULT_MIN_CONNECTIONS: u32 = 0;
+const DEFAULT_ACQUIRE_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            min_connections: DEFAULT_MIN_CONNECTIONS,
+            acquire_timeout: Duration::from_secs(DEFAULT_ACQUIRE_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl PoolConfig {
+    // Reads DB_MAX_CONNECTIONS, DB_MIN_CONNECTIONS and DB_ACQUIRE_TIMEOUT_SECS
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    // Builds the config from any variable lookup; invalid values log a warning and
+    // fall back to the defaults instead of aborting startup
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+
+        let mut max_connections =
+            parse_var(&lookup, "DB_MAX_CONNECTIONS", defaults.max_connections);
+        let mut min_connections =
+            parse_var(&lookup, "DB_MIN_CONNECTIONS", defaults.min_connections);
+        let acquire_timeout_secs = parse_var(
+            &lookup,
+            "DB_ACQUIRE_TIMEOUT_SECS",
+            DEFAULT_ACQUIRE_TIMEOUT_SECS,
+        );
+
+        if max_connections == 0 {
+            tracing::warn!("DB_MAX_CONNECTIONS must be at least 1, using default");
+            max_connections = defaults.max_connections;
+        }
+
+        if min_connections > max_connections {
+            tracing::warn!(
+                "DB_MIN_CONNECTIONS ({}) exceeds DB_MAX_CONNECTIONS ({}), capping it",
+                min_connections,
+                max_connections
+            );
+            min_connections = max_connections;
+        }
+
+        Self {
+            max_connections,
+            min_connections,
+            acquire_timeout: Duration::from_secs(acquire_timeout_secs),
+        }
+    }
+
+    pub fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+    }
+}
+
+// Returns the parsed value, or the default when unset or invalid
+fn parse_var<T: std::str::FromStr>(
+    lookup: &impl Fn(&str) -> Option<String>,
+    name: &str,
+    default: T,
+) -> T {
+    let Some(value) = lookup(name).filter(|value| !value.trim().is_empty()) else {
+        return default;
+    };
+
+    value.trim().parse().unwrap_or_else(|_| {
+        tracing::warn!("Invalid {} value {:?}, using default", name, value);
+        default
+    })
+}
@@ -1,68 +1,270 @@
 use axum::{
     Json,
-    http::StatusCode,
+    extract::{
+        multipart::{MultipartError, MultipartRejection},
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+    },
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde_json::json;
+use std::collections::HashMap;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Authentication failed")]
     AuthError,
+    // Both still answer 401; the codes let clients refresh an expired session quietly
+    #[error("Token expired")]
+    TokenExpired,
+    #[error("Invalid token")]
+    TokenInvalid,
+    #[error("Cookie-authenticated write without the CSRF header")]
+    CrossSiteRequest,
     #[error("Database error")]
     DatabaseError(#[from] sqlx::Error),
     #[error("Validation error: {0}")]
     ValidationError(String),
+    #[error("Validation failed for {} field(s)", .0.len())]
+    ValidationErrors(HashMap<String, String>),
     #[error("Bad request: {0}")]
     BadRequest(String),
     #[error("User already exists")]
     UserExists,
+    #[error("Email belongs to an account that isn't linked to this sign-in provider")]
+    AccountLinkingDisabled,
+    #[error("Account is already linked to a different sign-in account")]
+    OAuthAccountConflict,
     #[error("Resource not found")]
     NotFound,
+    #[error("Resource is hidden")]
+    ResourceHidden,
+    #[error("Request body too large")]
+    PayloadTooLarge,
+    #[error("Too many requests")]
+    TooManyRequests,
+    #[error("Method not allowed")]
+    MethodNotAllowed,
+    #[error("Upstream request timed out")]
+    UpstreamTimeout,
     #[error("Internal server error")]
     InternalError(#[from] anyhow::Error),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match &self {
+        // `code` is a stable identifier for clients; `message` is for display and may change
+        let (status, code, error_message) = match &self {
             AppError::AuthError => (
                 StatusCode::UNAUTHORIZED,
+                "AUTH_FAILED",
                 "Authentication failed".to_string(),
             ),
-            AppError::NotFound => (StatusCode::NOT_FOUND, "Resource not found".to_string()),
+            AppError::TokenExpired => (
+                StatusCode::UNAUTHORIZED,
+                "TOKEN_EXPIRED",
+                "Your session has expired, please sign in again".to_string(),
+            ),
+            AppError::TokenInvalid => (
+                StatusCode::UNAUTHORIZED,
+                "TOKEN_INVALID",
+                "Invalid authentication token".to_string(),
+            ),
+            AppError::CrossSiteRequest => (
+                StatusCode::FORBIDDEN,
+                "CSRF_HEADER_MISSING",
+                "Requests authenticated by cookie must send the X-Requested-With header"
+                    .to_string(),
+            ),
+            AppError::NotFound => (
+                StatusCode::NOT_FOUND,
+                "NOT_FOUND",
+                "Resource not found".to_string(),
+            ),
+            AppError::ResourceHidden => (
+                StatusCode::NOT_FOUND,
+                "RESOURCE_HIDDEN",
+                "Resource is not available".to_string(),
+            ),
+            AppError::PayloadTooLarge => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "PAYLOAD_TOO_LARGE",
+                "Request body is too large".to_string(),
+            ),
+            AppError::TooManyRequests => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "RATE_LIMITED",
+                "Too many requests, please try again later".to_string(),
+            ),
+            AppError::MethodNotAllowed => (
+                StatusCode::METHOD_NOT_ALLOWED,
+                "METHOD_NOT_ALLOWED",
+                "Method not allowed for this route".to_string(),
+            ),
+            AppError::UpstreamTimeout => (
+                StatusCode::GATEWAY_TIMEOUT,
+                "UPSTREAM_TIMEOUT",
+                "Your sign-in provider did not respond in time, please try again".to_string(),
+            ),
             AppError::DatabaseError(err) => match err {
                 sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
-                    if db_err.constraint() == Some("users_email_key") {
-                        (StatusCode::CONFLICT, "User already exists".to_string())
-                    } else {
-                        (
+                    match db_err.constraint() {
+                        Some("users_email_key" | "users_email_lower_key") => (
+                            StatusCode::CONFLICT,
+                            "USER_EXISTS",
+                            "User already exists".to_string(),
+                        ),
+                        // The provider account got linked to another user concurrently
+                        Some("users_google_id_key" | "users_github_id_key") => (
+                            StatusCode::CONFLICT,
+                            "OAUTH_ACCOUNT_CONFLICT",
+                            "This sign-in account is already linked to another user".to_string(),
+                        ),
+                        // Another request claimed the week between our check and the write
+                        Some("challenges_live_week_key") => (
+                            StatusCode::BAD_REQUEST,
+                            "BAD_REQUEST",
+                            "That week is already used by another challenge".to_string(),
+                        ),
+                        _ => (
                             StatusCode::INTERNAL_SERVER_ERROR,
+                            "INTERNAL_ERROR",
                             "Internal server error".to_string(),
-                        )
+                        ),
                     }
                 }
                 _ => (
                     StatusCode::INTERNAL_SERVER_ERROR,
+                    "INTERNAL_ERROR",
                     "Internal server error".to_string(),
                 ),
             },
-            AppError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
-            AppError::UserExists => (StatusCode::CONFLICT, "User already exists".to_string()),
+            AppError::ValidationError(msg) => {
+                (StatusCode::BAD_REQUEST, "VALIDATION_ERROR", msg.clone())
+            }
+            AppError::ValidationErrors(_) => (
+                StatusCode::BAD_REQUEST,
+                "VALIDATION_ERROR",
+                "Some fields are invalid".to_string(),
+            ),
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "BAD_REQUEST", msg.clone()),
+            AppError::UserExists => (
+                StatusCode::CONFLICT,
+                "USER_EXISTS",
+                "User already exists".to_string(),
+            ),
+            AppError::AccountLinkingDisabled => (
+                StatusCode::CONFLICT,
+                "ACCOUNT_LINKING_DISABLED",
+                "An account with this email already exists. Log in with your email and password instead.".to_string(),
+            ),
+            AppError::OAuthAccountConflict => (
+                StatusCode::CONFLICT,
+                "OAUTH_ACCOUNT_CONFLICT",
+                "This account is already linked to a different sign-in account".to_string(),
+            ),
             AppError::InternalError(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
                 "Internal server error".to_string(),
             ),
         };
 
         tracing::error!("Error occurred: {:?}", self);
 
-        let body = Json(json!({
+        let mut body = json!({
+            "code": code,
             "message": error_message
-        }));
+        });
+
+        // Field-level failures are reported as {"errors": {"field": "reason"}}
+        if let AppError::ValidationErrors(errors) = &self {
+            body["errors"] = json!(errors);
+        }
+
+        let body = Json(body);
 
         (status, body).into_response()
     }
 }
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::MissingJsonContentType(_) => AppError::BadRequest(
+                "Expected a JSON body with Content-Type: application/json".to_string(),
+            ),
+            rejection if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                AppError::PayloadTooLarge
+            }
+            // Syntax and data errors carry serde's description, e.g. "missing field `email`"
+            rejection => AppError::BadRequest(rejection.body_text()),
+        }
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        match rejection {
+            // e.g. "Invalid URL: Cannot parse `abc` to a `i32`"
+            PathRejection::FailedToDeserializePathParams(e) => AppError::BadRequest(e.body_text()),
+            // A handler asking for params its route doesn't have is our bug, not the client's
+            rejection => AppError::InternalError(anyhow::anyhow!(rejection.body_text())),
+        }
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        // e.g. "Failed to deserialize query string: missing field `email`"
+        AppError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<MultipartError> for AppError {
+    fn from(error: MultipartError) -> Self {
+        if error.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            AppError::PayloadTooLarge
+        } else {
+            AppError::BadRequest(error.body_text())
+        }
+    }
+}
+
+impl From<MultipartRejection> for AppError {
+    fn from(rejection: MultipartRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
+
+// RequestBodyLimitLayer answers oversized requests with a plain-text 413 before any
+// handler runs; rewrite those into our JSON error shape
+pub async fn json_payload_too_large(response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return AppError::PayloadTooLarge.into_response();
+    }
+
+    response
+}
+
+// The router answers a known path with an unregistered method by a bare 405 that lists
+// the registered methods in Allow; give it our JSON shape and keep that header
+pub async fn json_method_not_allowed(response: Response) -> Response {
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+        return response;
+    }
+
+    let mut json_response = AppError::MethodNotAllowed.into_response();
+    if let Some(allow) = response.headers().get(header::ALLOW) {
+        json_response
+            .headers_mut()
+            .insert(header::ALLOW, allow.clone());
+    }
+    json_response
+}
@@ -0,0 +1,88 @@
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequest, FromRequestParts, Multipart, Request},
+    http::{header, request::Parts},
+};
+use serde::de::DeserializeOwned;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use crate::{AppState, error::AppError};
+
+// Drop-in replacement for `axum::Json` as an extractor: malformed or mistyped bodies are
+// reported through AppError instead of axum's plain-text rejection
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct AppJson<T>(pub T);
+
+// Drop-in replacement for `axum::extract::Path`: ids that don't parse (e.g. `/resources/abc`)
+// are a 400 in our JSON shape rather than axum's plain-text rejection
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(AppError))]
+pub struct AppPath<T>(pub T);
+
+// Drop-in replacement for `axum::extract::Query`: a missing or malformed parameter
+// (e.g. `/auth/email-available` without `email`) is a 400 in our JSON shape
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct AppQuery<T>(pub T);
+
+// A body that may be sent either as JSON or as multipart form data, picked by
+// Content-Type. Lets API clients skip multipart where the browser forms need it for uploads.
+pub enum JsonOrMultipart<T> {
+    Json(T),
+    Multipart(Multipart),
+}
+
+#[async_trait]
+impl<T> FromRequest<AppState> for JsonOrMultipart<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &AppState) -> Result<Self, AppError> {
+        let is_json = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.trim_start().starts_with("application/json"));
+
+        if is_json {
+            let AppJson(body) = AppJson::from_request(req, state).await?;
+            Ok(JsonOrMultipart::Json(body))
+        } else {
+            Ok(JsonOrMultipart::Multipart(
+                Multipart::from_request(req, state).await?,
+            ))
+        }
+    }
+}
+
+// Address of the calling client. Behind our nginx the peer is the proxy, so X-Real-IP
+// is used instead when TRUST_PROXY_HEADERS is enabled.
+pub struct ClientIp(pub IpAddr);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        if state.trust_proxy_headers
+            && let Some(ip) = parts
+                .headers
+                .get("x-real-ip")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+        {
+            return Ok(ClientIp(ip));
+        }
+
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+        Ok(ClientIp(ip))
+    }
+}
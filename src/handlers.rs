@@ -1,57 +1,158 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Redirect},
+    extract::{
+        State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
 };
-use bcrypt::{DEFAULT_COST, hash, verify};
+use bcrypt::{hash, verify};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
 use crate::{
-    AppState,
-    auth::{AdminUser, AuthUser, create_token},
+    AppState, audit,
+    auth::{
+        AdminUser, AuthUser, Claims, ModeratorUser, OptionalAuthUser, ROLES, TOKEN_LIFETIME_SECS,
+        auth_cookie, create_token, create_token_with_lifetime,
+    },
+    config::{DEFAULT_FRONTEND_URL, OAuthConfig},
     error::AppError,
+    extract::{AppJson, AppPath, AppQuery, ClientIp, JsonOrMultipart},
+    images::{ImageKind, THUMBNAIL_WIDTH, make_thumbnail, thumbnail_key, verify_image},
+    live::ConnectionSlot,
+    mailer::EmailMessage,
     models::*,
+    notifications::{self, notify},
+    oauth,
+    ranking::recompute_ranks,
+    storage::FileStorage,
+    telemetry::record_pool_metrics,
+    validation::{
+        FieldErrors, allowed_frontend_url, canonical_email, normalize_email, normalize_http_url,
+        normalize_phone, sanitize_file_name, validate_password,
+    },
 };
 
+pub async fn route_not_found() -> AppError {
+    AppError::NotFound
+}
+
 #[derive(Serialize)]
 pub struct HealthResponse {
+    status: String,
+    version: &'static str,
+    #[serde(rename = "uptimeSeconds")]
+    uptime_seconds: u64,
+}
+
+#[derive(Serialize)]
+pub struct ReadinessResponse {
     status: String,
     database: String,
+    version: &'static str,
+    #[serde(rename = "uptimeSeconds")]
+    uptime_seconds: u64,
+    pool: PoolStatsResponse,
 }
 
-pub async fn health_check(
-    State(state): State<AppState>,
-) -> Result<Json<HealthResponse>, StatusCode> {
-    // Check database connection
-    let db_status = match sqlx::query("SELECT 1").fetch_one(&state.pool).await {
-        Ok(_) => "healthy",
-        Err(_) => "unhealthy",
-    };
+#[derive(Serialize)]
+pub struct PoolStatsResponse {
+    size: u32,
+    idle: usize,
+    active: usize,
+}
 
-    let response = HealthResponse {
-        status: if db_status == "healthy" {
-            "ok".to_string()
-        } else {
-            "degraded".to_string()
+// Liveness: answers as long as the process is up, without touching the database
+pub async fn health_check(State(state): State<AppState>) -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_seconds: state.started_at.elapsed().as_secs(),
+    })
+}
+
+// Readiness: 503 while the database is unreachable
+pub async fn readiness_check(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let db_healthy = sqlx::query("SELECT 1").fetch_one(&state.pool).await.is_ok();
+
+    let size = state.pool.size();
+    let idle = state.pool.num_idle();
+
+    let response = ReadinessResponse {
+        status: if db_healthy { "ok" } else { "degraded" }.to_string(),
+        database: if db_healthy { "healthy" } else { "unhealthy" }.to_string(),
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_seconds: state.started_at.elapsed().as_secs(),
+        pool: PoolStatsResponse {
+            size,
+            idle,
+            active: (size as usize).saturating_sub(idle),
         },
-        database: db_status.to_string(),
     };
 
-    if db_status == "healthy" {
-        Ok(Json(response))
+    let status = if db_healthy {
+        StatusCode::OK
     } else {
-        Err(StatusCode::SERVICE_UNAVAILABLE)
-    }
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(response))
+}
+
+// Prometheus exposition; 404 unless METRICS_ENABLED is set
+pub async fn get_metrics(State(state): State<AppState>) -> Result<String, AppError> {
+    let handle = state.metrics.as_ref().ok_or(AppError::NotFound)?;
+
+    record_pool_metrics(&state.pool);
+
+    Ok(handle.render())
+}
+
+// bcrypt is slow on purpose (see parse_bcrypt_cost), so it runs on the blocking pool
+// instead of holding up an async worker and every request queued behind it
+async fn hash_password(password: &str, cost: u32) -> Result<String, AppError> {
+    let password = password.to_owned();
+    tokio::task::spawn_blocking(move || hash(password.as_bytes(), cost))
+        .await
+        .map_err(|e| AppError::InternalError(e.into()))?
+        .map_err(|e| AppError::InternalError(e.into()))
+}
+
+async fn verify_password(password: &str, password_hash: &str) -> Result<bool, AppError> {
+    let (password, password_hash) = (password.to_owned(), password_hash.to_owned());
+    tokio::task::spawn_blocking(move || verify(password.as_bytes(), &password_hash))
+        .await
+        .map_err(|e| AppError::InternalError(e.into()))?
+        .map_err(|e| AppError::InternalError(e.into()))
 }
 
 pub async fn signup(
     State(state): State<AppState>,
-    Json(req): Json<RegisterRequest>,
-) -> Result<Json<AuthResponse>, AppError> {
-    let existing_user = sqlx::query("SELECT id FROM users WHERE email = $1")
-        .bind(&req.email)
+    AppJson(req): AppJson<RegisterRequest>,
+) -> Result<(HeaderMap, Json<AuthResponse>), AppError> {
+    let mut errors = FieldErrors::default();
+    let full_name = errors.require("fullName", Some(req.full_name));
+    let email = errors.check("email", normalize_email(&req.email));
+    let phone_num = errors.check("phoneNum", normalize_phone(&req.phone_num));
+    errors.check(
+        "password",
+        validate_password(&req.password, &state.password_policy),
+    );
+
+    let (Some(full_name), Some(email), Some(phone_num)) = (full_name, email, phone_num) else {
+        return Err(errors.into());
+    };
+    errors.into_result()?;
+
+    let existing_user = sqlx::query("SELECT id FROM users WHERE LOWER(email) = $1")
+        .bind(&email)
         .fetch_optional(&state.pool)
         .await?;
 
@@ -59,11 +160,13 @@ pub async fn signup(
         return Err(AppError::UserExists);
     }
 
-    let password_hash = hash(req.password.as_bytes(), DEFAULT_COST)
-        .map_err(|e| AppError::InternalError(e.into()))?;
+    let password_hash = hash_password(&req.password, state.bcrypt_cost).await?;
 
     let user_id = Uuid::new_v4();
 
+    // The user and their stats row are created together or not at all
+    let mut tx = state.pool.begin().await?;
+
     let user: User = sqlx::query_as(
         r#"
         INSERT INTO users (id, email, password_hash, full_name, phone_num, created_at)
@@ -72,442 +175,2318 @@ pub async fn signup(
         "#,
     )
     .bind(user_id)
-    .bind(&req.email)
+    .bind(&email)
     .bind(Some(password_hash))
-    .bind(req.full_name)
-    .bind(req.phone_num)
-    .fetch_one(&state.pool)
+    .bind(full_name.trim())
+    .bind(phone_num)
+    .fetch_one(&mut *tx)
     .await?;
 
     sqlx::query(
         "INSERT INTO user_stats (user_id, created_at, updated_at) VALUES ($1, NOW(), NOW())",
     )
     .bind(user_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
-    let token = create_token(user.id)?;
+    tx.commit().await?;
+
+    let (token, claims) = create_token(user.id)?;
+
+    Ok((
+        session_cookie(&state, &token, &claims),
+        Json(AuthResponse {
+            token,
+            expires_at: claims.exp,
+            expires_in: claims.expires_in(),
+            user: UserResponse {
+                id: user.id,
+                full_name: user.full_name,
+                email: user.email,
+                image: user.image,
+                role: user.role,
+            },
+        }),
+    ))
+}
+
+// Set-Cookie carrying the token when AUTH_COOKIE is on, nothing otherwise
+fn session_cookie(state: &AppState, token: &str, claims: &Claims) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(same_site) = state.auth_cookie {
+        headers.insert(
+            header::SET_COOKIE,
+            auth_cookie(token, claims.expires_in(), same_site),
+        );
+    }
+    headers
+}
 
-    Ok(Json(AuthResponse {
-        token,
-        user: UserResponse {
-            id: user.id,
-            full_name: user.full_name,
-            email: user.email,
-            image: user.image,
-            role: user.role,
-        },
-    }))
+// Lets the signup form flag a taken email early. Signup's 409 already reveals this,
+// but the endpoint is rate limited per IP to slow down enumeration.
+pub async fn check_email_available(
+    ClientIp(ip): ClientIp,
+    State(state): State<AppState>,
+    AppQuery(query): AppQuery<EmailAvailableQuery>,
+) -> Result<Json<EmailAvailableResponse>, AppError> {
+    if !state.email_check_limiter.check(ip) {
+        return Err(AppError::TooManyRequests);
+    }
+
+    let email = normalize_email(&query.email)?;
+
+    let (taken,): (bool,) =
+        sqlx::query_as("SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(email) = $1)")
+            .bind(&email)
+            .fetch_one(&state.pool)
+            .await?;
+
+    Ok(Json(EmailAvailableResponse { available: !taken }))
 }
 
 pub async fn login(
     State(state): State<AppState>,
-    Json(req): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, AppError> {
-    let user: User = sqlx::query_as("SELECT * FROM users WHERE email = $1")
-        .bind(req.email)
+    AppJson(req): AppJson<LoginRequest>,
+) -> Result<(HeaderMap, Json<AuthResponse>), AppError> {
+    let user: User = sqlx::query_as("SELECT * FROM users WHERE LOWER(email) = $1")
+        .bind(canonical_email(&req.email))
         .fetch_optional(&state.pool)
         .await?
         .ok_or(AppError::AuthError)?;
 
-    // Check if user has a password hash (not a Google OAuth-only user)
+    // Check if user has a password hash (not an OAuth-only user)
     let password_hash = user.password_hash.as_ref().ok_or_else(|| {
         AppError::BadRequest(
-            "This account uses Google Sign-In. Please use the 'Sign in with Google' button."
+            "This account doesn't have a password. Please use your sign-in provider instead."
                 .to_string(),
         )
     })?;
 
-    if !verify(req.password.as_bytes(), password_hash)
-        .map_err(|e| AppError::InternalError(e.into()))?
-    {
+    if !verify_password(&req.password, password_hash).await? {
         return Err(AppError::AuthError);
     }
 
-    let token = create_token(user.id)?;
+    // Bring hashes made under an older BCRYPT_COST up to date while we have the plaintext
+    upgrade_password_hash(&state, user.id, &req.password, password_hash).await;
 
-    Ok(Json(AuthResponse {
-        token,
-        user: UserResponse {
-            id: user.id,
-            full_name: user.full_name,
-            email: user.email,
-            image: user.image,
-            role: user.role,
-        },
-    }))
+    // Remembered logins outlive the usual day, see AppConfig::remember_me_lifetime
+    let lifetime = if req.remember_me {
+        state.remember_me_lifetime.as_secs() as i64
+    } else {
+        TOKEN_LIFETIME_SECS
+    };
+    let (token, claims) = create_token_with_lifetime(user.id, lifetime)?;
+
+    Ok((
+        session_cookie(&state, &token, &claims),
+        Json(AuthResponse {
+            token,
+            expires_at: claims.exp,
+            expires_in: claims.expires_in(),
+            user: UserResponse {
+                id: user.id,
+                full_name: user.full_name,
+                email: user.email,
+                image: user.image,
+                role: user.role,
+            },
+        }),
+    ))
 }
 
-pub async fn get_leaderboards(
-    State(state): State<AppState>,
-) -> Result<Json<Vec<LeaderboardResponse>>, AppError> {
-    // Get top 10 users by points
-    let entries: Vec<LeaderboardEntry> =
-        sqlx::query_as("SELECT full_name as name, points FROM users ORDER BY points DESC LIMIT 10")
-            .fetch_all(&state.pool)
-            .await?;
+// Clears the auth cookie. Tokens are stateless, so header clients just discard theirs.
+pub async fn logout(State(state): State<AppState>) -> (StatusCode, HeaderMap) {
+    let mut headers = HeaderMap::new();
+    if let Some(same_site) = state.auth_cookie {
+        headers.insert(header::SET_COOKIE, auth_cookie("", 0, same_site));
+    }
+    (StatusCode::NO_CONTENT, headers)
+}
 
-    // Return a single leaderboard with top 10 users
-    let response = LeaderboardResponse {
-        id: 1,
-        title: "Top Users".to_string(),
-        entries,
+// Re-hashes a just-verified password if its hash is cheaper than the configured cost.
+// Failures are logged only; the user already proved their password.
+async fn upgrade_password_hash(
+    state: &AppState,
+    user_id: Uuid,
+    password: &str,
+    current_hash: &str,
+) {
+    let cost = match current_hash.parse::<bcrypt::HashParts>() {
+        Ok(parts) => parts.get_cost(),
+        Err(e) => {
+            tracing::warn!("Unreadable password hash for user {}: {}", user_id, e);
+            return;
+        }
     };
+    if cost >= state.bcrypt_cost {
+        return;
+    }
 
-    Ok(Json(vec![response]))
-}
-
-pub async fn get_resources(
-    State(state): State<AppState>,
-) -> Result<Json<Vec<ResourceListResponse>>, AppError> {
-    let resources: Vec<Resource> =
-        sqlx::query_as("SELECT * FROM resources WHERE visible = true ORDER BY id")
-            .fetch_all(&state.pool)
-            .await?;
+    let new_hash = match hash_password(password, state.bcrypt_cost).await {
+        Ok(new_hash) => new_hash,
+        Err(e) => {
+            tracing::warn!("Failed to rehash password for user {}: {:?}", user_id, e);
+            return;
+        }
+    };
 
-    let responses: Vec<ResourceListResponse> = resources
-        .into_iter()
-        .map(|r| ResourceListResponse {
-            id: r.id,
-            title: r.title,
-            provider: r.provider,
-            cover_image: r.cover_image,
-            instructor: InstructorResponse {
-                name: r.instructor_name,
-                image: r.instructor_image,
-            },
-        })
-        .collect();
+    // Skip the write if the password changed since we read it
+    let result =
+        sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2 AND password_hash = $3")
+            .bind(&new_hash)
+            .bind(user_id)
+            .bind(current_hash)
+            .execute(&state.pool)
+            .await;
+
+    match result {
+        Ok(_) => tracing::info!(
+            "Upgraded password hash for user {} from cost {} to {}",
+            user_id,
+            cost,
+            state.bcrypt_cost
+        ),
+        Err(e) => tracing::warn!(
+            "Failed to store rehashed password for user {}: {:?}",
+            user_id,
+            e
+        ),
+    }
+}
 
-    Ok(Json(responses))
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+    period: Option<LeaderboardPeriod>,
+    university: Option<String>,
+    major: Option<String>,
 }
 
-pub async fn get_resource_by_id(
-    State(state): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<Json<ResourceDetailResponse>, AppError> {
-    let resource: Resource =
-        sqlx::query_as("SELECT * FROM resources WHERE id = $1 AND visible = true")
-            .bind(id)
-            .fetch_optional(&state.pool)
-            .await?
-            .ok_or(AppError::NotFound)?;
+// Narrows a board to the users of one university and/or major
+#[derive(Default)]
+struct LeaderboardFilter {
+    university: Option<String>,
+    major: Option<String>,
+}
 
-    // Fetch a random quote from the quotes table
-    let quote: Option<Quote> =
-        sqlx::query_as("SELECT * FROM quotes WHERE visible = true ORDER BY RANDOM() LIMIT 1")
-            .fetch_optional(&state.pool)
-            .await?;
+impl LeaderboardFilter {
+    // Blank values mean no filter. Values that don't fit the columns could never match.
+    fn from_query(university: Option<String>, major: Option<String>) -> Result<Self, AppError> {
+        let clean = |field: &str, value: Option<String>| -> Result<Option<String>, AppError> {
+            let Some(value) = value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+            else {
+                return Ok(None);
+            };
+            if value.chars().count() > 255 {
+                return Err(AppError::ValidationError(format!(
+                    "{field} must be at most 255 characters"
+                )));
+            }
+            Ok(Some(value))
+        };
 
-    let quote_response = quote.map(|q| QuoteResponse {
-        text: q.text,
-        author: q.author,
-    });
+        Ok(Self {
+            university: clean("university", university)?,
+            major: clean("major", major)?,
+        })
+    }
 
-    Ok(Json(ResourceDetailResponse {
-        id: resource.id,
-        title: resource.title,
-        provider: resource.provider,
-        notion_url: resource.notion_url,
-        instructor: InstructorResponse {
-            name: resource.instructor_name,
-            image: resource.instructor_image,
-        },
-        quote: quote_response,
-    }))
+    fn is_empty(&self) -> bool {
+        self.university.is_none() && self.major.is_none()
+    }
 }
 
-pub async fn get_current_challenge(
-    _auth: AuthUser,
-    State(state): State<AppState>,
-) -> Result<Json<ChallengeResponse>, AppError> {
-    let challenge: Challenge = sqlx::query_as(
-        r#"
-        SELECT * FROM challenges 
-        WHERE visible = true 
-        AND (start_date IS NULL OR start_date <= NOW())
-        AND (end_date IS NULL OR end_date >= NOW())
-        ORDER BY created_at DESC 
-        LIMIT 1
-        "#,
-    )
-    .fetch_optional(&state.pool)
-    .await?
-    .ok_or(AppError::NotFound)?;
+// Exact, case-insensitive match on the filter, which is bound as $1 and $2
+const LEADERBOARD_FILTER_SQL: &str = r#"
+    ($1::TEXT IS NULL OR LOWER(university) = LOWER($1))
+    AND ($2::TEXT IS NULL OR LOWER(major) = LOWER($2))
+"#;
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaderboardPeriod {
+    Weekly,
+    Monthly,
+    #[default]
+    All,
+}
 
-    Ok(Json(ChallengeResponse {
-        id: challenge.id,
-        week: challenge.week,
-        title: challenge.title,
-        description: challenge.description,
-        challenge_url: challenge.challenge_url,
-    }))
+impl LeaderboardPeriod {
+    // Subquery yielding (id, name, points, image, created_at, university, major) per
+    // user for the period.
+    // Bounded periods sum the points ledger since the start of the current week/month.
+    fn source_sql(self) -> &'static str {
+        match self {
+            LeaderboardPeriod::Weekly => {
+                r#"
+                SELECT u.id, u.full_name AS name, COALESCE(SUM(p.delta), 0)::INTEGER AS points, u.image, u.created_at, u.university, u.major
+                FROM users u
+                LEFT JOIN points_history p ON p.user_id = u.id AND p.created_at >= date_trunc('week', NOW())
+                GROUP BY u.id
+                "#
+            }
+            LeaderboardPeriod::Monthly => {
+                r#"
+                SELECT u.id, u.full_name AS name, COALESCE(SUM(p.delta), 0)::INTEGER AS points, u.image, u.created_at, u.university, u.major
+                FROM users u
+                LEFT JOIN points_history p ON p.user_id = u.id AND p.created_at >= date_trunc('month', NOW())
+                GROUP BY u.id
+                "#
+            }
+            LeaderboardPeriod::All => {
+                "SELECT id, full_name AS name, points, image, created_at, university, major FROM users"
+            }
+        }
+    }
 }
 
-pub async fn get_challenge_leaderboard(
-    _auth: AuthUser,
-    State(state): State<AppState>,
-) -> Result<Json<Vec<ChallengeLeaderboardEntry>>, AppError> {
-    // Get top 10 users by points from users table
-    let entries: Vec<ChallengeLeaderboardEntry> = sqlx::query_as(
-        r#"
-        SELECT id, full_name as name, points, image
-        FROM users
-        ORDER BY points DESC
-        LIMIT 10
-        "#,
-    )
-    .fetch_all(&state.pool)
-    .await?;
+// Top 10 users by points for the period among those matching the filter
+async fn fetch_top_entries(
+    pool: &sqlx::PgPool,
+    period: LeaderboardPeriod,
+    filter: &LeaderboardFilter,
+) -> Result<Vec<ChallengeLeaderboardEntry>, AppError> {
+    let sql = format!(
+        "SELECT id, name, points, image FROM ({}) board WHERE {} ORDER BY points DESC, created_at ASC, id ASC LIMIT 10",
+        period.source_sql(),
+        LEADERBOARD_FILTER_SQL
+    );
 
-    Ok(Json(entries))
+    Ok(sqlx::query_as(&sql)
+        .bind(&filter.university)
+        .bind(&filter.major)
+        .fetch_all(pool)
+        .await?)
 }
 
-pub async fn get_user_profile(
-    auth: AuthUser,
-    State(state): State<AppState>,
-) -> Result<Json<UserProfileResponse>, AppError> {
-    let user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
-        .bind(auth.user_id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+// The unfiltered fetch_top_entries behind the in-memory cache; publish_leaderboard clears it
+async fn cached_top_entries(
+    state: &AppState,
+    period: LeaderboardPeriod,
+) -> Result<Arc<Vec<ChallengeLeaderboardEntry>>, AppError> {
+    if let Some(entries) = state.leaderboard_cache.get(period) {
+        return Ok(entries);
+    }
 
-    let stats: UserStats = sqlx::query_as("SELECT * FROM user_stats WHERE user_id = $1")
-        .bind(auth.user_id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let generation = state.leaderboard_cache.generation();
+    let entries =
+        Arc::new(fetch_top_entries(&state.pool, period, &LeaderboardFilter::default()).await?);
+    state
+        .leaderboard_cache
+        .store(period, generation, entries.clone());
 
-    Ok(Json(UserProfileResponse {
-        rank: user.rank,
-        name: user.full_name,
-        points: user.points,
-        image: user.image,
-        stats: UserStatsResponse {
-            best_subject: stats.best_subject,
-            improveable: stats.improveable,
-            quickest_hunter: stats.quickest_hunter,
-            challenges_taken: stats.challenges_taken,
-        },
-    }))
+    Ok(entries)
 }
 
-pub async fn create_contact(
-    State(state): State<AppState>,
-    Json(req): Json<ContactRequest>,
-) -> Result<Json<ContactResponse>, AppError> {
-    sqlx::query(
-        "INSERT INTO contact_messages (name, email, message, created_at) VALUES ($1, $2, $3, NOW())",
-    ).bind(req.name)
-    .bind(req.email)
-    .bind(req.message)
-    .execute(&state.pool)
-    .await?;
-
-    Ok(Json(ContactResponse {
-        success: true,
-        message: "Message sent successfully".to_string(),
-    }))
+// The public boards only show names and points
+fn public_entries(entries: &[ChallengeLeaderboardEntry]) -> Vec<LeaderboardEntry> {
+    entries
+        .iter()
+        .map(|entry| LeaderboardEntry {
+            name: entry.name.clone(),
+            points: entry.points,
+        })
+        .collect()
 }
 
-#[derive(Deserialize)]
-pub struct AdminResourceQuery {
-    #[serde(rename = "includeHidden")]
-    include_hidden: Option<bool>,
+// Helper function to find a user's position on the points leaderboard for a period.
+// Users outside the filter have no position.
+async fn fetch_leaderboard_position(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+    period: LeaderboardPeriod,
+    filter: &LeaderboardFilter,
+) -> Result<Option<LeaderboardPosition>, AppError> {
+    let sql = format!(
+        r#"
+        SELECT position, id, name, points, image
+        FROM (
+            SELECT id, name, points, image,
+                   ROW_NUMBER() OVER (ORDER BY points DESC, created_at ASC, id ASC) AS position
+            FROM ({}) board
+            WHERE {}
+        ) ranked
+        WHERE id = $3
+        "#,
+        period.source_sql(),
+        LEADERBOARD_FILTER_SQL
+    );
+
+    let position: Option<LeaderboardPosition> = sqlx::query_as(&sql)
+        .bind(&filter.university)
+        .bind(&filter.major)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(position)
 }
 
-pub async fn admin_get_resources(
-    _auth: AdminUser,
+pub async fn get_leaderboards(
+    OptionalAuthUser(auth): OptionalAuthUser,
     State(state): State<AppState>,
-    Query(query): Query<AdminResourceQuery>,
-) -> Result<Json<AdminItemsResponse<AdminResourceResponse>>, AppError> {
-    let include_hidden = query.include_hidden.unwrap_or(false);
+    AppQuery(query): AppQuery<LeaderboardQuery>,
+) -> Result<Json<Vec<LeaderboardResponse>>, AppError> {
+    let period = query.period.unwrap_or_default();
+    let filter = LeaderboardFilter::from_query(query.university, query.major)?;
 
-    let sql = if include_hidden {
-        "SELECT * FROM resources ORDER BY id"
+    // Only the unfiltered boards are cached
+    let entries = if filter.is_empty() {
+        public_entries(&cached_top_entries(&state, period).await?)
     } else {
-        "SELECT * FROM resources WHERE visible = true ORDER BY id"
+        public_entries(&fetch_top_entries(&state.pool, period, &filter).await?)
+    };
+
+    let current_user = match auth {
+        Some(auth) => {
+            fetch_leaderboard_position(&state.pool, auth.user_id, period, &filter).await?
+        }
+        None => None,
     };
 
-    let resources: Vec<Resource> = sqlx::query_as(sql).fetch_all(&state.pool).await?;
+    let leaderboards = fetch_leaderboards(&state.pool).await?;
 
-    let responses: Vec<AdminResourceResponse> = resources
+    // Every board ranks users by the same points for now
+    let response = leaderboards
         .into_iter()
-        .map(|r| AdminResourceResponse {
-            id: r.id,
-            title: r.title,
-            provider: r.provider,
-            cover_image: r.cover_image,
-            notion_url: r.notion_url,
-            instructor: Some(AdminInstructorResponse {
-                name: r.instructor_name,
-                image: r.instructor_image,
-            }),
-            quote: None, // Quotes are now in a separate table
-            visible: r.visible,
-            created_at: r.created_at,
-            updated_at: r.updated_at,
+        .map(|leaderboard| LeaderboardResponse {
+            id: leaderboard.id,
+            title: leaderboard.title,
+            entries: entries.clone(),
+            current_user: current_user.clone(),
         })
         .collect();
 
-    Ok(Json(AdminItemsResponse { items: responses }))
+    Ok(Json(response))
 }
 
-pub async fn admin_get_resource_by_id(
-    _auth: AdminUser,
-    State(state): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
-    let resource: Resource = sqlx::query_as("SELECT * FROM resources WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
+// Boards are defined in the leaderboards table, which is where their ids and titles come from
+async fn fetch_leaderboards(pool: &sqlx::PgPool) -> Result<Vec<Leaderboard>, AppError> {
+    Ok(sqlx::query_as("SELECT * FROM leaderboards ORDER BY id")
+        .fetch_all(pool)
+        .await?)
+}
+
+// The first board's all-time standings as sent over /ws/leaderboard
+async fn leaderboard_snapshot(pool: &sqlx::PgPool) -> Result<String, AppError> {
+    let leaderboard = fetch_leaderboards(pool)
         .await?
+        .into_iter()
+        .next()
         .ok_or(AppError::NotFound)?;
-
-    let response = AdminResourceResponse {
-        id: resource.id,
-        title: resource.title,
-        provider: resource.provider,
-        cover_image: resource.cover_image,
-        notion_url: resource.notion_url,
-        instructor: Some(AdminInstructorResponse {
-            name: resource.instructor_name,
-            image: resource.instructor_image,
-        }),
-        quote: None,
-        visible: resource.visible,
-        created_at: resource.created_at,
-        updated_at: resource.updated_at,
+    let snapshot = LeaderboardResponse {
+        id: leaderboard.id,
+        title: leaderboard.title,
+        entries: public_entries(
+            &fetch_top_entries(pool, LeaderboardPeriod::All, &LeaderboardFilter::default()).await?,
+        ),
+        current_user: None,
     };
 
-    Ok(Json(AdminItemResponse { item: response }))
+    serde_json::to_string(&snapshot).map_err(|e| AppError::InternalError(e.into()))
 }
 
-pub async fn admin_create_resource(
-    _auth: AdminUser,
-    State(state): State<AppState>,
-    Json(req): Json<AdminCreateResourceRequest>,
-) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
-    let visible = req.visible.unwrap_or(true);
-    let instructor_name = req
-        .instructor
-        .as_ref()
-        .map(|i| i.name.clone())
-        .unwrap_or_default();
-    let instructor_image = req.instructor.as_ref().and_then(|i| i.image.clone());
+// Drops the cached boards and pushes the current one to live clients after points
+// changed. The change is already committed, so failures are only logged.
+async fn publish_leaderboard(state: &AppState) {
+    state.leaderboard_cache.invalidate();
 
-    let resource: Resource = sqlx::query_as(
-        r#"
-        INSERT INTO resources (title, provider, cover_image, notion_url, instructor_name, instructor_image, visible, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
-        RETURNING *
-        "#,
-    )
-    .bind(&req.title)
-    .bind(&req.provider)
-    .bind(&req.cover_image)
-    .bind(&req.notion_url)
-    .bind(&instructor_name)
-    .bind(&instructor_image)
-    .bind(visible)
-    .fetch_one(&state.pool)
-    .await?;
+    if !state.leaderboard_hub.has_subscribers() {
+        return;
+    }
 
-    let response = AdminResourceResponse {
-        id: resource.id,
-        title: resource.title,
+    match leaderboard_snapshot(&state.pool).await {
+        Ok(snapshot) => state.leaderboard_hub.publish(snapshot),
+        Err(e) => tracing::warn!("Failed to publish leaderboard update: {:?}", e),
+    }
+}
+
+// Streams the top 10 to the client: the current board on connect, then a fresh one
+// whenever points change
+pub async fn leaderboard_ws(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    let slot = state
+        .leaderboard_hub
+        .try_connect()
+        .ok_or(AppError::TooManyRequests)?;
+
+    // Subscribe before reading the initial board so no update can slip in between
+    let updates = state.leaderboard_hub.subscribe();
+    let initial = leaderboard_snapshot(&state.pool).await?;
+
+    Ok(ws.on_upgrade(move |socket| stream_leaderboard(socket, initial, updates, slot)))
+}
+
+async fn stream_leaderboard(
+    mut socket: WebSocket,
+    initial: String,
+    mut updates: broadcast::Receiver<Arc<str>>,
+    _slot: ConnectionSlot,
+) {
+    if socket.send(Message::Text(initial)).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(snapshot) => {
+                    if socket.send(Message::Text(snapshot.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+                // Missed snapshots are superseded by the next one
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                // Clients have nothing to say; anything but a close is ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ResourceQuery {
+    tag: Option<String>,
+    #[serde(default)]
+    sort: ResourceSort,
+}
+
+// Without `sort` the list keeps its original id order so existing clients see no change
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceSort {
+    #[default]
+    Id,
+    Newest,
+    Oldest,
+    Title,
+}
+
+impl ResourceSort {
+    // Fixed clauses only; the query parameter itself never reaches the SQL.
+    // id breaks ties so pages of equal timestamps or titles come back in a stable order.
+    fn order_by(self) -> &'static str {
+        match self {
+            ResourceSort::Id => "id",
+            ResourceSort::Newest => "created_at DESC, id DESC",
+            ResourceSort::Oldest => "created_at, id",
+            ResourceSort::Title => "LOWER(title), id",
+        }
+    }
+}
+
+pub async fn get_resources(
+    State(state): State<AppState>,
+    AppQuery(query): AppQuery<ResourceQuery>,
+) -> Result<Json<Vec<ResourceListResponse>>, AppError> {
+    let tag = query
+        .tag
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty());
+
+    // Tags are matched case-insensitively so "nlp" finds resources tagged "NLP"
+    let resources: Vec<Resource> = sqlx::query_as(&format!(
+        r#"
+        SELECT * FROM resources
+        WHERE visible = true AND deleted_at IS NULL
+          AND ($1::TEXT IS NULL OR EXISTS (
+              SELECT 1 FROM unnest(tags) AS tag WHERE LOWER(tag) = LOWER($1)
+          ))
+        ORDER BY {}
+        "#,
+        query.sort.order_by()
+    ))
+    .bind(&tag)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let responses: Vec<ResourceListResponse> =
+        resources.into_iter().map(resource_list_response).collect();
+
+    Ok(Json(responses))
+}
+
+// The home page spotlight: visible featured resources, newest first
+pub async fn get_featured_resources(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ResourceListResponse>>, AppError> {
+    let resources: Vec<Resource> = sqlx::query_as(
+        r#"
+        SELECT * FROM resources
+        WHERE featured AND visible = true AND deleted_at IS NULL
+        ORDER BY created_at DESC, id DESC
+        LIMIT $1
+        "#,
+    )
+    .bind(i64::from(state.featured_resources_limit))
+    .fetch_all(&state.pool)
+    .await?;
+
+    let responses: Vec<ResourceListResponse> =
+        resources.into_iter().map(resource_list_response).collect();
+
+    Ok(Json(responses))
+}
+
+fn resource_list_response(r: Resource) -> ResourceListResponse {
+    ResourceListResponse {
+        id: r.id,
+        title: r.title,
+        provider: r.provider,
+        cover_image: r.cover_image,
+        thumbnail_url: r.cover_thumbnail,
+        tags: r.tags,
+        instructor: InstructorResponse {
+            name: r.instructor_name,
+            image: r.instructor_image,
+            title: r.instructor_title,
+            bio: r.instructor_bio,
+        },
+    }
+}
+
+pub async fn bookmark_resource(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+) -> Result<StatusCode, AppError> {
+    let (visible,): (bool,) =
+        sqlx::query_as("SELECT visible FROM resources WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    if !visible {
+        return Err(AppError::NotFound);
+    }
+
+    // Bookmarking twice is a no-op
+    sqlx::query(
+        r#"
+        INSERT INTO resource_bookmarks (user_id, resource_id)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id, resource_id) DO NOTHING
+        "#,
+    )
+    .bind(auth.user_id)
+    .bind(id)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn remove_resource_bookmark(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+) -> Result<StatusCode, AppError> {
+    sqlx::query("DELETE FROM resource_bookmarks WHERE user_id = $1 AND resource_id = $2")
+        .bind(auth.user_id)
+        .bind(id)
+        .execute(&state.pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_user_bookmarks(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ResourceListResponse>>, AppError> {
+    // Bookmarks on resources that were hidden since are kept but not listed
+    let resources: Vec<Resource> = sqlx::query_as(
+        r#"
+        SELECT r.* FROM resource_bookmarks b
+        JOIN resources r ON r.id = b.resource_id
+        WHERE b.user_id = $1 AND r.visible = true AND r.deleted_at IS NULL
+        ORDER BY b.created_at DESC
+        "#,
+    )
+    .bind(auth.user_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(
+        resources.into_iter().map(resource_list_response).collect(),
+    ))
+}
+
+pub async fn get_resource_by_id(
+    OptionalAuthUser(auth): OptionalAuthUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let resource: Resource =
+        sqlx::query_as("SELECT * FROM resources WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    // Hidden resources stay a 404 publicly but with a distinct code
+    if !resource.visible {
+        return Err(AppError::ResourceHidden);
+    }
+
+    if let Some(auth) = auth {
+        record_resource_view(&state.pool, auth.user_id, resource.id).await;
+    }
+
+    // Prefer the linked quote, falling back to a random one (also when it was hidden)
+    let quote: Option<Quote> = sqlx::query_as(
+        "SELECT * FROM quotes WHERE visible = true ORDER BY (id = $1) IS TRUE DESC, RANDOM() LIMIT 1",
+    )
+    .bind(resource.quote_id)
+    .fetch_optional(&state.pool)
+    .await?;
+
+    // A random fallback quote differs between requests, so only responses with the
+    // linked quote (or no quote at all) are stable enough to get an ETag
+    let etag = match &quote {
+        Some(q) if resource.quote_id != Some(q.id) => None,
+        quote => Some(resource_etag(&resource, quote.as_ref())),
+    };
+
+    if let Some(etag) = &etag
+        && etag_matches(&headers, etag)
+    {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag.clone())]).into_response());
+    }
+
+    let quote_response = quote.map(|q| QuoteResponse {
+        text: q.text,
+        author: q.author,
+    });
+
+    let mut response = Json(ResourceDetailResponse {
+        id: resource.id,
+        title: resource.title,
         provider: resource.provider,
-        cover_image: resource.cover_image,
         notion_url: resource.notion_url,
-        instructor: Some(AdminInstructorResponse {
+        instructor: InstructorResponse {
             name: resource.instructor_name,
             image: resource.instructor_image,
-        }),
-        quote: None,
-        visible: resource.visible,
-        created_at: resource.created_at,
-        updated_at: resource.updated_at,
-    };
+            title: resource.instructor_title,
+            bio: resource.instructor_bio,
+        },
+        quote: quote_response,
+    })
+    .into_response();
+
+    if let Some(etag) = etag.and_then(|etag| HeaderValue::from_str(&etag).ok()) {
+        response.headers_mut().insert(header::ETAG, etag);
+    }
+
+    Ok(response)
+}
+
+// Counts a member's first view of a resource. Anonymous and repeat views are left out
+// so bots and refreshes don't inflate the count. Leaves updated_at (and so the ETag) alone.
+async fn record_resource_view(pool: &sqlx::PgPool, user_id: Uuid, resource_id: i32) {
+    let result = sqlx::query(
+        r#"
+        WITH first_view AS (
+            INSERT INTO resource_views (user_id, resource_id)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id, resource_id) DO NOTHING
+            RETURNING resource_id
+        )
+        UPDATE resources SET view_count = view_count + 1
+        WHERE id IN (SELECT resource_id FROM first_view)
+        "#,
+    )
+    .bind(user_id)
+    .bind(resource_id)
+    .execute(pool)
+    .await;
+
+    // Only a statistic, so it shouldn't cost the reader the page
+    if let Err(e) = result {
+        tracing::warn!("Failed to record view of resource {}: {:?}", resource_id, e);
+    }
+}
+
+// Every edit bumps updated_at, so the timestamps identify the exact version served
+fn resource_etag(resource: &Resource, quote: Option<&Quote>) -> String {
+    let quote_version = quote
+        .map(|q| format!("{}.{}", q.id, q.updated_at.unix_timestamp_nanos()))
+        .unwrap_or_else(|| "none".to_string());
+
+    format!(
+        "\"{}.{}-{}\"",
+        resource.id,
+        resource.updated_at.unix_timestamp_nanos(),
+        quote_version
+    )
+}
+
+// If-None-Match may list several tags, weak ones included, or be `*`
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+pub async fn get_random_quote(State(state): State<AppState>) -> Result<Response, AppError> {
+    let quote: Option<Quote> =
+        sqlx::query_as("SELECT * FROM quotes WHERE visible = true ORDER BY RANDOM() LIMIT 1")
+            .fetch_optional(&state.pool)
+            .await?;
+
+    Ok(quote_response(quote))
+}
+
+// Quote of the day: hashing each id with the UTC date gives an order that is fixed for
+// the day and reshuffled the next, without storing anything
+pub async fn get_daily_quote(State(state): State<AppState>) -> Result<Response, AppError> {
+    let quote: Option<Quote> = sqlx::query_as(
+        r#"
+        SELECT * FROM quotes
+        WHERE visible = true
+        ORDER BY md5(id::TEXT || ':' || (NOW() AT TIME ZONE 'UTC')::DATE::TEXT), id
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    Ok(quote_response(quote))
+}
+
+// 204 when there are no visible quotes to pick from
+fn quote_response(quote: Option<Quote>) -> Response {
+    match quote {
+        Some(q) => Json(QuoteResponse {
+            text: q.text,
+            author: q.author,
+        })
+        .into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+pub async fn get_current_challenge(
+    _auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<ChallengeResponse>, AppError> {
+    let challenge = fetch_current_challenge(&state.pool).await?;
+
+    Ok(Json(ChallengeResponse {
+        id: challenge.id,
+        week: challenge.week,
+        title: challenge.title,
+        description: challenge.description,
+        challenge_url: challenge.challenge_url,
+        start_date: challenge.start_date,
+        end_date: challenge.end_date,
+    }))
+}
+
+// Teaser for the landing page; the task link itself stays behind sign-in
+pub async fn get_public_current_challenge(
+    State(state): State<AppState>,
+) -> Result<Json<PublicChallengeResponse>, AppError> {
+    let challenge = fetch_current_challenge(&state.pool).await?;
+
+    Ok(Json(PublicChallengeResponse {
+        id: challenge.id,
+        week: challenge.week,
+        title: challenge.title,
+        description: challenge.description,
+        start_date: challenge.start_date,
+        end_date: challenge.end_date,
+    }))
+}
+
+// The pinned challenge if there is one, otherwise the newest whose window covers now
+async fn fetch_current_challenge(pool: &sqlx::PgPool) -> Result<Challenge, AppError> {
+    let challenge = sqlx::query_as(
+        r#"
+        SELECT * FROM challenges 
+        WHERE visible = true AND deleted_at IS NULL
+        AND (
+            is_current
+            OR (
+                (start_date IS NULL OR start_date <= NOW())
+                AND (end_date IS NULL OR end_date >= NOW())
+            )
+        )
+        ORDER BY is_current DESC, created_at DESC 
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    Ok(challenge)
+}
+
+pub async fn get_challenge_leaderboard(
+    _auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ChallengeLeaderboardEntry>>, AppError> {
+    let entries = cached_top_entries(&state, LeaderboardPeriod::All)
+        .await?
+        .to_vec();
+
+    Ok(Json(entries))
+}
+
+// v2 of /challenges/leaderboard: an object, so the caller's own position fits beside the
+// top entries. v1 keeps returning the bare array existing clients parse.
+pub async fn get_challenge_leaderboard_v2(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<ChallengeLeaderboardResponse>, AppError> {
+    let entries = cached_top_entries(&state, LeaderboardPeriod::All)
+        .await?
+        .to_vec();
+
+    let current_user = fetch_leaderboard_position(
+        &state.pool,
+        auth.user_id,
+        LeaderboardPeriod::All,
+        &LeaderboardFilter::default(),
+    )
+    .await?;
+
+    Ok(Json(ChallengeLeaderboardResponse {
+        entries,
+        current_user,
+    }))
+}
+
+// Helper function to check a submission URL against the configured domain allowlist
+fn validate_submission_url(
+    submission_url: &str,
+    allowed_domains: &[String],
+) -> Result<(), AppError> {
+    if allowed_domains.is_empty() {
+        return Ok(());
+    }
+
+    let url = url::Url::parse(submission_url)
+        .map_err(|_| AppError::ValidationError("Submission URL is not a valid URL".to_string()))?;
+
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(AppError::ValidationError(
+            "Submission URL must use http or https".to_string(),
+        ));
+    }
+
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    let allowed = allowed_domains
+        .iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{domain}")));
+
+    if !allowed {
+        return Err(AppError::ValidationError(format!(
+            "Submissions must link to one of: {}",
+            allowed_domains.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn create_challenge_submission(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+    AppJson(req): AppJson<CreateSubmissionRequest>,
+) -> Result<Json<SubmissionResponse>, AppError> {
+    let submission_url = req.submission_url.trim();
+    if submission_url.is_empty() {
+        return Err(AppError::ValidationError(
+            "Submission URL is required".to_string(),
+        ));
+    }
+    // The column is VARCHAR(512)
+    if submission_url.chars().count() > 512 {
+        return Err(AppError::ValidationError(
+            "Submission URL must be at most 512 characters".to_string(),
+        ));
+    }
+
+    validate_submission_url(submission_url, &state.submission_allowed_domains)?;
+
+    sqlx::query(
+        "SELECT id FROM challenges WHERE id = $1 AND visible = true AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    // Resubmitting replaces the URL but keeps any score already given
+    let submission: ChallengeSubmission = sqlx::query_as(
+        r#"
+        INSERT INTO challenge_submissions (challenge_id, user_id, submission_url, created_at, updated_at)
+        VALUES ($1, $2, $3, NOW(), NOW())
+        ON CONFLICT (challenge_id, user_id)
+        DO UPDATE SET submission_url = EXCLUDED.submission_url, updated_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(auth.user_id)
+    .bind(submission_url)
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(Json(SubmissionResponse {
+        id: submission.id,
+        challenge_id: submission.challenge_id,
+        submission_url: submission.submission_url,
+        score: submission.score,
+        created_at: submission.created_at,
+        updated_at: submission.updated_at,
+    }))
+}
+
+pub async fn get_user_profile(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<UserProfileResponse>, AppError> {
+    let user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
+        .bind(auth.user_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let stats: Option<UserStats> = sqlx::query_as("SELECT * FROM user_stats WHERE user_id = $1")
+        .bind(auth.user_id)
+        .fetch_optional(&state.pool)
+        .await?;
+
+    // Accounts created before signup was transactional may lack a stats row;
+    // create it on first access instead of failing forever
+    let stats = match stats {
+        Some(stats) => stats,
+        None => {
+            sqlx::query(
+                r#"
+                INSERT INTO user_stats (user_id, created_at, updated_at)
+                VALUES ($1, NOW(), NOW())
+                ON CONFLICT (user_id) DO NOTHING
+                "#,
+            )
+            .bind(auth.user_id)
+            .execute(&state.pool)
+            .await?;
+
+            sqlx::query_as("SELECT * FROM user_stats WHERE user_id = $1")
+                .bind(auth.user_id)
+                .fetch_one(&state.pool)
+                .await?
+        }
+    };
+
+    Ok(Json(UserProfileResponse {
+        rank: user.rank,
+        name: user.full_name,
+        points: user.points,
+        image: user.image,
+        stats: UserStatsResponse {
+            best_subject: stats.best_subject,
+            improveable: stats.improveable,
+            quickest_hunter: stats.quickest_hunter,
+            challenges_taken: stats.challenges_taken,
+        },
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct PointsTimelineQuery {
+    period: Option<TimelinePeriod>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelinePeriod {
+    Week,
+    Month,
+}
+
+impl TimelinePeriod {
+    fn as_str(self) -> &'static str {
+        match self {
+            TimelinePeriod::Week => "week",
+            TimelinePeriod::Month => "month",
+        }
+    }
+}
+
+pub async fn get_points_timeline(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    AppQuery(query): AppQuery<PointsTimelineQuery>,
+) -> Result<Json<PointsTimelineResponse>, AppError> {
+    let period = query.period.unwrap_or(TimelinePeriod::Week);
+
+    // Buckets run from the user's first ledger entry to now; empty buckets carry the
+    // previous total forward. Points earned before the ledger existed form the baseline
+    // so the last bucket always matches users.points.
+    let buckets: Vec<PointsTimelineBucket> = sqlx::query_as(
+        r#"
+        WITH buckets AS (
+            SELECT generate_series(
+                date_trunc($2, (SELECT MIN(created_at) FROM points_history WHERE user_id = $1)),
+                date_trunc($2, NOW()),
+                ('1 ' || $2)::INTERVAL
+            ) AS bucket
+        ),
+        deltas AS (
+            SELECT date_trunc($2, created_at) AS bucket, SUM(delta) AS delta
+            FROM points_history
+            WHERE user_id = $1
+            GROUP BY 1
+        ),
+        baseline AS (
+            SELECT u.points - COALESCE((SELECT SUM(delta) FROM points_history WHERE user_id = $1), 0) AS points
+            FROM users u
+            WHERE u.id = $1
+        )
+        SELECT b.bucket AS start,
+               ((SELECT points FROM baseline) + SUM(COALESCE(d.delta, 0)) OVER (ORDER BY b.bucket))::INTEGER AS total
+        FROM buckets b
+        LEFT JOIN deltas d ON d.bucket = b.bucket
+        ORDER BY b.bucket
+        "#,
+    )
+    .bind(auth.user_id)
+    .bind(period.as_str())
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(PointsTimelineResponse {
+        period: period.as_str().to_string(),
+        buckets,
+    }))
+}
+
+// Newest first, so the entries explaining the latest changes come on the first page
+pub async fn get_points_history(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    AppQuery(pagination): AppQuery<PaginationQuery>,
+) -> Result<Json<PaginatedResponse<PointsHistoryEntry>>, AppError> {
+    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM points_history WHERE user_id = $1")
+        .bind(auth.user_id)
+        .fetch_one(&state.pool)
+        .await?;
+
+    let entries: Vec<PointsHistoryEntry> = sqlx::query_as(
+        r#"
+        SELECT p.id, p.delta, p.reason, p.challenge_id, c.title AS challenge_title, p.created_at
+        FROM points_history p
+        LEFT JOIN challenges c ON c.id = p.challenge_id
+        WHERE p.user_id = $1
+        ORDER BY p.created_at DESC, p.id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(auth.user_id)
+    .bind(pagination.page_size())
+    .bind(pagination.offset())
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(PaginatedResponse {
+        items: entries,
+        page: pagination.page(),
+        page_size: pagination.page_size(),
+        total,
+    }))
+}
+
+// Newest first, across all challenges
+pub async fn get_user_submissions(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    AppQuery(pagination): AppQuery<PaginationQuery>,
+) -> Result<Json<PaginatedResponse<UserSubmissionEntry>>, AppError> {
+    let (total,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM challenge_submissions WHERE user_id = $1")
+            .bind(auth.user_id)
+            .fetch_one(&state.pool)
+            .await?;
+
+    let submissions: Vec<UserSubmissionEntry> = sqlx::query_as(
+        r#"
+        SELECT s.id, s.challenge_id, c.title AS challenge_title, c.week AS challenge_week,
+               s.submission_url, s.score, s.score IS NOT NULL AS graded, s.scored_at,
+               s.created_at, s.updated_at
+        FROM challenge_submissions s
+        JOIN challenges c ON c.id = s.challenge_id
+        WHERE s.user_id = $1
+        ORDER BY s.created_at DESC, s.id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(auth.user_id)
+    .bind(pagination.page_size())
+    .bind(pagination.offset())
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(PaginatedResponse {
+        items: submissions,
+        page: pagination.page(),
+        page_size: pagination.page_size(),
+        total,
+    }))
+}
+
+pub async fn create_contact(
+    ClientIp(ip): ClientIp,
+    State(state): State<AppState>,
+    AppJson(req): AppJson<ContactRequest>,
+) -> Result<Json<ContactResponse>, AppError> {
+    if !state.contact_limiter.check(ip) {
+        return Err(AppError::TooManyRequests);
+    }
+
+    // Bots fill every field they find; answer as usual so they don't learn to skip it
+    if let Some(field) = &state.contact_honeypot_field
+        && req.extra.get(field).is_some_and(is_filled)
+    {
+        tracing::info!(
+            "Dropped contact message from {} that filled the honeypot",
+            ip
+        );
+        return Ok(Json(contact_sent()));
+    }
+
+    let limits = state.contact_limits;
+    let name = required_text("Name", &req.name, limits.max_name_len)?;
+    let email = normalize_email(&req.email)?;
+    let message = required_text("Message", &req.message, limits.max_message_len)?;
+
+    sqlx::query(
+        "INSERT INTO contact_messages (name, email, message, created_at) VALUES ($1, $2, $3, NOW())",
+    ).bind(&name)
+    .bind(&email)
+    .bind(&message)
+    .execute(&state.pool)
+    .await?;
+
+    // Notify admins in the background; a failed email must not fail the request
+    if let Some(to) = state.admin_notification_email.clone() {
+        let mailer = state.mailer.clone();
+        let message = EmailMessage {
+            to,
+            subject: format!("New contact message from {}", name),
+            body: format!("From: {} <{}>\n\n{}", name, email, message),
+        };
+
+        tokio::spawn(async move {
+            if let Err(e) = mailer.send(message).await {
+                tracing::error!("Failed to send contact notification: {}", e);
+            }
+        });
+    }
+
+    Ok(Json(contact_sent()))
+}
+
+fn contact_sent() -> ContactResponse {
+    ContactResponse {
+        success: true,
+        message: "Message sent successfully".to_string(),
+    }
+}
+
+// Any value other than null or a blank string counts as filled in
+fn is_filled(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => false,
+        serde_json::Value::String(text) => !text.trim().is_empty(),
+        _ => true,
+    }
+}
+
+pub async fn admin_get_contact_messages(
+    _auth: AdminUser,
+    State(state): State<AppState>,
+    AppQuery(pagination): AppQuery<PaginationQuery>,
+) -> Result<Json<PaginatedResponse<AdminContactMessageResponse>>, AppError> {
+    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM contact_messages")
+        .fetch_one(&state.pool)
+        .await?;
+
+    let messages: Vec<ContactMessage> = sqlx::query_as(
+        "SELECT * FROM contact_messages ORDER BY created_at DESC, id LIMIT $1 OFFSET $2",
+    )
+    .bind(pagination.page_size())
+    .bind(pagination.offset())
+    .fetch_all(&state.pool)
+    .await?;
+
+    let items: Vec<AdminContactMessageResponse> = messages
+        .into_iter()
+        .map(|m| AdminContactMessageResponse {
+            id: m.id,
+            name: m.name,
+            email: m.email,
+            message: m.message,
+            handled: m.handled,
+            created_at: m.created_at,
+        })
+        .collect();
+
+    Ok(Json(PaginatedResponse {
+        items,
+        page: pagination.page(),
+        page_size: pagination.page_size(),
+        total,
+    }))
+}
+
+pub async fn admin_patch_contact_message(
+    _auth: AdminUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<Uuid>,
+    AppJson(req): AppJson<AdminContactHandledRequest>,
+) -> Result<Json<AdminItemResponse<AdminContactMessageResponse>>, AppError> {
+    let message: ContactMessage =
+        sqlx::query_as("UPDATE contact_messages SET handled = $1 WHERE id = $2 RETURNING *")
+            .bind(req.handled)
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    let response = AdminContactMessageResponse {
+        id: message.id,
+        name: message.name,
+        email: message.email,
+        message: message.message,
+        handled: message.handled,
+        created_at: message.created_at,
+    };
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+pub async fn get_notifications(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    AppQuery(pagination): AppQuery<PaginationQuery>,
+) -> Result<Json<NotificationListResponse>, AppError> {
+    let (total, unread_count): (i64, i64) = sqlx::query_as(
+        "SELECT COUNT(*), COUNT(*) FILTER (WHERE NOT read) FROM notifications WHERE user_id = $1",
+    )
+    .bind(auth.user_id)
+    .fetch_one(&state.pool)
+    .await?;
+
+    let notifications: Vec<Notification> = sqlx::query_as(
+        "SELECT * FROM notifications WHERE user_id = $1 ORDER BY created_at DESC, id LIMIT $2 OFFSET $3",
+    )
+    .bind(auth.user_id)
+    .bind(pagination.page_size())
+    .bind(pagination.offset())
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(NotificationListResponse {
+        items: notifications.into_iter().map(Into::into).collect(),
+        page: pagination.page(),
+        page_size: pagination.page_size(),
+        total,
+        unread_count,
+    }))
+}
+
+pub async fn mark_notification_read(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<Uuid>,
+) -> Result<Json<NotificationResponse>, AppError> {
+    // Other users' notifications are indistinguishable from missing ones
+    let notification: Notification = sqlx::query_as(
+        "UPDATE notifications SET read = true WHERE id = $1 AND user_id = $2 RETURNING *",
+    )
+    .bind(id)
+    .bind(auth.user_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    Ok(Json(notification.into()))
+}
+
+#[derive(Deserialize)]
+pub struct AdminResourceQuery {
+    #[serde(rename = "includeHidden")]
+    include_hidden: Option<bool>,
+    #[serde(rename = "includeDeleted")]
+    include_deleted: Option<bool>,
+}
+
+fn admin_resource_response(r: Resource, quote: Option<Quote>) -> AdminResourceResponse {
+    AdminResourceResponse {
+        id: r.id,
+        title: r.title,
+        provider: r.provider,
+        cover_image: r.cover_image,
+        thumbnail_url: r.cover_thumbnail,
+        notion_url: r.notion_url,
+        tags: r.tags,
+        instructor: Some(AdminInstructorResponse {
+            name: r.instructor_name,
+            image: r.instructor_image,
+            title: r.instructor_title,
+            bio: r.instructor_bio,
+        }),
+        quote: quote.map(|q| AdminQuoteResponse {
+            id: q.id,
+            text: q.text,
+            author: q.author,
+        }),
+        visible: r.visible,
+        featured: r.featured,
+        view_count: r.view_count,
+        created_by: r.created_by,
+        created_at: r.created_at,
+        updated_at: r.updated_at,
+        deleted_at: r.deleted_at,
+    }
+}
+
+// Builds the admin view of a single resource, including its linked quote
+async fn resource_to_response(
+    pool: &sqlx::PgPool,
+    resource: Resource,
+) -> Result<AdminResourceResponse, AppError> {
+    let quote: Option<Quote> = match resource.quote_id {
+        Some(quote_id) => {
+            sqlx::query_as("SELECT * FROM quotes WHERE id = $1")
+                .bind(quote_id)
+                .fetch_optional(pool)
+                .await?
+        }
+        None => None,
+    };
+
+    Ok(admin_resource_response(resource, quote))
+}
+
+// How the link field is named in validation messages
+const NOTION_URL: &str = "Notion URL";
+
+const MAX_INSTRUCTOR_TITLE_LEN: usize = 255;
+const MAX_INSTRUCTOR_BIO_LEN: usize = 2000;
+
+// Trims the optional instructor title and bio; blank values mean "not set"
+fn instructor_details(
+    title: Option<&str>,
+    bio: Option<&str>,
+) -> Result<(Option<String>, Option<String>), AppError> {
+    Ok((
+        optional_text("Instructor title", title, MAX_INSTRUCTOR_TITLE_LEN)?,
+        optional_text("Instructor bio", bio, MAX_INSTRUCTOR_BIO_LEN)?,
+    ))
+}
+
+// Trims a required text field and checks it is neither blank nor longer than max_len
+fn required_text(label: &str, value: &str, max_len: usize) -> Result<String, AppError> {
+    optional_text(label, Some(value), max_len)?
+        .ok_or_else(|| AppError::ValidationError(format!("{label} is required")))
+}
+
+fn optional_text(
+    label: &str,
+    value: Option<&str>,
+    max_len: usize,
+) -> Result<Option<String>, AppError> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    if value.chars().count() > max_len {
+        return Err(AppError::ValidationError(format!(
+            "{label} must be at most {max_len} characters"
+        )));
+    }
+    Ok(Some(value.to_string()))
+}
+
+// Like resource_to_response for a whole list, loading all linked quotes in one query
+async fn resources_to_responses(
+    pool: &sqlx::PgPool,
+    resources: Vec<Resource>,
+) -> Result<Vec<AdminResourceResponse>, AppError> {
+    let quote_ids: Vec<i32> = resources.iter().filter_map(|r| r.quote_id).collect();
+    let quotes: Vec<Quote> = sqlx::query_as("SELECT * FROM quotes WHERE id = ANY($1)")
+        .bind(&quote_ids)
+        .fetch_all(pool)
+        .await?;
+    // Several resources can share a quote, so each gets its own copy
+    let quotes: HashMap<i32, Quote> = quotes.into_iter().map(|q| (q.id, q)).collect();
+
+    Ok(resources
+        .into_iter()
+        .map(|r| {
+            let quote = r.quote_id.and_then(|id| quotes.get(&id).cloned());
+            admin_resource_response(r, quote)
+        })
+        .collect())
+}
+
+// The quote a resource create or update asked for: an inline one, or an existing id
+struct QuoteChoice {
+    quote: Option<AdminQuoteRequest>,
+    quote_id: Option<i32>,
+}
+
+// Works out which quote a resource should link to. An inline quote is created first;
+// otherwise the given id must point at an existing quote. Run it in the transaction
+// that writes the resource, so a failed write doesn't leave the new quote behind.
+async fn resolve_resource_quote<'e, E>(
+    executor: E,
+    QuoteChoice { quote, quote_id }: QuoteChoice,
+) -> Result<Option<i32>, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    if let Some(quote) = quote {
+        let mut errors = FieldErrors::default();
+        let text = errors.require("quoteText", Some(quote.text));
+        let author = errors.require("quoteAuthor", Some(quote.author));
+        let (Some(text), Some(author)) = (text, author) else {
+            return Err(errors.into());
+        };
+
+        let (id,): (i32,) =
+            sqlx::query_as("INSERT INTO quotes (text, author) VALUES ($1, $2) RETURNING id")
+                .bind(text.trim())
+                .bind(author.trim())
+                .fetch_one(executor)
+                .await?;
+        return Ok(Some(id));
+    }
+
+    if let Some(quote_id) = quote_id {
+        let (exists,): (bool,) =
+            sqlx::query_as("SELECT EXISTS(SELECT 1 FROM quotes WHERE id = $1)")
+                .bind(quote_id)
+                .fetch_one(executor)
+                .await?;
+        if !exists {
+            return Err(AppError::BadRequest("Quote not found".to_string()));
+        }
+    }
+
+    Ok(quote_id)
+}
+
+pub async fn admin_get_resources(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppQuery(query): AppQuery<AdminResourceQuery>,
+) -> Result<Json<AdminItemsResponse<AdminResourceResponse>>, AppError> {
+    let include_hidden = query.include_hidden.unwrap_or(false);
+    let include_deleted = query.include_deleted.unwrap_or(false);
+
+    let resources: Vec<Resource> = sqlx::query_as(
+        "SELECT * FROM resources WHERE ($1 OR visible = true) AND ($2 OR deleted_at IS NULL) ORDER BY id",
+    )
+    .bind(include_hidden)
+    .bind(include_deleted)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let responses = resources_to_responses(&state.pool, resources).await?;
+
+    Ok(Json(AdminItemsResponse { items: responses }))
+}
+
+pub async fn admin_get_resource_by_id(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
+    let resource: Resource = sqlx::query_as("SELECT * FROM resources WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let response = resource_to_response(&state.pool, resource).await?;
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+// 201 Created for admin create endpoints: the usual JSON body plus a Location header
+// pointing at the new item
+pub struct Created<T> {
+    location: String,
+    body: T,
+}
+
+impl<T> Created<T> {
+    fn at(location: String, body: T) -> Self {
+        Self { location, body }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Created<T> {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::CREATED,
+            [(header::LOCATION, self.location)],
+            Json(self.body),
+        )
+            .into_response()
+    }
+}
+
+// Takes JSON, or multipart form data when uploading the cover or instructor image
+pub async fn admin_create_resource(
+    auth: ModeratorUser,
+    State(state): State<AppState>,
+    body: JsonOrMultipart<AdminCreateResourceRequest>,
+) -> Result<Created<AdminItemResponse<AdminResourceResponse>>, AppError> {
+    let (new_resource, quote) = match body {
+        JsonOrMultipart::Json(req) => new_resource_from_json(state.storage.as_ref(), req)?,
+        JsonOrMultipart::Multipart(multipart) => {
+            new_resource_from_multipart(&state, multipart).await?
+        }
+    };
+
+    let mut tx = state.pool.begin().await?;
+    let quote_id = resolve_resource_quote(&mut *tx, quote).await?;
+    let resource = insert_resource(&mut *tx, &new_resource, quote_id, auth.user_id).await?;
+    tx.commit().await?;
+
+    let response = resource_to_response(&state.pool, resource).await?;
+
+    Ok(Created::at(
+        format!("/admin/resources/{}", response.id),
+        AdminItemResponse { item: response },
+    ))
+}
+
+// Same body formats as admin_create_resource; omitted fields keep their current values
+pub async fn admin_update_resource(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+    body: JsonOrMultipart<AdminUpdateResourceRequest>,
+) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
+    let mut resource: Resource =
+        sqlx::query_as("SELECT * FROM resources WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    let previous_images = [
+        resource.cover_image.clone(),
+        resource.instructor_image.clone(),
+    ];
+
+    let quote = match body {
+        JsonOrMultipart::Json(req) => {
+            apply_resource_json(state.storage.as_ref(), &mut resource, req)?
+        }
+        JsonOrMultipart::Multipart(multipart) => {
+            apply_resource_multipart(&state, &mut resource, multipart).await?
+        }
+    };
+
+    let mut tx = state.pool.begin().await?;
+    if let Some(quote) = quote {
+        resource.quote_id = resolve_resource_quote(&mut *tx, quote).await?;
+    }
+    let resource = save_resource(&mut *tx, &resource).await?;
+    tx.commit().await?;
+
+    // Clean up images that were replaced or removed, once the change is committed
+    for url in previous_images.into_iter().flatten() {
+        if resource.cover_image.as_ref() != Some(&url)
+            && resource.instructor_image.as_ref() != Some(&url)
+        {
+            release_resource_image(&state, &url).await;
+        }
+    }
+    let response = resource_to_response(&state.pool, resource).await?;
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+// JSON bodies may point a resource at an outside image, or keep one it already shows.
+// Other hosted files only get attached by uploading them with the resource, since
+// clearing an image later deletes its file.
+fn check_linked_image(
+    storage: &dyn FileStorage,
+    url: &str,
+    current: &[Option<String>],
+) -> Result<(), AppError> {
+    if storage.key_for(url).is_some() && !current.iter().flatten().any(|image| image == url) {
+        return Err(AppError::ValidationError(
+            "Upload a new image instead of linking to an uploaded file".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+// Deletes an image a resource stopped using if it was uploaded for a resource, unless a
+// resource or an account still shows it. Failures are only logged; the resource itself
+// is already updated.
+async fn release_resource_image(state: &AppState, url: &str) {
+    let uploaded_for_resource = state
+        .storage
+        .key_for(url)
+        .is_some_and(|key| key.starts_with("resources/"));
+    if !uploaded_for_resource {
+        return;
+    }
+
+    let in_use = sqlx::query_as::<_, (bool,)>(
+        r#"
+        SELECT EXISTS(SELECT 1 FROM resources WHERE cover_image = $1 OR instructor_image = $1)
+            OR EXISTS(SELECT 1 FROM users WHERE image = $1)
+        "#,
+    )
+    .bind(url)
+    .fetch_one(&state.pool)
+    .await;
+
+    match in_use {
+        Ok((false,)) => remove_uploaded_file(state.storage.as_ref(), url).await,
+        Ok((true,)) => {}
+        Err(e) => tracing::warn!("Failed to check whether {} is still used: {:?}", url, e),
+    }
+}
+
+// Column values for a resource about to be created, whichever format the request used
+struct NewResource {
+    title: String,
+    provider: String,
+    cover_image: Option<String>,
+    cover_thumbnail: Option<String>,
+    notion_url: Option<String>,
+    instructor_name: String,
+    instructor_image: Option<String>,
+    instructor_title: Option<String>,
+    instructor_bio: Option<String>,
+    visible: bool,
+    tags: Vec<String>,
+}
+
+async fn insert_resource<'e, E>(
+    executor: E,
+    resource: &NewResource,
+    quote_id: Option<i32>,
+    created_by: Uuid,
+) -> Result<Resource, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let resource = sqlx::query_as(
+        r#"
+        INSERT INTO resources (title, provider, cover_image, cover_thumbnail, notion_url, instructor_name, instructor_image, instructor_title, instructor_bio, visible, tags, quote_id, created_by, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW(), NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(&resource.title)
+    .bind(&resource.provider)
+    .bind(&resource.cover_image)
+    .bind(&resource.cover_thumbnail)
+    .bind(&resource.notion_url)
+    .bind(&resource.instructor_name)
+    .bind(&resource.instructor_image)
+    .bind(&resource.instructor_title)
+    .bind(&resource.instructor_bio)
+    .bind(resource.visible)
+    .bind(&resource.tags)
+    .bind(quote_id)
+    .bind(created_by)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(resource)
+}
+
+// Writes back every editable column of an existing resource
+async fn save_resource<'e, E>(executor: E, resource: &Resource) -> Result<Resource, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let resource = sqlx::query_as(
+        r#"
+        UPDATE resources 
+        SET title = $1, provider = $2, cover_image = $3, cover_thumbnail = $4, notion_url = $5, instructor_name = $6, instructor_image = $7, instructor_title = $8, instructor_bio = $9, visible = $10, tags = $11, quote_id = $12, updated_at = NOW()
+        WHERE id = $13
+        RETURNING *
+        "#,
+    )
+    .bind(&resource.title)
+    .bind(&resource.provider)
+    .bind(&resource.cover_image)
+    .bind(&resource.cover_thumbnail)
+    .bind(&resource.notion_url)
+    .bind(&resource.instructor_name)
+    .bind(&resource.instructor_image)
+    .bind(&resource.instructor_title)
+    .bind(&resource.instructor_bio)
+    .bind(resource.visible)
+    .bind(&resource.tags)
+    .bind(resource.quote_id)
+    .bind(resource.id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(resource)
+}
+
+// The checks every resource write runs, whichever body format it came in. Updates run
+// them on the merged result, so blanking a field fails the same way as leaving it out.
+fn check_resource_fields(
+    errors: &mut FieldErrors,
+    title: &str,
+    provider: &str,
+    instructor_name: &str,
+) {
+    for (field, value) in [
+        ("title", title),
+        ("provider", provider),
+        ("instructorName", instructor_name),
+    ] {
+        if value.trim().is_empty() {
+            errors.add(field, "This field is required");
+        }
+    }
+}
+
+fn new_resource_from_json(
+    storage: &dyn FileStorage,
+    req: AdminCreateResourceRequest,
+) -> Result<(NewResource, QuoteChoice), AppError> {
+    let linked_images = [
+        req.cover_image.as_deref(),
+        req.instructor.as_ref().and_then(|i| i.image.as_deref()),
+    ];
+    for url in linked_images.into_iter().flatten() {
+        check_linked_image(storage, url, &[])?;
+    }
+    let (instructor_title, instructor_bio) = match &req.instructor {
+        Some(i) => instructor_details(i.title.as_deref(), i.bio.as_deref())?,
+        None => (None, None),
+    };
+    let (instructor_name, instructor_image) = match req.instructor {
+        Some(i) => (i.name, i.image),
+        None => (String::new(), None),
+    };
+    let mut errors = FieldErrors::default();
+    check_resource_fields(&mut errors, &req.title, &req.provider, &instructor_name);
+    let notion_url = errors.check(
+        "notionUrl",
+        normalize_http_url(NOTION_URL, req.notion_url.as_deref().unwrap_or_default()),
+    );
+    errors.into_result()?;
+    let notion_url = notion_url.flatten();
+    let quote = QuoteChoice {
+        quote: req.quote,
+        quote_id: req.quote_id,
+    };
+
+    Ok((
+        NewResource {
+            title: req.title,
+            provider: req.provider,
+            cover_image: req.cover_image,
+            cover_thumbnail: None,
+            notion_url,
+            instructor_name,
+            instructor_image,
+            instructor_title,
+            instructor_bio,
+            visible: req.visible.unwrap_or(true),
+            tags: normalize_tags(req.tags.unwrap_or_default()),
+        },
+        quote,
+    ))
+}
+
+// Applies the edits to `resource`, returning the quote change for the caller to resolve
+fn apply_resource_json(
+    storage: &dyn FileStorage,
+    resource: &mut Resource,
+    req: AdminUpdateResourceRequest,
+) -> Result<Option<QuoteChoice>, AppError> {
+    let current_images = [
+        resource.cover_image.clone(),
+        resource.instructor_image.clone(),
+    ];
+    let linked_images = [
+        req.cover_image.as_ref().and_then(|cover| cover.as_deref()),
+        req.instructor.as_ref().and_then(|i| i.image.as_deref()),
+    ];
+    for url in linked_images.into_iter().flatten() {
+        check_linked_image(storage, url, &current_images)?;
+    }
+
+    if let Some(title) = req.title {
+        resource.title = title;
+    }
+    if let Some(provider) = req.provider {
+        resource.provider = provider;
+    }
+    // The stored thumbnail belongs to the old cover, so a different cover drops it
+    if let Some(cover_image) = req.cover_image
+        && resource.cover_image != cover_image
+    {
+        resource.cover_image = cover_image;
+        resource.cover_thumbnail = None;
+    }
+    if let Some(notion_url) = req.notion_url {
+        resource.notion_url = normalize_http_url(NOTION_URL, &notion_url)?;
+    }
+    if let Some(instructor) = req.instructor {
+        // Sending an instructor object replaces the title and bio; blank ones clear them
+        (resource.instructor_title, resource.instructor_bio) =
+            instructor_details(instructor.title.as_deref(), instructor.bio.as_deref())?;
+        resource.instructor_name = instructor.name;
+        if let Some(image) = instructor.image {
+            resource.instructor_image = Some(image);
+        }
+    }
+    if let Some(visible) = req.visible {
+        resource.visible = visible;
+    }
+    if let Some(tags) = req.tags {
+        resource.tags = normalize_tags(tags);
+    }
+    let mut errors = FieldErrors::default();
+    check_resource_fields(
+        &mut errors,
+        &resource.title,
+        &resource.provider,
+        &resource.instructor_name,
+    );
+    errors.into_result()?;
+    if req.quote.is_none() && req.quote_id.is_none() {
+        return Ok(None);
+    }
+
+    Ok(Some(QuoteChoice {
+        quote: req.quote,
+        quote_id: req.quote_id.flatten(),
+    }))
+}
+
+pub async fn admin_delete_resource(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+) -> Result<Json<AdminSuccessResponse>, AppError> {
+    let result = sqlx::query(
+        "UPDATE resources SET deleted_at = NOW(), updated_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .execute(&state.pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(Json(AdminSuccessResponse { success: true }))
+}
+
+pub async fn admin_patch_resource_visibility(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+    AppJson(req): AppJson<AdminVisibilityRequest>,
+) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
+    let resource: Resource = sqlx::query_as(
+        "UPDATE resources SET visible = $1, updated_at = NOW() WHERE id = $2 AND deleted_at IS NULL RETURNING *",
+    )
+    .bind(req.visible)
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let response = resource_to_response(&state.pool, resource).await?;
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+pub async fn admin_patch_resource_featured(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+    AppJson(req): AppJson<AdminFeaturedRequest>,
+) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
+    let resource: Resource = sqlx::query_as(
+        "UPDATE resources SET featured = $1, updated_at = NOW() WHERE id = $2 AND deleted_at IS NULL RETURNING *",
+    )
+    .bind(req.featured)
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let response = resource_to_response(&state.pool, resource).await?;
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+// Shows or hides several resources at once. Ids that don't exist (or are deleted) are
+// skipped, so `count` can be lower than the number of ids sent.
+pub async fn admin_patch_resources_visibility(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppJson(req): AppJson<AdminBatchVisibilityRequest>,
+) -> Result<Json<AdminBatchResponse<AdminResourceResponse>>, AppError> {
+    validate_batch_ids(&req.ids)?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let mut resources: Vec<Resource> = sqlx::query_as(
+        "UPDATE resources SET visible = $1, updated_at = NOW() WHERE id = ANY($2) AND deleted_at IS NULL RETURNING *",
+    )
+    .bind(req.visible)
+    .bind(&req.ids)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    resources.sort_by_key(|r| r.id);
+    let items = resources_to_responses(&state.pool, resources).await?;
+
+    Ok(Json(AdminBatchResponse {
+        count: items.len(),
+        items,
+    }))
+}
+
+// Undoes a soft delete
+pub async fn admin_restore_resource(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
+    let resource: Resource = sqlx::query_as(
+        "UPDATE resources SET deleted_at = NULL, updated_at = NOW() WHERE id = $1 AND deleted_at IS NOT NULL RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let response = resource_to_response(&state.pool, resource).await?;
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+#[derive(Deserialize)]
+pub struct AdminChallengeQuery {
+    #[serde(rename = "includeHidden")]
+    include_hidden: Option<bool>,
+    #[serde(rename = "includeDeleted")]
+    include_deleted: Option<bool>,
+    week: Option<i32>,
+    // A date (YYYY-MM-DD) that must fall inside the challenge's start/end window
+    #[serde(rename = "activeOn")]
+    active_on: Option<String>,
+    sort: Option<AdminChallengeSort>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AdminChallengeSort {
+    #[default]
+    Week,
+    CreatedAt,
+}
+
+impl AdminChallengeSort {
+    fn order_by(self) -> &'static str {
+        match self {
+            AdminChallengeSort::Week => "week DESC, id DESC",
+            AdminChallengeSort::CreatedAt => "created_at DESC, id DESC",
+        }
+    }
+}
+
+pub async fn admin_get_challenges(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppQuery(pagination): AppQuery<PaginationQuery>,
+    AppQuery(query): AppQuery<AdminChallengeQuery>,
+) -> Result<Json<PaginatedResponse<AdminChallengeResponse>>, AppError> {
+    let include_hidden = query.include_hidden.unwrap_or(false);
+    let include_deleted = query.include_deleted.unwrap_or(false);
+
+    // A challenge is active on a day if its window overlaps [day start, next day start)
+    let active_day = query
+        .active_on
+        .as_deref()
+        .map(|date| {
+            time::Date::parse(
+                date.trim(),
+                &time::format_description::well_known::Iso8601::DEFAULT,
+            )
+            .map(|date| {
+                let start = date.midnight().assume_utc();
+                (start, start + time::Duration::days(1))
+            })
+            .map_err(|_| {
+                AppError::BadRequest("activeOn must be a date like 2025-01-31".to_string())
+            })
+        })
+        .transpose()?;
+    let (day_start, day_end) = active_day.unzip();
+
+    let filters = r#"
+        ($1 OR visible = true)
+        AND ($2 OR deleted_at IS NULL)
+        AND ($3::INT IS NULL OR week = $3)
+        AND ($4::TIMESTAMPTZ IS NULL OR (
+            (start_date IS NULL OR start_date < $5)
+            AND (end_date IS NULL OR end_date >= $4)
+        ))
+    "#;
+
+    let (total,): (i64,) =
+        sqlx::query_as(&format!("SELECT COUNT(*) FROM challenges WHERE {filters}"))
+            .bind(include_hidden)
+            .bind(include_deleted)
+            .bind(query.week)
+            .bind(day_start)
+            .bind(day_end)
+            .fetch_one(&state.pool)
+            .await?;
+
+    let sql = format!(
+        "SELECT * FROM challenges WHERE {filters} ORDER BY {} LIMIT $6 OFFSET $7",
+        query.sort.unwrap_or_default().order_by()
+    );
+    let challenges: Vec<Challenge> = sqlx::query_as(&sql)
+        .bind(include_hidden)
+        .bind(include_deleted)
+        .bind(query.week)
+        .bind(day_start)
+        .bind(day_end)
+        .bind(pagination.page_size())
+        .bind(pagination.offset())
+        .fetch_all(&state.pool)
+        .await?;
+
+    let responses: Vec<AdminChallengeResponse> = challenges
+        .into_iter()
+        .map(AdminChallengeResponse::from)
+        .collect();
+
+    Ok(Json(PaginatedResponse {
+        items: responses,
+        page: pagination.page(),
+        page_size: pagination.page_size(),
+        total,
+    }))
+}
+
+pub async fn admin_get_challenge_by_id(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
+    let challenge: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let response = AdminChallengeResponse::from(challenge);
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+fn validate_challenge_window(
+    start_date: Option<time::OffsetDateTime>,
+    end_date: Option<time::OffsetDateTime>,
+) -> Result<(), AppError> {
+    if let (Some(start_date), Some(end_date)) = (start_date, end_date)
+        && start_date >= end_date
+    {
+        return Err(AppError::ValidationError(
+            "Challenge start date must be before its end date".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn admin_create_challenge(
+    auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppJson(req): AppJson<AdminCreateChallengeRequest>,
+) -> Result<Created<AdminItemResponse<AdminChallengeResponse>>, AppError> {
+    let visible = req.visible.unwrap_or(true);
+    let week = req.week.unwrap_or(1);
+    let challenge_url = req.challenge_url.unwrap_or_default();
 
-    Ok(Json(AdminItemResponse { item: response }))
+    validate_challenge_window(req.start_date, req.end_date)?;
+
+    let mut tx = state.pool.begin().await?;
+    lock_challenge_weeks(&mut *tx).await?;
+    ensure_week_available(&mut *tx, week, None).await?;
+
+    let challenge: Challenge = sqlx::query_as(
+        r#"
+        INSERT INTO challenges (title, description, start_date, end_date, visible, week, challenge_url, is_current, created_by, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, false, $8, NOW(), NOW())
+        RETURNING *
+        "#,
+    )
+    .bind(&req.title)
+    .bind(&req.description)
+    .bind(req.start_date)
+    .bind(req.end_date)
+    .bind(visible)
+    .bind(week)
+    .bind(&challenge_url)
+    .bind(auth.user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let response = AdminChallengeResponse::from(challenge);
+
+    Ok(Created::at(
+        format!("/admin/challenges/{}", response.id),
+        AdminItemResponse { item: response },
+    ))
 }
 
-pub async fn admin_update_resource(
-    _auth: AdminUser,
+pub async fn admin_update_challenge(
+    _auth: ModeratorUser,
     State(state): State<AppState>,
-    Path(id): Path<i32>,
-    Json(req): Json<AdminUpdateResourceRequest>,
-) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
-    let existing: Resource = sqlx::query_as("SELECT * FROM resources WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    AppPath(id): AppPath<i32>,
+    AppJson(req): AppJson<AdminUpdateChallengeRequest>,
+) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
+    let mut tx = state.pool.begin().await?;
+    lock_challenge_weeks(&mut *tx).await?;
+
+    let existing: Challenge =
+        sqlx::query_as("SELECT * FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound)?;
 
     let title = req.title.unwrap_or(existing.title);
-    let provider = req.provider.unwrap_or(existing.provider);
-    let cover_image = req.cover_image.or(existing.cover_image);
-    let notion_url = req.notion_url.or(existing.notion_url);
-    let instructor_name = req
-        .instructor
-        .as_ref()
-        .map(|i| i.name.clone())
-        .unwrap_or(existing.instructor_name);
-    let instructor_image = req
-        .instructor
-        .as_ref()
-        .and_then(|i| i.image.clone())
-        .or(existing.instructor_image);
+    let description = req.description.unwrap_or(existing.description);
+    let week = req.week.unwrap_or(existing.week);
+    let challenge_url = req.challenge_url.unwrap_or(existing.challenge_url);
+    let start_date = req.start_date.or(existing.start_date);
+    let end_date = req.end_date.or(existing.end_date);
     let visible = req.visible.unwrap_or(existing.visible);
 
-    let resource: Resource = sqlx::query_as(
+    // Checked against the merged values so a partial update can't invert the window
+    validate_challenge_window(start_date, end_date)?;
+    ensure_week_available(&mut *tx, week, Some(id)).await?;
+
+    let challenge: Challenge = sqlx::query_as(
         r#"
-        UPDATE resources 
-        SET title = $1, provider = $2, cover_image = $3, notion_url = $4, instructor_name = $5, instructor_image = $6, visible = $7, updated_at = NOW()
+        UPDATE challenges 
+        SET title = $1, description = $2, week = $3, challenge_url = $4, start_date = $5, end_date = $6, visible = $7, updated_at = NOW()
         WHERE id = $8
         RETURNING *
         "#,
     )
     .bind(&title)
-    .bind(&provider)
-    .bind(&cover_image)
-    .bind(&notion_url)
-    .bind(&instructor_name)
-    .bind(&instructor_image)
+    .bind(&description)
+    .bind(week)
+    .bind(&challenge_url)
+    .bind(start_date)
+    .bind(end_date)
     .bind(visible)
     .bind(id)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
-    let response = AdminResourceResponse {
-        id: resource.id,
-        title: resource.title,
-        provider: resource.provider,
-        cover_image: resource.cover_image,
-        notion_url: resource.notion_url,
-        instructor: Some(AdminInstructorResponse {
-            name: resource.instructor_name,
-            image: resource.instructor_image,
-        }),
-        quote: None,
-        visible: resource.visible,
-        created_at: resource.created_at,
-        updated_at: resource.updated_at,
-    };
+    tx.commit().await?;
+
+    let response = AdminChallengeResponse::from(challenge);
 
     Ok(Json(AdminItemResponse { item: response }))
 }
 
-pub async fn admin_delete_resource(
-    _auth: AdminUser,
+// Serializes writes that claim a week number so two requests can't both take a free one
+async fn lock_challenge_weeks<'e, E>(executor: E) -> Result<(), AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('challenges.week'))")
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+// Week numbers identify challenges to members, so live challenges may not share one.
+// Deleted challenges don't count; restoring one re-checks its week. The partial unique
+// index challenges_live_week_key is the backstop.
+async fn ensure_week_available<'e, E>(
+    executor: E,
+    week: i32,
+    except_id: Option<i32>,
+) -> Result<(), AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let taken: Option<(i32,)> = sqlx::query_as(
+        "SELECT id FROM challenges WHERE week = $1 AND deleted_at IS NULL AND ($2::INT IS NULL OR id <> $2) LIMIT 1",
+    )
+    .bind(week)
+    .bind(except_id)
+    .fetch_optional(executor)
+    .await?;
+
+    match taken {
+        Some((id,)) => Err(AppError::BadRequest(format!(
+            "Week {week} is already used by challenge {id}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+pub async fn admin_delete_challenge(
+    _auth: ModeratorUser,
     State(state): State<AppState>,
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
 ) -> Result<Json<AdminSuccessResponse>, AppError> {
-    let result = sqlx::query("DELETE FROM resources WHERE id = $1")
-        .bind(id)
-        .execute(&state.pool)
-        .await?;
+    let result = sqlx::query(
+        "UPDATE challenges SET deleted_at = NOW(), is_current = false, updated_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .execute(&state.pool)
+    .await?;
 
     if result.rows_affected() == 0 {
         return Err(AppError::NotFound);
@@ -516,14 +2495,14 @@ pub async fn admin_delete_resource(
     Ok(Json(AdminSuccessResponse { success: true }))
 }
 
-pub async fn admin_patch_resource_visibility(
-    _auth: AdminUser,
+pub async fn admin_patch_challenge_visibility(
+    _auth: ModeratorUser,
     State(state): State<AppState>,
-    Path(id): Path<i32>,
-    Json(req): Json<AdminVisibilityRequest>,
-) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
-    let resource: Resource = sqlx::query_as(
-        "UPDATE resources SET visible = $1, updated_at = NOW() WHERE id = $2 RETURNING *",
+    AppPath(id): AppPath<i32>,
+    AppJson(req): AppJson<AdminVisibilityRequest>,
+) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
+    let challenge: Challenge = sqlx::query_as(
+        "UPDATE challenges SET visible = $1, updated_at = NOW() WHERE id = $2 AND deleted_at IS NULL RETURNING *",
     )
     .bind(req.visible)
     .bind(id)
@@ -531,233 +2510,637 @@ pub async fn admin_patch_resource_visibility(
     .await?
     .ok_or(AppError::NotFound)?;
 
-    let response = AdminResourceResponse {
-        id: resource.id,
-        title: resource.title,
-        provider: resource.provider,
-        cover_image: resource.cover_image,
-        notion_url: resource.notion_url,
-        instructor: Some(AdminInstructorResponse {
-            name: resource.instructor_name,
-            image: resource.instructor_image,
-        }),
-        quote: None,
-        visible: resource.visible,
-        created_at: resource.created_at,
-        updated_at: resource.updated_at,
-    };
+    let response = AdminChallengeResponse::from(challenge);
 
     Ok(Json(AdminItemResponse { item: response }))
 }
 
-#[derive(Deserialize)]
-pub struct AdminChallengeQuery {
-    #[serde(rename = "includeHidden")]
-    include_hidden: Option<bool>,
-}
-
-pub async fn admin_get_challenges(
-    _auth: AdminUser,
+// Challenge counterpart of admin_patch_resources_visibility
+pub async fn admin_patch_challenges_visibility(
+    _auth: ModeratorUser,
     State(state): State<AppState>,
-    Query(query): Query<AdminChallengeQuery>,
-) -> Result<Json<AdminItemsResponse<AdminChallengeResponse>>, AppError> {
-    let include_hidden = query.include_hidden.unwrap_or(false);
+    AppJson(req): AppJson<AdminBatchVisibilityRequest>,
+) -> Result<Json<AdminBatchResponse<AdminChallengeResponse>>, AppError> {
+    validate_batch_ids(&req.ids)?;
 
-    let sql = if include_hidden {
-        "SELECT * FROM challenges ORDER BY id"
-    } else {
-        "SELECT * FROM challenges WHERE visible = true ORDER BY id"
-    };
+    let mut tx = state.pool.begin().await?;
+
+    let mut challenges: Vec<Challenge> = sqlx::query_as(
+        "UPDATE challenges SET visible = $1, updated_at = NOW() WHERE id = ANY($2) AND deleted_at IS NULL RETURNING *",
+    )
+    .bind(req.visible)
+    .bind(&req.ids)
+    .fetch_all(&mut *tx)
+    .await?;
 
-    let challenges: Vec<Challenge> = sqlx::query_as(sql).fetch_all(&state.pool).await?;
+    tx.commit().await?;
 
-    let responses: Vec<AdminChallengeResponse> = challenges
+    challenges.sort_by_key(|c| c.id);
+    let items: Vec<AdminChallengeResponse> = challenges
         .into_iter()
-        .map(|c| AdminChallengeResponse {
-            id: c.id,
-            title: c.title,
-            description: c.description,
-            start_date: c.start_date,
-            end_date: c.end_date,
-            visible: c.visible,
-            created_at: c.created_at,
-            updated_at: c.updated_at,
-        })
+        .map(AdminChallengeResponse::from)
         .collect();
 
-    Ok(Json(AdminItemsResponse { items: responses }))
+    Ok(Json(AdminBatchResponse {
+        count: items.len(),
+        items,
+    }))
 }
 
-pub async fn admin_get_challenge_by_id(
-    _auth: AdminUser,
+// Most ids a single bulk request may touch
+const MAX_BATCH_IDS: usize = 500;
+
+fn validate_batch_ids(ids: &[i32]) -> Result<(), AppError> {
+    if ids.len() > MAX_BATCH_IDS {
+        return Err(AppError::ValidationError(format!(
+            "At most {MAX_BATCH_IDS} ids can be updated at once"
+        )));
+    }
+    Ok(())
+}
+
+// Pins a challenge as the current one, overriding the date windows
+pub async fn admin_set_current_challenge(
+    _auth: ModeratorUser,
     State(state): State<AppState>,
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
 ) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
-    let challenge: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
-        .bind(id)
+    let mut tx = state.pool.begin().await?;
+
+    // Serialize concurrent set-current calls; the partial unique index is the backstop
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('challenges.is_current'))")
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        "UPDATE challenges SET is_current = false, updated_at = NOW() WHERE is_current AND id <> $1",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    let challenge: Challenge = sqlx::query_as(
+        "UPDATE challenges SET is_current = true, updated_at = NOW() WHERE id = $1 AND deleted_at IS NULL RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    tx.commit().await?;
+
+    let response = AdminChallengeResponse::from(challenge);
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+// Undoes a soft delete
+pub async fn admin_restore_challenge(
+    _auth: ModeratorUser,
+    State(state): State<AppState>,
+    AppPath(id): AppPath<i32>,
+) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
+    let mut tx = state.pool.begin().await?;
+    lock_challenge_weeks(&mut *tx).await?;
+
+    let (week,): (i32,) =
+        sqlx::query_as("SELECT week FROM challenges WHERE id = $1 AND deleted_at IS NOT NULL")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound)?;
+    ensure_week_available(&mut *tx, week, Some(id)).await?;
+
+    let challenge: Challenge = sqlx::query_as(
+        "UPDATE challenges SET deleted_at = NULL, updated_at = NOW() WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let response = AdminChallengeResponse::from(challenge);
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+#[derive(Deserialize)]
+pub struct AdminSubmissionQuery {
+    status: Option<SubmissionStatus>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmissionStatus {
+    Graded,
+    Ungraded,
+}
+
+impl SubmissionStatus {
+    fn filter_sql(self) -> &'static str {
+        match self {
+            SubmissionStatus::Graded => "s.score IS NOT NULL",
+            SubmissionStatus::Ungraded => "s.score IS NULL",
+        }
+    }
+}
+
+// Oldest first, so graders work through the queue in the order it arrived
+pub async fn admin_get_challenge_submissions(
+    _auth: AdminUser,
+    State(state): State<AppState>,
+    AppPath(challenge_id): AppPath<i32>,
+    AppQuery(pagination): AppQuery<PaginationQuery>,
+    AppQuery(query): AppQuery<AdminSubmissionQuery>,
+) -> Result<Json<PaginatedResponse<AdminChallengeSubmissionResponse>>, AppError> {
+    sqlx::query("SELECT 1 FROM challenges WHERE id = $1")
+        .bind(challenge_id)
         .fetch_optional(&state.pool)
         .await?
         .ok_or(AppError::NotFound)?;
 
-    let response = AdminChallengeResponse {
-        id: challenge.id,
-        title: challenge.title,
-        description: challenge.description,
-        start_date: challenge.start_date,
-        end_date: challenge.end_date,
-        visible: challenge.visible,
-        created_at: challenge.created_at,
-        updated_at: challenge.updated_at,
-    };
+    let filter = query.status.map_or("TRUE", SubmissionStatus::filter_sql);
+
+    let (total,): (i64,) = sqlx::query_as(&format!(
+        "SELECT COUNT(*) FROM challenge_submissions s WHERE s.challenge_id = $1 AND {filter}"
+    ))
+    .bind(challenge_id)
+    .fetch_one(&state.pool)
+    .await?;
+
+    let sql = format!(
+        r#"
+        SELECT s.id, s.challenge_id, s.user_id, u.full_name AS user_name, u.email AS user_email,
+               s.submission_url, s.score, s.scored_at, s.created_at, s.updated_at
+        FROM challenge_submissions s
+        JOIN users u ON u.id = s.user_id
+        WHERE s.challenge_id = $1 AND {filter}
+        ORDER BY s.created_at, s.id
+        LIMIT $2 OFFSET $3
+        "#
+    );
+    let items: Vec<AdminChallengeSubmissionResponse> = sqlx::query_as(&sql)
+        .bind(challenge_id)
+        .bind(pagination.page_size())
+        .bind(pagination.offset())
+        .fetch_all(&state.pool)
+        .await?;
+
+    Ok(Json(PaginatedResponse {
+        items,
+        page: pagination.page(),
+        page_size: pagination.page_size(),
+        total,
+    }))
+}
+
+// Keeps a single score from pushing users.points past what an INTEGER holds
+const MAX_SUBMISSION_SCORE: i32 = 10_000;
+
+pub async fn admin_score_submission(
+    _auth: AdminUser,
+    State(state): State<AppState>,
+    AppPath((challenge_id, submission_id)): AppPath<(i32, Uuid)>,
+    AppJson(req): AppJson<AdminScoreSubmissionRequest>,
+) -> Result<Json<AdminItemResponse<AdminSubmissionResponse>>, AppError> {
+    if req.score < 0 {
+        return Err(AppError::ValidationError(
+            "Score must not be negative".to_string(),
+        ));
+    }
+    if req.score > MAX_SUBMISSION_SCORE {
+        return Err(AppError::ValidationError(format!(
+            "Score must be at most {MAX_SUBMISSION_SCORE}"
+        )));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Lock the submission so concurrent re-scores apply their deltas one at a time
+    let existing: ChallengeSubmission = sqlx::query_as(
+        "SELECT * FROM challenge_submissions WHERE id = $1 AND challenge_id = $2 FOR UPDATE",
+    )
+    .bind(submission_id)
+    .bind(challenge_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    // Re-scoring only moves the user's points by the difference
+    let delta = req.score - existing.score.unwrap_or(0);
+
+    let submission: ChallengeSubmission = sqlx::query_as(
+        r#"
+        UPDATE challenge_submissions
+        SET score = $1, scored_at = NOW(), updated_at = NOW()
+        WHERE id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(req.score)
+    .bind(submission_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if delta != 0 {
+        sqlx::query("UPDATE users SET points = points + $1 WHERE id = $2")
+            .bind(delta)
+            .bind(submission.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO points_history (user_id, delta, reason, challenge_id, created_at) VALUES ($1, $2, 'challenge_score', $3, NOW())",
+        )
+        .bind(submission.user_id)
+        .bind(delta)
+        .bind(submission.challenge_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if existing.score.is_none() {
+        sqlx::query(
+            "UPDATE user_stats SET challenges_taken = challenges_taken + 1, updated_at = NOW() WHERE user_id = $1",
+        )
+        .bind(submission.user_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if delta != 0 {
+        recompute_ranks(&mut *tx).await?;
+    }
+
+    notify(
+        &mut *tx,
+        submission.user_id,
+        notifications::SUBMISSION_SCORED,
+        serde_json::json!({
+            "challengeId": submission.challenge_id,
+            "submissionId": submission.id,
+            "score": req.score,
+        }),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    if delta != 0 {
+        publish_leaderboard(&state).await;
+    }
+
+    let response = AdminSubmissionResponse {
+        id: submission.id,
+        challenge_id: submission.challenge_id,
+        user_id: submission.user_id,
+        submission_url: submission.submission_url,
+        score: submission.score,
+        scored_at: submission.scored_at,
+        created_at: submission.created_at,
+        updated_at: submission.updated_at,
+    };
+
+    Ok(Json(AdminItemResponse { item: response }))
+}
+
+pub async fn admin_recompute_ranks(
+    _auth: AdminUser,
+    State(state): State<AppState>,
+) -> Result<Json<AdminRecomputeRanksResponse>, AppError> {
+    let updated = recompute_ranks(&state.pool).await?;
+
+    Ok(Json(AdminRecomputeRanksResponse {
+        success: true,
+        updated,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct AdminUserQuery {
+    q: Option<String>,
+    sort: Option<AdminUserSort>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AdminUserSort {
+    #[default]
+    Newest,
+    Oldest,
+    Name,
+    Email,
+    Points,
+}
+
+impl AdminUserSort {
+    fn order_by(self) -> &'static str {
+        match self {
+            AdminUserSort::Newest => "created_at DESC, id",
+            AdminUserSort::Oldest => "created_at ASC, id",
+            AdminUserSort::Name => "full_name ASC, id",
+            AdminUserSort::Email => "email ASC, id",
+            AdminUserSort::Points => "points DESC, created_at ASC, id",
+        }
+    }
+}
+
+// Promotes the BOOTSTRAP_ADMIN_EMAIL account to admin so a fresh deployment doesn't need
+// database access to get its first admin. Requires BOOTSTRAP_ADMIN_TOKEN, and does
+// nothing once any admin exists. A 404 unless both variables are set.
+pub async fn admin_bootstrap(
+    State(state): State<AppState>,
+    AppJson(req): AppJson<AdminBootstrapRequest>,
+) -> Result<Json<AdminBootstrapResponse>, AppError> {
+    let Some(bootstrap) = &state.admin_bootstrap else {
+        return Err(AppError::NotFound);
+    };
+    if !constant_time_eq(req.token.as_bytes(), bootstrap.token.as_bytes()) {
+        return Err(AppError::AuthError);
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Two concurrent calls must not both see "no admin yet"
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('admin_bootstrap'))")
+        .execute(&mut *tx)
+        .await?;
+
+    let (admin_exists,): (bool,) =
+        sqlx::query_as("SELECT EXISTS(SELECT 1 FROM users WHERE role = 'admin')")
+            .fetch_one(&mut *tx)
+            .await?;
+    if admin_exists {
+        return Ok(Json(AdminBootstrapResponse { promoted: false }));
+    }
+
+    let (user_id,): (Uuid,) =
+        sqlx::query_as("UPDATE users SET role = 'admin' WHERE LOWER(email) = $1 RETURNING id")
+            .bind(&bootstrap.email)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest(
+                    "Sign up with the bootstrap admin email before bootstrapping".to_string(),
+                )
+            })?;
+
+    audit::record(
+        &mut *tx,
+        Some(user_id),
+        audit::ADMIN_BOOTSTRAPPED,
+        serde_json::json!({ "email": bootstrap.email }),
+    )
+    .await?;
+
+    tx.commit().await?;
 
-    Ok(Json(AdminItemResponse { item: response }))
+    tracing::info!("Promoted {} to admin via bootstrap", bootstrap.email);
+
+    Ok(Json(AdminBootstrapResponse { promoted: true }))
 }
 
-pub async fn admin_create_challenge(
+// Compares secrets without leaking how many leading bytes matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// One round trip for the dashboard card; the week starts Monday 00:00 UTC
+pub async fn admin_get_stats(
     _auth: AdminUser,
     State(state): State<AppState>,
-    Json(req): Json<AdminCreateChallengeRequest>,
-) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
-    let visible = req.visible.unwrap_or(true);
-    let week = req.week.unwrap_or(1);
-    let challenge_url = req.challenge_url.unwrap_or_default();
-
-    let challenge: Challenge = sqlx::query_as(
+) -> Result<Json<AdminStatsResponse>, AppError> {
+    let stats: AdminStatsResponse = sqlx::query_as(
         r#"
-        INSERT INTO challenges (title, description, start_date, end_date, visible, week, challenge_url, is_current, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, false, NOW(), NOW())
-        RETURNING *
+        SELECT
+            u.total_users, u.completed_profiles,
+            r.total_resources, r.visible_resources, r.hidden_resources,
+            c.total_challenges, m.unhandled_contact_messages, s.submissions_this_week
+        FROM
+            (SELECT COUNT(*) AS total_users,
+                    COUNT(*) FILTER (WHERE university_major_set) AS completed_profiles
+             FROM users) u,
+            (SELECT COUNT(*) AS total_resources,
+                    COUNT(*) FILTER (WHERE visible) AS visible_resources,
+                    COUNT(*) FILTER (WHERE NOT visible) AS hidden_resources
+             FROM resources WHERE deleted_at IS NULL) r,
+            (SELECT COUNT(*) AS total_challenges
+             FROM challenges WHERE deleted_at IS NULL) c,
+            (SELECT COUNT(*) AS unhandled_contact_messages
+             FROM contact_messages WHERE NOT handled) m,
+            (SELECT COUNT(*) AS submissions_this_week
+             FROM challenge_submissions
+             WHERE created_at >= date_trunc('week', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC') s
         "#,
     )
-    .bind(&req.title)
-    .bind(&req.description)
-    .bind(req.start_date)
-    .bind(req.end_date)
-    .bind(visible)
-    .bind(week)
-    .bind(&challenge_url)
     .fetch_one(&state.pool)
     .await?;
 
-    let response = AdminChallengeResponse {
-        id: challenge.id,
-        title: challenge.title,
-        description: challenge.description,
-        start_date: challenge.start_date,
-        end_date: challenge.end_date,
-        visible: challenge.visible,
-        created_at: challenge.created_at,
-        updated_at: challenge.updated_at,
-    };
-
-    Ok(Json(AdminItemResponse { item: response }))
+    Ok(Json(stats))
 }
 
-pub async fn admin_update_challenge(
+pub async fn admin_get_users(
     _auth: AdminUser,
     State(state): State<AppState>,
-    Path(id): Path<i32>,
-    Json(req): Json<AdminUpdateChallengeRequest>,
-) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
-    let existing: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
-
-    let title = req.title.unwrap_or(existing.title);
-    let description = req.description.unwrap_or(existing.description);
-    let week = req.week.unwrap_or(existing.week);
-    let challenge_url = req.challenge_url.unwrap_or(existing.challenge_url);
-    let start_date = req.start_date.or(existing.start_date);
-    let end_date = req.end_date.or(existing.end_date);
-    let visible = req.visible.unwrap_or(existing.visible);
-
-    let challenge: Challenge = sqlx::query_as(
-        r#"
-        UPDATE challenges 
-        SET title = $1, description = $2, week = $3, challenge_url = $4, start_date = $5, end_date = $6, visible = $7, updated_at = NOW()
-        WHERE id = $8
-        RETURNING *
-        "#,
+    AppQuery(pagination): AppQuery<PaginationQuery>,
+    AppQuery(query): AppQuery<AdminUserQuery>,
+) -> Result<Json<PaginatedResponse<AdminUserResponse>>, AppError> {
+    // Match the search term literally inside the ILIKE pattern
+    let pattern = query
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| {
+            let escaped = q
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{escaped}%")
+        });
+
+    let (total,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM users WHERE ($1::TEXT IS NULL OR full_name ILIKE $1 OR email ILIKE $1)",
     )
-    .bind(&title)
-    .bind(&description)
-    .bind(week)
-    .bind(&challenge_url)
-    .bind(start_date)
-    .bind(end_date)
-    .bind(visible)
-    .bind(id)
+    .bind(&pattern)
     .fetch_one(&state.pool)
     .await?;
 
-    let response = AdminChallengeResponse {
-        id: challenge.id,
-        title: challenge.title,
-        description: challenge.description,
-        start_date: challenge.start_date,
-        end_date: challenge.end_date,
-        visible: challenge.visible,
-        created_at: challenge.created_at,
-        updated_at: challenge.updated_at,
-    };
+    let sql = format!(
+        "SELECT * FROM users WHERE ($1::TEXT IS NULL OR full_name ILIKE $1 OR email ILIKE $1) ORDER BY {} LIMIT $2 OFFSET $3",
+        query.sort.unwrap_or_default().order_by()
+    );
+    let users: Vec<User> = sqlx::query_as(&sql)
+        .bind(&pattern)
+        .bind(pagination.page_size())
+        .bind(pagination.offset())
+        .fetch_all(&state.pool)
+        .await?;
 
-    Ok(Json(AdminItemResponse { item: response }))
+    let items: Vec<AdminUserResponse> = users
+        .into_iter()
+        .map(|u| AdminUserResponse {
+            id: u.id,
+            email: u.email,
+            full_name: u.full_name,
+            role: u.role,
+            points: u.points,
+            rank: u.rank,
+            created_at: u.created_at,
+        })
+        .collect();
+
+    Ok(Json(PaginatedResponse {
+        items,
+        page: pagination.page(),
+        page_size: pagination.page_size(),
+        total,
+    }))
 }
 
-pub async fn admin_delete_challenge(
+// Serializes everything that can take away an admin, so two concurrent demotions or
+// deletions can't both see another admin left
+async fn lock_admin_roles<'e, E>(executor: E) -> Result<(), AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('users.admins'))")
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+async fn count_admins<'e, E>(executor: E) -> Result<i64, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let (admins,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE role = 'admin'")
+        .fetch_one(executor)
+        .await?;
+    Ok(admins)
+}
+
+pub async fn admin_update_user(
     _auth: AdminUser,
     State(state): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<Json<AdminSuccessResponse>, AppError> {
-    let result = sqlx::query("DELETE FROM challenges WHERE id = $1")
+    AppPath(id): AppPath<Uuid>,
+    AppJson(req): AppJson<AdminUpdateUserRequest>,
+) -> Result<Json<AdminItemResponse<AdminUserResponse>>, AppError> {
+    if let Some(ref role) = req.role
+        && !ROLES.contains(&role.as_str())
+    {
+        return Err(AppError::ValidationError(format!(
+            "Role must be one of: {}",
+            ROLES.join(", ")
+        )));
+    }
+
+    if req.points.is_some_and(|points| points < 0) {
+        return Err(AppError::ValidationError(
+            "Points must not be negative".to_string(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Taken before any row lock, in the same order as delete_user_account
+    lock_admin_roles(&mut *tx).await?;
+
+    let existing: User = sqlx::query_as("SELECT * FROM users WHERE id = $1 FOR UPDATE")
         .bind(id)
-        .execute(&state.pool)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let role = req.role.unwrap_or(existing.role.clone());
+
+    if existing.role == "admin" && role != "admin" && count_admins(&mut *tx).await? <= 1 {
+        return Err(AppError::BadRequest(
+            "Cannot demote the last remaining admin".to_string(),
+        ));
+    }
+
+    let points = req.points.unwrap_or(existing.points);
+    let delta = points - existing.points;
+
+    let user: User =
+        sqlx::query_as("UPDATE users SET role = $1, points = $2 WHERE id = $3 RETURNING *")
+            .bind(&role)
+            .bind(points)
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    let user = if delta != 0 {
+        sqlx::query(
+            "INSERT INTO points_history (user_id, delta, reason, created_at) VALUES ($1, $2, 'admin_adjustment', NOW())",
+        )
+        .bind(id)
+        .bind(delta)
+        .execute(&mut *tx)
         .await?;
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound);
+        recompute_ranks(&mut *tx).await?;
+
+        // Re-read so the response carries the recomputed rank
+        sqlx::query_as("SELECT * FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?
+    } else {
+        user
+    };
+
+    tx.commit().await?;
+
+    if delta != 0 {
+        publish_leaderboard(&state).await;
     }
 
-    Ok(Json(AdminSuccessResponse { success: true }))
+    let response = AdminUserResponse {
+        id: user.id,
+        email: user.email,
+        full_name: user.full_name,
+        role: user.role,
+        points: user.points,
+        rank: user.rank,
+        created_at: user.created_at,
+    };
+
+    Ok(Json(AdminItemResponse { item: response }))
 }
 
-pub async fn admin_patch_challenge_visibility(
-    _auth: AdminUser,
+// User profile management endpoints
+
+pub async fn get_public_user_profile(
+    OptionalAuthUser(auth): OptionalAuthUser,
     State(state): State<AppState>,
-    Path(id): Path<i32>,
-    Json(req): Json<AdminVisibilityRequest>,
-) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
-    let challenge: Challenge = sqlx::query_as(
-        "UPDATE challenges SET visible = $1, updated_at = NOW() WHERE id = $2 RETURNING *",
+    AppPath(id): AppPath<Uuid>,
+) -> Result<Json<PublicUserProfileResponse>, AppError> {
+    // Private profiles look nonexistent to everyone but their owner
+    let viewer_id = auth.map(|auth| auth.user_id);
+
+    let profile: PublicUserProfileResponse = sqlx::query_as(
+        r#"
+        SELECT id, full_name, image, points, rank, university, major
+        FROM users
+        WHERE id = $1 AND (profile_public OR id = $2)
+        "#,
     )
-    .bind(req.visible)
     .bind(id)
+    .bind(viewer_id)
     .fetch_optional(&state.pool)
     .await?
     .ok_or(AppError::NotFound)?;
 
-    let response = AdminChallengeResponse {
-        id: challenge.id,
-        title: challenge.title,
-        description: challenge.description,
-        start_date: challenge.start_date,
-        end_date: challenge.end_date,
-        visible: challenge.visible,
-        created_at: challenge.created_at,
-        updated_at: challenge.updated_at,
-    };
-
-    Ok(Json(AdminItemResponse { item: response }))
+    Ok(Json(profile))
 }
 
-// User profile management endpoints
-
 pub async fn update_user_profile(
     auth: AuthUser,
     State(state): State<AppState>,
-    Json(req): Json<UpdateProfileRequest>,
+    AppJson(req): AppJson<UpdateProfileRequest>,
 ) -> Result<Json<UpdateProfileResponse>, AppError> {
     // Get current user data
     let current_user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
@@ -766,36 +3149,53 @@ pub async fn update_user_profile(
         .await?
         .ok_or(AppError::NotFound)?;
 
+    let new_email = req.email.as_deref().map(normalize_email).transpose()?;
+    let new_phone_num = req.phone_num.as_deref().map(normalize_phone).transpose()?;
+
     // Check if email is being changed and if it's already taken
-    if let Some(ref new_email) = req.email {
-        if new_email != &current_user.email {
-            let existing_user = sqlx::query("SELECT id FROM users WHERE email = $1 AND id != $2")
+    if let Some(ref new_email) = new_email
+        && new_email != &current_user.email
+    {
+        let existing_user =
+            sqlx::query("SELECT id FROM users WHERE LOWER(email) = $1 AND id != $2")
                 .bind(new_email)
                 .bind(auth.user_id)
                 .fetch_optional(&state.pool)
                 .await?;
 
-            if existing_user.is_some() {
-                return Err(AppError::UserExists);
-            }
+        if existing_user.is_some() {
+            return Err(AppError::UserExists);
         }
     }
 
+    // Hosted files only reach a profile through the avatar upload, which files them under
+    // the uploader; otherwise members could claim (and later delete) each other's uploads
+    if let Some(image) = &req.image
+        && current_user.image.as_ref() != Some(image)
+        && state.storage.key_for(image).is_some()
+    {
+        return Err(AppError::ValidationError(
+            "Upload a new picture instead of linking to an uploaded file".to_string(),
+        ));
+    }
+
     let full_name = req.full_name.unwrap_or(current_user.full_name);
-    let email = req.email.unwrap_or(current_user.email);
+    let email = new_email.unwrap_or(current_user.email);
     let image = req.image.or(current_user.image);
+    let phone_num = new_phone_num.or(current_user.phone_num);
 
     let updated_user: User = sqlx::query_as(
         r#"
         UPDATE users 
-        SET full_name = $1, email = $2, image = $3
-        WHERE id = $4
+        SET full_name = $1, email = $2, image = $3, phone_num = $4
+        WHERE id = $5
         RETURNING *
         "#,
     )
     .bind(&full_name)
     .bind(&email)
     .bind(&image)
+    .bind(&phone_num)
     .bind(auth.user_id)
     .fetch_one(&state.pool)
     .await?;
@@ -805,139 +3205,265 @@ pub async fn update_user_profile(
         full_name: updated_user.full_name,
         email: updated_user.email,
         image: updated_user.image,
+        phone_num: updated_user.phone_num,
         role: updated_user.role,
     }))
 }
 
-// Helper function to save uploaded file
-async fn save_uploaded_file(
-    _field_name: &str,
+// Trims tags, drops empty ones and removes case-insensitive duplicates,
+// keeping the first spelling we saw
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+
+    normalized
+}
+
+// Writes an already decoded upload to storage. The client's extension is swapped for the
+// decoded format's, so e.g. a PNG named x.html is still stored and served as a PNG.
+async fn store_upload(
+    storage: &dyn FileStorage,
     file_name: &str,
+    kind: ImageKind,
     data: &[u8],
     subdirectory: &str,
 ) -> Result<String, AppError> {
-    use tokio::io::AsyncWriteExt;
+    let file_name = sanitize_file_name(file_name)?;
+    let stem = file_name
+        .rsplit_once('.')
+        .map_or(file_name.as_str(), |(stem, _)| stem);
+    let key = format!(
+        "{subdirectory}/{}_{stem}.{}",
+        Uuid::new_v4(),
+        kind.extension
+    );
 
-    let upload_dir = format!("uploads/{subdirectory}");
+    tracing::info!("Saving file: {}", key);
 
-    tracing::info!("Creating directory: {}", upload_dir);
+    let result_url = storage
+        .save(&key, data, Some(kind.content_type))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save file {}: {}", key, e);
+            AppError::InternalError(anyhow::anyhow!("Failed to save file: {e}"))
+        })?;
 
-    tokio::fs::create_dir_all(&upload_dir).await.map_err(|e| {
-        tracing::error!("Failed to create directory {}: {}", upload_dir, e);
-        AppError::InternalError(anyhow::anyhow!("Failed to create upload directory: {e}"))
-    })?;
+    tracing::info!("File saved successfully: {}", result_url);
+
+    Ok(result_url)
+}
+
+// Helper function to save an uploaded image without a thumbnail. It is still decoded
+// first so only images we accept end up in storage.
+async fn save_uploaded_file(
+    storage: &dyn FileStorage,
+    file_name: &str,
+    data: &[u8],
+    subdirectory: &str,
+) -> Result<String, AppError> {
+    let source = data.to_vec();
+    let kind = tokio::task::spawn_blocking(move || verify_image(&source))
+        .await
+        .map_err(|e| AppError::InternalError(e.into()))??;
 
-    let unique_filename = format!("{}_{}", Uuid::new_v4(), file_name);
-    let file_path = format!("{upload_dir}/{unique_filename}");
+    store_upload(storage, file_name, kind, data, subdirectory).await
+}
 
-    tracing::info!("Saving file to: {}", file_path);
+// Where an uploaded image and its thumbnail ended up, and how large the original is
+struct StoredImage {
+    url: String,
+    thumbnail_url: String,
+    width: u32,
+    height: u32,
+}
 
-    let mut file = tokio::fs::File::create(&file_path).await.map_err(|e| {
-        tracing::error!("Failed to create file {}: {}", file_path, e);
-        AppError::InternalError(anyhow::anyhow!("Failed to create file: {e}"))
-    })?;
+// Helper function to save an uploaded image together with a thumbnail next to it
+async fn save_uploaded_image(
+    storage: &dyn FileStorage,
+    file_name: &str,
+    data: &[u8],
+    subdirectory: &str,
+) -> Result<StoredImage, AppError> {
+    // Decoding and resizing is CPU-bound, keep it off the async workers
+    let source = data.to_vec();
+    let thumbnail = tokio::task::spawn_blocking(move || make_thumbnail(&source, THUMBNAIL_WIDTH))
+        .await
+        .map_err(|e| AppError::InternalError(e.into()))??;
 
-    file.write_all(data).await.map_err(|e| {
-        tracing::error!("Failed to write file {}: {}", file_path, e);
-        AppError::InternalError(anyhow::anyhow!("Failed to write file: {e}"))
+    // The client's Content-Type is not trusted; the decoder decided what this is
+    let kind = thumbnail.kind;
+    let url = store_upload(storage, file_name, kind, data, subdirectory).await?;
+    let key = storage.key_for(&url).ok_or_else(|| {
+        AppError::InternalError(anyhow::anyhow!("Storage returned a foreign URL: {url}"))
     })?;
 
-    let result_url = format!("/{upload_dir}/{unique_filename}");
-    tracing::info!("File saved successfully: {}", result_url);
+    let thumbnail_url = match storage
+        .save(
+            &thumbnail_key(&key),
+            &thumbnail.bytes,
+            Some(kind.content_type),
+        )
+        .await
+    {
+        Ok(thumbnail_url) => thumbnail_url,
+        Err(e) => {
+            tracing::error!("Failed to save thumbnail for {}: {}", key, e);
+            // Don't leave the original behind without its thumbnail
+            if let Err(e) = storage.delete(&key).await {
+                tracing::warn!("Failed to remove file {}: {}", key, e);
+            }
+            return Err(AppError::InternalError(anyhow::anyhow!(
+                "Failed to save thumbnail: {e}"
+            )));
+        }
+    };
 
-    Ok(result_url)
+    Ok(StoredImage {
+        url,
+        thumbnail_url,
+        width: thumbnail.source_width,
+        height: thumbnail.source_height,
+    })
+}
+
+// Helper function to delete a previously uploaded file (and its thumbnail, if any)
+// by its public URL. URLs we don't host (e.g. Google profile pictures) are left alone.
+async fn remove_uploaded_file(storage: &dyn FileStorage, url: &str) {
+    let Some(key) = storage.key_for(url) else {
+        return;
+    };
+
+    if let Err(e) = storage.delete(&key).await {
+        tracing::warn!("Failed to remove file {}: {}", key, e);
+    }
+
+    // Files uploaded before thumbnails existed don't have one
+    if let Err(e) = storage.delete(&thumbnail_key(&key)).await {
+        tracing::debug!("No thumbnail removed for {}: {}", key, e);
+    }
 }
 
 // Admin resource endpoints with multipart form data
 
-pub async fn admin_create_resource_multipart(
-    _auth: AdminUser,
-    State(state): State<AppState>,
+fn parse_quote_id(text: &str) -> Result<i32, AppError> {
+    text.trim()
+        .parse()
+        .map_err(|_| AppError::BadRequest("quoteId must be a number".to_string()))
+}
+
+// Multipart forms send the quote as two separate fields; blank fields mean no quote
+fn inline_quote(text: Option<String>, author: Option<String>) -> Option<AdminQuoteRequest> {
+    let text = text.filter(|t| !t.trim().is_empty());
+    let author = author.filter(|a| !a.trim().is_empty());
+    if text.is_none() && author.is_none() {
+        return None;
+    }
+
+    Some(AdminQuoteRequest {
+        text: text.unwrap_or_default(),
+        author: author.unwrap_or_default(),
+    })
+}
+
+async fn new_resource_from_multipart(
+    state: &AppState,
     mut multipart: axum::extract::Multipart,
-) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
+) -> Result<(NewResource, QuoteChoice), AppError> {
     tracing::info!("Starting multipart resource creation");
 
     let mut title: Option<String> = None;
     let mut provider: Option<String> = None;
     let mut cover_image: Option<String> = None;
+    let mut cover_thumbnail: Option<String> = None;
     let mut notion_url: Option<String> = None;
     let mut instructor_name: Option<String> = None;
     let mut instructor_image: Option<String> = None;
+    let mut instructor_title: Option<String> = None;
+    let mut instructor_bio: Option<String> = None;
     let mut visible: Option<bool> = None;
+    let mut tags: Option<Vec<String>> = None;
+    let mut quote_text: Option<String> = None;
+    let mut quote_author: Option<String> = None;
+    let mut quote_id: Option<i32> = None;
 
     while let Some(field) = multipart.next_field().await.map_err(|e| {
         tracing::error!("Error reading multipart field: {}", e);
-        AppError::InternalError(e.into())
+        AppError::from(e)
     })? {
         let field_name = field.name().unwrap_or("").to_string();
         tracing::info!("Processing field: {}", field_name);
 
         match field_name.as_str() {
             "title" => {
-                title = Some(
-                    field
-                        .text()
-                        .await
-                        .map_err(|e| AppError::InternalError(e.into()))?,
-                );
+                title = Some(field.text().await?);
             }
             "provider" => {
-                provider = Some(
-                    field
-                        .text()
-                        .await
-                        .map_err(|e| AppError::InternalError(e.into()))?,
-                );
+                provider = Some(field.text().await?);
             }
             "notionUrl" => {
-                let text = field
-                    .text()
-                    .await
-                    .map_err(|e| AppError::InternalError(e.into()))?;
-                if !text.is_empty() {
-                    notion_url = Some(text);
-                }
+                notion_url = Some(field.text().await?);
             }
             "instructorName" => {
-                let text = field
-                    .text()
-                    .await
-                    .map_err(|e| AppError::InternalError(e.into()))?;
+                let text = field.text().await?;
                 if !text.is_empty() {
                     instructor_name = Some(text);
                 }
             }
-            "quoteText" | "quoteAuthor" => {
-                // Ignore quote fields - quotes are now in a separate table
-                let _ = field.text().await;
+            "instructorTitle" => {
+                instructor_title = Some(field.text().await?);
+            }
+            "instructorBio" => {
+                instructor_bio = Some(field.text().await?);
+            }
+            "quoteText" => {
+                quote_text = Some(field.text().await?);
+            }
+            "quoteAuthor" => {
+                quote_author = Some(field.text().await?);
+            }
+            "quoteId" => {
+                let text = field.text().await?;
+                if !text.is_empty() {
+                    quote_id = Some(parse_quote_id(&text)?);
+                }
+            }
+            "tags" => {
+                // Accept both a comma-separated list and repeated `tags` fields
+                let text = field.text().await?;
+                tags.get_or_insert_with(Vec::new)
+                    .extend(text.split(',').map(str::to_string));
             }
             "visible" => {
-                let text = field
-                    .text()
-                    .await
-                    .map_err(|e| AppError::InternalError(e.into()))?;
+                let text = field.text().await?;
                 visible = Some(text == "true" || text == "1");
             }
             "coverImage" => {
                 if let Some(file_name) = field.file_name().map(|s| s.to_string()) {
-                    let data = field
-                        .bytes()
-                        .await
-                        .map_err(|e| AppError::InternalError(e.into()))?;
-                    let url =
-                        save_uploaded_file("coverImage", &file_name, &data, "resources/covers")
-                            .await?;
+                    let data = field.bytes().await?;
+                    let StoredImage {
+                        url, thumbnail_url, ..
+                    } = save_uploaded_image(
+                        state.storage.as_ref(),
+                        &file_name,
+                        &data,
+                        "resources/covers",
+                    )
+                    .await?;
                     cover_image = Some(url);
+                    cover_thumbnail = Some(thumbnail_url);
                 }
             }
             "instructorImage" => {
                 if let Some(file_name) = field.file_name().map(|s| s.to_string()) {
-                    let data = field
-                        .bytes()
-                        .await
-                        .map_err(|e| AppError::InternalError(e.into()))?;
+                    let data = field.bytes().await?;
                     let url = save_uploaded_file(
-                        "instructorImage",
+                        state.storage.as_ref(),
                         &file_name,
                         &data,
                         "resources/instructors",
@@ -950,196 +3476,228 @@ pub async fn admin_create_resource_multipart(
         }
     }
 
-    let title =
-        title.ok_or_else(|| AppError::BadRequest("Missing required field: title".to_string()))?;
-    let provider = provider
-        .ok_or_else(|| AppError::BadRequest("Missing required field: provider".to_string()))?;
+    let title = title.unwrap_or_default();
+    let provider = provider.unwrap_or_default();
     let instructor_name = instructor_name.unwrap_or_default();
-    let visible = visible.unwrap_or(true);
-
-    let resource: Resource = sqlx::query_as(
-        r#"
-        INSERT INTO resources (title, provider, cover_image, notion_url, instructor_name, instructor_image, visible, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
-        RETURNING *
-        "#,
-    )
-    .bind(&title)
-    .bind(&provider)
-    .bind(&cover_image)
-    .bind(&notion_url)
-    .bind(&instructor_name)
-    .bind(&instructor_image)
-    .bind(visible)
-    .fetch_one(&state.pool)
-    .await?;
-
-    let response = AdminResourceResponse {
-        id: resource.id,
-        title: resource.title,
-        provider: resource.provider,
-        cover_image: resource.cover_image,
-        notion_url: resource.notion_url,
-        instructor: Some(AdminInstructorResponse {
-            name: resource.instructor_name,
-            image: resource.instructor_image,
-        }),
-        quote: None,
-        visible: resource.visible,
-        created_at: resource.created_at,
-        updated_at: resource.updated_at,
+    let mut errors = FieldErrors::default();
+    check_resource_fields(&mut errors, &title, &provider, &instructor_name);
+    let notion_url = errors.check(
+        "notionUrl",
+        normalize_http_url(NOTION_URL, notion_url.as_deref().unwrap_or_default()),
+    );
+    if let Err(e) = errors.into_result() {
+        // Nothing will reference the images uploaded with the rejected form
+        for url in [&cover_image, &instructor_image].into_iter().flatten() {
+            remove_uploaded_file(state.storage.as_ref(), url).await;
+        }
+        return Err(e);
+    }
+    let notion_url = notion_url.flatten();
+    let (instructor_title, instructor_bio) =
+        instructor_details(instructor_title.as_deref(), instructor_bio.as_deref())?;
+    let quote = QuoteChoice {
+        quote: inline_quote(quote_text, quote_author),
+        quote_id,
     };
 
-    Ok(Json(AdminItemResponse { item: response }))
+    Ok((
+        NewResource {
+            title,
+            provider,
+            cover_image,
+            cover_thumbnail,
+            notion_url,
+            instructor_name,
+            instructor_image,
+            instructor_title,
+            instructor_bio,
+            visible: visible.unwrap_or(true),
+            tags: normalize_tags(tags.unwrap_or_default()),
+        },
+        quote,
+    ))
 }
 
-pub async fn admin_update_resource_multipart(
-    _auth: AdminUser,
-    State(state): State<AppState>,
-    Path(id): Path<i32>,
+async fn apply_resource_multipart(
+    state: &AppState,
+    resource: &mut Resource,
     mut multipart: axum::extract::Multipart,
-) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
-    let existing: Resource = sqlx::query_as("SELECT * FROM resources WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
-
+) -> Result<Option<QuoteChoice>, AppError> {
     let mut title: Option<String> = None;
     let mut provider: Option<String> = None;
-    let mut cover_image: Option<String> = None;
+    let mut cover_image: Option<Option<String>> = None;
+    let mut cover_thumbnail: Option<String> = None;
     let mut notion_url: Option<Option<String>> = None;
     let mut instructor_name: Option<String> = None;
     let mut instructor_image: Option<Option<String>> = None;
+    let mut instructor_title: Option<Option<String>> = None;
+    let mut instructor_bio: Option<Option<String>> = None;
     let mut visible: Option<bool> = None;
-
-    while let Some(field) = multipart
-        .next_field()
-        .await
-        .map_err(|e| AppError::InternalError(e.into()))?
-    {
+    let mut tags: Option<Vec<String>> = None;
+    let mut quote_text: Option<String> = None;
+    let mut quote_author: Option<String> = None;
+    let mut quote_id: Option<Option<i32>> = None;
+
+    while let Some(field) = multipart.next_field().await? {
         let field_name = field.name().unwrap_or("").to_string();
 
         match field_name.as_str() {
             "title" => {
-                title = Some(
-                    field
-                        .text()
-                        .await
-                        .map_err(|e| AppError::InternalError(e.into()))?,
-                );
+                title = Some(field.text().await?);
             }
             "provider" => {
-                provider = Some(
-                    field
-                        .text()
-                        .await
-                        .map_err(|e| AppError::InternalError(e.into()))?,
-                );
+                provider = Some(field.text().await?);
             }
             "notionUrl" => {
-                let text = field
-                    .text()
-                    .await
-                    .map_err(|e| AppError::InternalError(e.into()))?;
-                notion_url = Some(if text.is_empty() { None } else { Some(text) });
+                // An empty value removes the link
+                notion_url = Some(normalize_http_url(NOTION_URL, &field.text().await?)?);
             }
             "instructorName" => {
-                let text = field
-                    .text()
-                    .await
-                    .map_err(|e| AppError::InternalError(e.into()))?;
+                let text = field.text().await?;
                 if !text.is_empty() {
                     instructor_name = Some(text);
                 }
             }
-            "quoteText" | "quoteAuthor" => {
-                // Ignore quote fields - quotes are now in a separate table
-                let _ = field.text().await;
+            // Unlike the name, an empty title or bio clears it
+            "instructorTitle" => {
+                instructor_title = Some(optional_text(
+                    "Instructor title",
+                    Some(&field.text().await?),
+                    MAX_INSTRUCTOR_TITLE_LEN,
+                )?);
+            }
+            "instructorBio" => {
+                instructor_bio = Some(optional_text(
+                    "Instructor bio",
+                    Some(&field.text().await?),
+                    MAX_INSTRUCTOR_BIO_LEN,
+                )?);
+            }
+            "quoteText" => {
+                quote_text = Some(field.text().await?);
+            }
+            "quoteAuthor" => {
+                quote_author = Some(field.text().await?);
+            }
+            "quoteId" => {
+                // An empty value unlinks the current quote
+                let text = field.text().await?;
+                quote_id = Some(if text.is_empty() {
+                    None
+                } else {
+                    Some(parse_quote_id(&text)?)
+                });
+            }
+            "tags" => {
+                // Accept both a comma-separated list and repeated `tags` fields
+                let text = field.text().await?;
+                tags.get_or_insert_with(Vec::new)
+                    .extend(text.split(',').map(str::to_string));
             }
             "visible" => {
-                let text = field
-                    .text()
-                    .await
-                    .map_err(|e| AppError::InternalError(e.into()))?;
+                let text = field.text().await?;
                 visible = Some(text == "true" || text == "1");
             }
             "coverImage" => {
                 if let Some(file_name) = field.file_name().map(|s| s.to_string()) {
-                    let data = field
-                        .bytes()
-                        .await
-                        .map_err(|e| AppError::InternalError(e.into()))?;
-                    let url =
-                        save_uploaded_file("coverImage", &file_name, &data, "resources/covers")
-                            .await?;
-                    cover_image = Some(url);
+                    let data = field.bytes().await?;
+                    let StoredImage {
+                        url, thumbnail_url, ..
+                    } = save_uploaded_image(
+                        state.storage.as_ref(),
+                        &file_name,
+                        &data,
+                        "resources/covers",
+                    )
+                    .await?;
+                    cover_image = Some(Some(url));
+                    cover_thumbnail = Some(thumbnail_url);
+                } else if field.text().await?.trim().is_empty() {
+                    // Like notionUrl, an empty text field removes the image
+                    cover_image = Some(None);
                 }
             }
             "instructorImage" => {
                 if let Some(file_name) = field.file_name().map(|s| s.to_string()) {
-                    let data = field
-                        .bytes()
-                        .await
-                        .map_err(|e| AppError::InternalError(e.into()))?;
+                    let data = field.bytes().await?;
                     let url = save_uploaded_file(
-                        "instructorImage",
+                        state.storage.as_ref(),
                         &file_name,
                         &data,
                         "resources/instructors",
                     )
                     .await?;
                     instructor_image = Some(Some(url));
+                } else if field.text().await?.trim().is_empty() {
+                    instructor_image = Some(None);
                 }
             }
             _ => {}
         }
     }
 
-    let title = title.unwrap_or(existing.title);
-    let provider = provider.unwrap_or(existing.provider);
-    let cover_image = cover_image.or(existing.cover_image);
-    let notion_url = notion_url.unwrap_or(existing.notion_url);
-    let instructor_name = instructor_name.unwrap_or(existing.instructor_name);
-    let instructor_image = instructor_image.unwrap_or(existing.instructor_image);
-    let visible = visible.unwrap_or(existing.visible);
-
-    let resource: Resource = sqlx::query_as(
-        r#"
-        UPDATE resources 
-        SET title = $1, provider = $2, cover_image = $3, notion_url = $4, instructor_name = $5, instructor_image = $6, visible = $7, updated_at = NOW()
-        WHERE id = $8
-        RETURNING *
-        "#,
-    )
-    .bind(&title)
-    .bind(&provider)
-    .bind(&cover_image)
-    .bind(&notion_url)
-    .bind(&instructor_name)
-    .bind(&instructor_image)
-    .bind(visible)
-    .bind(id)
-    .fetch_one(&state.pool)
-    .await?;
+    let mut errors = FieldErrors::default();
+    check_resource_fields(
+        &mut errors,
+        title.as_deref().unwrap_or(&resource.title),
+        provider.as_deref().unwrap_or(&resource.provider),
+        instructor_name
+            .as_deref()
+            .unwrap_or(&resource.instructor_name),
+    );
+    if let Err(e) = errors.into_result() {
+        // Nothing will reference the images uploaded with the rejected form
+        for url in [&cover_image, &instructor_image]
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            remove_uploaded_file(state.storage.as_ref(), url).await;
+        }
+        return Err(e);
+    }
 
-    let response = AdminResourceResponse {
-        id: resource.id,
-        title: resource.title,
-        provider: resource.provider,
-        cover_image: resource.cover_image,
-        notion_url: resource.notion_url,
-        instructor: Some(AdminInstructorResponse {
-            name: resource.instructor_name,
-            image: resource.instructor_image,
-        }),
-        quote: None,
-        visible: resource.visible,
-        created_at: resource.created_at,
-        updated_at: resource.updated_at,
-    };
+    if let Some(title) = title {
+        resource.title = title;
+    }
+    if let Some(provider) = provider {
+        resource.provider = provider;
+    }
+    // Only replace the thumbnail together with the cover it belongs to
+    if let Some(cover_image) = cover_image {
+        resource.cover_image = cover_image;
+        resource.cover_thumbnail = cover_thumbnail;
+    }
+    if let Some(notion_url) = notion_url {
+        resource.notion_url = notion_url;
+    }
+    if let Some(instructor_name) = instructor_name {
+        resource.instructor_name = instructor_name;
+    }
+    if let Some(instructor_image) = instructor_image {
+        resource.instructor_image = instructor_image;
+    }
+    if let Some(instructor_title) = instructor_title {
+        resource.instructor_title = instructor_title;
+    }
+    if let Some(instructor_bio) = instructor_bio {
+        resource.instructor_bio = instructor_bio;
+    }
+    if let Some(visible) = visible {
+        resource.visible = visible;
+    }
+    if let Some(tags) = tags {
+        resource.tags = normalize_tags(tags);
+    }
+    let quote = inline_quote(quote_text, quote_author);
+    if quote.is_none() && quote_id.is_none() {
+        return Ok(None);
+    }
 
-    Ok(Json(AdminItemResponse { item: response }))
+    Ok(Some(QuoteChoice {
+        quote,
+        quote_id: quote_id.flatten(),
+    }))
 }
 
 pub async fn upload_user_avatar(
@@ -1147,13 +3705,7 @@ pub async fn upload_user_avatar(
     State(state): State<AppState>,
     mut multipart: axum::extract::Multipart,
 ) -> Result<Json<UploadAvatarResponse>, AppError> {
-    use tokio::io::AsyncWriteExt;
-
-    while let Some(field) = multipart
-        .next_field()
-        .await
-        .map_err(|e| AppError::InternalError(e.into()))?
-    {
+    while let Some(field) = multipart.next_field().await? {
         let name = field.name().unwrap_or("").to_string();
 
         if name == "avatar" {
@@ -1162,51 +3714,134 @@ pub async fn upload_user_avatar(
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| format!("{}.jpg", Uuid::new_v4()));
 
-            let data = field
-                .bytes()
-                .await
-                .map_err(|e| AppError::InternalError(e.into()))?;
+            let data = field.bytes().await?;
+
+            // Store the files before touching the database so the row lock below is only
+            // held for the swap itself
+            let StoredImage {
+                url: image_url,
+                thumbnail_url,
+                width,
+                height,
+            } = save_uploaded_image(
+                state.storage.as_ref(),
+                &file_name,
+                &data,
+                &avatar_directory(auth.user_id),
+            )
+            .await?;
 
-            // Create uploads directory if it doesn't exist
-            tokio::fs::create_dir_all("uploads/avatars")
-                .await
-                .map_err(|e| AppError::InternalError(e.into()))?;
+            // Nothing references the new files until the swap commits, so drop them if it fails
+            let previous_image =
+                match replace_user_image(&state.pool, auth.user_id, Some(&image_url)).await {
+                    Ok(previous_image) => previous_image,
+                    Err(e) => {
+                        remove_uploaded_file(state.storage.as_ref(), &image_url).await;
+                        return Err(e);
+                    }
+                };
+
+            // Only drop the replaced avatar once the new one is committed
+            if let Some(previous_image) = previous_image {
+                release_user_image(&state, auth.user_id, &previous_image).await;
+            }
 
-            // Generate unique filename
-            let unique_filename = format!("{}_{}", Uuid::new_v4(), file_name);
-            let file_path = format!("uploads/avatars/{unique_filename}");
+            return Ok(Json(UploadAvatarResponse {
+                image_url,
+                thumbnail_url,
+                width: Some(width),
+                height: Some(height),
+                size_bytes: Some(data.len() as u64),
+            }));
+        }
+    }
 
-            // Save file
-            let mut file = tokio::fs::File::create(&file_path)
-                .await
-                .map_err(|e| AppError::InternalError(e.into()))?;
+    Err(AppError::BadRequest("No avatar file provided".to_string()))
+}
 
-            file.write_all(&data)
-                .await
-                .map_err(|e| AppError::InternalError(e.into()))?;
+// Reverts to the default avatar. Only files this user uploaded are deleted; external
+// pictures (e.g. from Google sign-in) are just unlinked.
+pub async fn delete_user_avatar(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let previous_image = replace_user_image(&state.pool, auth.user_id, None).await?;
 
-            // Generate URL (you may want to customize this based on your domain)
-            let image_url = format!("/uploads/avatars/{unique_filename}");
+    if let Some(previous_image) = previous_image {
+        release_user_image(&state, auth.user_id, &previous_image).await;
+    }
 
-            // Update user's image in database
-            sqlx::query("UPDATE users SET image = $1 WHERE id = $2")
-                .bind(&image_url)
-                .bind(auth.user_id)
-                .execute(&state.pool)
-                .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-            return Ok(Json(UploadAvatarResponse { image_url }));
-        }
+// Points the user at a new avatar and returns the one it replaced
+async fn replace_user_image(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+    image_url: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    // Lock the user's row so concurrent uploads for the same user are applied
+    // one after another and each sees the image the previous one committed
+    let mut tx = pool.begin().await?;
+
+    let (previous_image,): (Option<String>,) =
+        sqlx::query_as("SELECT image FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    sqlx::query("UPDATE users SET image = $1 WHERE id = $2")
+        .bind(image_url)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(previous_image)
+}
+
+// Each member's avatars live in their own directory, so ownership is in the key
+fn avatar_directory(user_id: Uuid) -> String {
+    format!("avatars/{user_id}")
+}
+
+// Deletes an avatar a user stopped using if they uploaded it, unless another account or a
+// resource still shows it. Failures are only logged; the user row is already updated.
+async fn release_user_image(state: &AppState, user_id: Uuid, url: &str) {
+    let owned = state
+        .storage
+        .key_for(url)
+        .is_some_and(|key| key.starts_with(&format!("{}/", avatar_directory(user_id))));
+    if !owned {
+        return;
     }
 
-    Err(AppError::BadRequest("No avatar file provided".to_string()))
+    let in_use = sqlx::query_as::<_, (bool,)>(
+        r#"
+        SELECT EXISTS(SELECT 1 FROM users WHERE image = $1)
+            OR EXISTS(SELECT 1 FROM resources WHERE cover_image = $1 OR instructor_image = $1)
+        "#,
+    )
+    .bind(url)
+    .fetch_one(&state.pool)
+    .await;
+
+    match in_use {
+        Ok((false,)) => remove_uploaded_file(state.storage.as_ref(), url).await,
+        Ok((true,)) => {}
+        Err(e) => tracing::warn!("Failed to check whether {} is still used: {:?}", url, e),
+    }
 }
 
 pub async fn update_user_password(
     auth: AuthUser,
     State(state): State<AppState>,
-    Json(req): Json<UpdatePasswordRequest>,
+    AppJson(req): AppJson<UpdatePasswordRequest>,
 ) -> Result<Json<UpdatePasswordResponse>, AppError> {
+    validate_password(&req.new_password, &state.password_policy)?;
+
     // Get current user
     let user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
         .bind(auth.user_id)
@@ -1214,23 +3849,21 @@ pub async fn update_user_password(
         .await?
         .ok_or(AppError::NotFound)?;
 
-    // Check if user has a password (not a Google OAuth-only user)
+    // Check if user has a password (not an OAuth-only user)
     let current_password_hash = user.password_hash.as_ref().ok_or_else(|| {
         AppError::BadRequest(
-            "This account uses Google Sign-In and doesn't have a password.".to_string(),
+            "This account signs in with your sign-in provider and doesn't have a password."
+                .to_string(),
         )
     })?;
 
     // Verify current password
-    if !verify(req.current_password.as_bytes(), current_password_hash)
-        .map_err(|e| AppError::InternalError(e.into()))?
-    {
+    if !verify_password(&req.current_password, current_password_hash).await? {
         return Err(AppError::AuthError);
     }
 
     // Hash new password
-    let new_password_hash = hash(req.new_password.as_bytes(), DEFAULT_COST)
-        .map_err(|e| AppError::InternalError(e.into()))?;
+    let new_password_hash = hash_password(&req.new_password, state.bcrypt_cost).await?;
 
     // Update password
     sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
@@ -1242,203 +3875,453 @@ pub async fn update_user_password(
     Ok(Json(UpdatePasswordResponse { success: true }))
 }
 
+pub async fn export_user_data(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let profile: UserExportProfile = sqlx::query_as(
+        r#"
+        SELECT email, full_name, phone_num, image, points, rank, role, university, major, created_at
+        FROM users
+        WHERE id = $1
+        "#,
+    )
+    .bind(auth.user_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let stats: Option<UserStats> = sqlx::query_as("SELECT * FROM user_stats WHERE user_id = $1")
+        .bind(auth.user_id)
+        .fetch_optional(&state.pool)
+        .await?;
+
+    let submissions: Vec<UserExportSubmission> = sqlx::query_as(
+        r#"
+        SELECT c.title AS challenge_title, c.week AS challenge_week, s.submission_url, s.score, s.scored_at, s.created_at
+        FROM challenge_submissions s
+        JOIN challenges c ON c.id = s.challenge_id
+        WHERE s.user_id = $1
+        ORDER BY s.created_at
+        "#,
+    )
+    .bind(auth.user_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let contact_messages: Vec<UserExportContactMessage> = sqlx::query_as(
+        "SELECT name, email, message, created_at FROM contact_messages WHERE LOWER(email) = LOWER($1) ORDER BY created_at",
+    )
+    .bind(&profile.email)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let points_history: Vec<UserExportPointsEvent> = sqlx::query_as(
+        "SELECT delta, reason, created_at FROM points_history WHERE user_id = $1 ORDER BY created_at",
+    )
+    .bind(auth.user_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let export = UserExportResponse {
+        profile,
+        stats: stats.map(|stats| UserStatsResponse {
+            best_subject: stats.best_subject,
+            improveable: stats.improveable,
+            quickest_hunter: stats.quickest_hunter,
+            challenges_taken: stats.challenges_taken,
+        }),
+        submissions,
+        contact_messages,
+        points_history,
+        exported_at: time::OffsetDateTime::now_utc(),
+    };
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"uj-ai-club-data-export.json\"",
+        )],
+        Json(export),
+    ))
+}
+
+pub async fn delete_user_account(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    AppJson(req): AppJson<DeleteAccountRequest>,
+) -> Result<StatusCode, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    // Taken before the row lock, in the same order as admin_update_user
+    lock_admin_roles(&mut *tx).await?;
+
+    let user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1 FOR UPDATE")
+        .bind(auth.user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    // Password accounts confirm with their password, OAuth-only accounts by retyping their email
+    match user.password_hash.as_ref() {
+        Some(password_hash) => {
+            let password = req
+                .password
+                .as_deref()
+                .ok_or_else(|| AppError::BadRequest("Current password is required".to_string()))?;
+
+            // The session is fine, so a mistyped password is a form error rather than a 401
+            if !verify_password(password, password_hash).await? {
+                return Err(AppError::ValidationError(
+                    "Current password is incorrect".to_string(),
+                ));
+            }
+        }
+        None => {
+            let confirmed = req
+                .confirm_email
+                .as_deref()
+                .is_some_and(|email| email.trim().eq_ignore_ascii_case(&user.email));
+
+            if !confirmed {
+                return Err(AppError::BadRequest(
+                    "Please confirm deletion by entering your account email".to_string(),
+                ));
+            }
+        }
+    }
+
+    if user.role == "admin" && count_admins(&mut *tx).await? <= 1 {
+        return Err(AppError::BadRequest(
+            "The last remaining admin cannot delete their account".to_string(),
+        ));
+    }
+
+    sqlx::query("DELETE FROM user_stats WHERE user_id = $1")
+        .bind(auth.user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM challenge_submissions WHERE user_id = $1")
+        .bind(auth.user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // Contact messages aren't linked to accounts, so strip the identifying fields instead
+    sqlx::query(
+        "UPDATE contact_messages SET name = 'Deleted user', email = 'deleted' WHERE LOWER(email) = LOWER($1)",
+    )
+    .bind(&user.email)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(auth.user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // Everyone below the deleted account moves up a place
+    recompute_ranks(&mut *tx).await?;
+
+    tx.commit().await?;
+
+    // The account may have been on the board
+    publish_leaderboard(&state).await;
+
+    if let Some(image) = user.image {
+        release_user_image(&state, auth.user_id, &image).await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // Google OAuth handlers
 #[derive(Debug, Deserialize)]
 pub struct OAuthCallbackQuery {
     code: String,
-    state: String,
 }
 
-pub async fn google_auth_init(State(state): State<AppState>) -> impl IntoResponse {
+// The original Google-only routes, kept so existing redirect URIs keep working
+pub async fn google_auth_init(State(state): State<AppState>) -> Result<Redirect, AppError> {
+    start_oauth(&state, "google")
+}
+
+pub async fn google_auth_callback(
+    State(state): State<AppState>,
+    AppQuery(query): AppQuery<OAuthCallbackQuery>,
+) -> Result<Redirect, AppError> {
+    finish_oauth(&state, "google", query).await
+}
+
+pub async fn oauth_init(
+    State(state): State<AppState>,
+    AppPath(provider): AppPath<String>,
+) -> Result<Redirect, AppError> {
+    start_oauth(&state, &provider)
+}
+
+pub async fn oauth_callback(
+    State(state): State<AppState>,
+    AppPath(provider): AppPath<String>,
+    AppQuery(query): AppQuery<OAuthCallbackQuery>,
+) -> Result<Redirect, AppError> {
+    finish_oauth(&state, &provider, query).await
+}
+
+// Providers that aren't configured 404 like any unknown route
+fn oauth_provider<'a>(state: &'a AppState, provider: &str) -> Result<&'a OAuthConfig, AppError> {
+    state
+        .oauth_providers
+        .get(provider)
+        .ok_or(AppError::NotFound)
+}
+
+fn oauth_client(config: &OAuthConfig) -> oauth2::basic::BasicClient {
     use oauth2::basic::BasicClient;
-    use oauth2::{AuthUrl, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope, TokenUrl};
-
-    // Create OAuth client
-    let client = BasicClient::new(
-        ClientId::new(state.oauth_config.client_id.clone()),
-        Some(ClientSecret::new(state.oauth_config.client_secret.clone())),
-        AuthUrl::new(state.oauth_config.auth_url.clone())
-            .expect("Invalid authorization endpoint URL"),
-        Some(
-            TokenUrl::new(state.oauth_config.token_url.clone())
-                .expect("Invalid token endpoint URL"),
-        ),
+    use oauth2::{AuthUrl, ClientId, ClientSecret, RedirectUrl, TokenUrl};
+
+    BasicClient::new(
+        ClientId::new(config.client_id.clone()),
+        Some(ClientSecret::new(config.client_secret.clone())),
+        AuthUrl::new(config.auth_url.clone()).expect("Invalid authorization endpoint URL"),
+        Some(TokenUrl::new(config.token_url.clone()).expect("Invalid token endpoint URL")),
     )
-    .set_redirect_uri(
-        RedirectUrl::new(state.oauth_config.redirect_uri.clone()).expect("Invalid redirect URL"),
-    );
+    .set_redirect_uri(RedirectUrl::new(config.redirect_uri.clone()).expect("Invalid redirect URL"))
+}
+
+fn start_oauth(state: &AppState, provider: &str) -> Result<Redirect, AppError> {
+    use oauth2::{CsrfToken, Scope};
+
+    let config = oauth_provider(state, provider)?;
 
     // Generate authorization URL
-    let (auth_url, _csrf_token) = client
+    let (auth_url, _csrf_token) = oauth_client(config)
         .authorize_url(CsrfToken::new_random)
-        .add_scope(Scope::new("openid".to_string()))
-        .add_scope(Scope::new("email".to_string()))
-        .add_scope(Scope::new("profile".to_string()))
+        .add_scopes(
+            config
+                .provider
+                .scopes()
+                .iter()
+                .map(|scope| Scope::new(scope.to_string())),
+        )
         .url();
 
-    Redirect::temporary(auth_url.as_str())
+    Ok(Redirect::temporary(auth_url.as_str()))
 }
 
-pub async fn google_auth_callback(
-    State(state): State<AppState>,
-    Query(query): Query<OAuthCallbackQuery>,
-) -> Result<impl IntoResponse, AppError> {
-    use oauth2::basic::BasicClient;
-    use oauth2::{
-        AuthUrl, AuthorizationCode, ClientId, ClientSecret, RedirectUrl, TokenResponse, TokenUrl,
-    };
+async fn finish_oauth(
+    state: &AppState,
+    provider: &str,
+    query: OAuthCallbackQuery,
+) -> Result<Redirect, AppError> {
+    use oauth2::{AuthorizationCode, RequestTokenError, TokenResponse};
 
-    // Create OAuth client
-    let client = BasicClient::new(
-        ClientId::new(state.oauth_config.client_id.clone()),
-        Some(ClientSecret::new(state.oauth_config.client_secret.clone())),
-        AuthUrl::new(state.oauth_config.auth_url.clone())
-            .expect("Invalid authorization endpoint URL"),
-        Some(
-            TokenUrl::new(state.oauth_config.token_url.clone())
-                .expect("Invalid token endpoint URL"),
-        ),
-    )
-    .set_redirect_uri(
-        RedirectUrl::new(state.oauth_config.redirect_uri.clone()).expect("Invalid redirect URL"),
-    );
+    let config = oauth_provider(state, provider)?;
+    let provider = config.provider;
 
     // Exchange authorization code for access token
-    let token_result = client
+    let token_result = oauth_client(config)
         .exchange_code(AuthorizationCode::new(query.code))
-        .request_async(oauth2::reqwest::async_http_client)
+        .request_async(|request| oauth::send(&state.oauth_http, request))
         .await
-        .map_err(|e| AppError::InternalError(anyhow::anyhow!("Token exchange failed: {e}")))?;
+        .map_err(|e| match e {
+            RequestTokenError::Request(e) => oauth::upstream_error(e),
+            e => AppError::InternalError(anyhow::anyhow!("Token exchange failed: {e}")),
+        })?;
+
+    let user_info = oauth::fetch_user_info(
+        &state.oauth_http,
+        provider,
+        &config.userinfo_url,
+        token_result.access_token().secret(),
+    )
+    .await?;
 
-    // Fetch user info from Google
-    let user_info: GoogleUserInfo = reqwest::Client::new()
-        .get("https://www.googleapis.com/oauth2/v3/userinfo")
-        .bearer_auth(token_result.access_token().secret())
-        .send()
-        .await
-        .map_err(|e| AppError::InternalError(e.into()))?
-        .json()
-        .await
-        .map_err(|e| AppError::InternalError(e.into()))?;
+    // Providers may return a differently-cased address than the one used at signup
+    let oauth_email = canonical_email(&user_info.email);
+    let id_column = provider.id_column();
 
-    // Check if user exists with this google_id
-    let existing_user: Option<User> = sqlx::query_as(
-        "SELECT id, email, password_hash, full_name, phone_num, image, points, rank, role, created_at 
-         FROM users WHERE google_id = $1"
-    )
-    .bind(&user_info.sub)
+    // Check if user exists with this provider account
+    let existing_user: Option<User> = sqlx::query_as(&format!(
+        "SELECT id, email, password_hash, full_name, phone_num, image, points, rank, role, created_at
+         FROM users WHERE {id_column} = $1"
+    ))
+    .bind(&user_info.subject)
     .fetch_optional(&state.pool)
     .await?;
 
     let user = if let Some(user) = existing_user {
-        // User exists, update their info if needed
-        sqlx::query_as(
-            "UPDATE users SET email = $1, full_name = $2, image = $3 
-             WHERE google_id = $4
+        // The stored email stays: the provider's may be unverified or belong to another
+        // account by now. Its picture only fills in a missing avatar, so an uploaded one
+        // is never replaced.
+        sqlx::query_as(&format!(
+            "UPDATE users SET full_name = $1, image = COALESCE(image, $2)
+             WHERE {id_column} = $3
              RETURNING id, email, password_hash, full_name, phone_num, image, points, rank, role, created_at"
-        )
-        .bind(&user_info.email)
+        ))
         .bind(user_info.name.as_deref().unwrap_or(&user.full_name))
         .bind(&user_info.picture)
-        .bind(&user_info.sub)
+        .bind(&user_info.subject)
         .fetch_one(&state.pool)
         .await?
     } else {
-        // Check if user exists with same email (linking accounts)
-        let email_user: Option<User> = sqlx::query_as(
+        // An unverified address proves nothing about who owns it, so it can neither claim
+        // an existing account nor become the email of a new one
+        if !user_info.email_verified {
+            return Err(AppError::BadRequest(format!(
+                "Your {} account email is not verified",
+                provider.display_name()
+            )));
+        }
+
+        // Check if user exists with same email (linking accounts). A user already carrying
+        // this provider account was just created by a concurrent sign-in and is picked up
+        // by the upsert below instead.
+        let email_user: Option<User> = sqlx::query_as(&format!(
             "SELECT id, email, password_hash, full_name, phone_num, image, points, rank, role, created_at 
-             FROM users WHERE email = $1"
-        )
-        .bind(&user_info.email)
+             FROM users WHERE LOWER(email) = $1 AND {id_column} IS DISTINCT FROM $2"
+        ))
+        .bind(&oauth_email)
+        .bind(&user_info.subject)
         .fetch_optional(&state.pool)
         .await?;
 
         if let Some(existing) = email_user {
-            // Link Google account to existing user
-            sqlx::query_as(
-                "UPDATE users SET google_id = $1, image = COALESCE($2, image) 
-                 WHERE id = $3
+            // Whoever controls the provider account would take over the existing one
+            if !state.allow_oauth_account_linking {
+                return Err(AppError::AccountLinkingDisabled);
+            }
+
+            // Link the provider account to the existing user, unless another account of the
+            // same provider got linked in the meantime
+            let mut tx = state.pool.begin().await?;
+
+            let user: User = sqlx::query_as(&format!(
+                "UPDATE users SET {id_column} = $1, image = COALESCE(image, $2)
+                 WHERE id = $3 AND ({id_column} IS NULL OR {id_column} = $1)
                  RETURNING id, email, password_hash, full_name, phone_num, image, points, rank, role, created_at"
-            )
-            .bind(&user_info.sub)
+            ))
+            .bind(&user_info.subject)
             .bind(&user_info.picture)
             .bind(existing.id)
-            .fetch_one(&state.pool)
+            .fetch_optional(&mut *tx)
             .await?
+            .ok_or(AppError::OAuthAccountConflict)?;
+
+            audit::record(
+                &mut *tx,
+                Some(user.id),
+                provider.linked_action(),
+                serde_json::json!({ provider.id_key(): user_info.subject, "email": oauth_email }),
+            )
+            .await?;
+
+            tx.commit().await?;
+
+            user
         } else {
-            // Create new user
+            // Create new user together with their stats row. A concurrent first sign-in with
+            // the same provider account may have created them since the lookup above; that
+            // row keeps its email and any avatar already set, like an existing user.
             let user_id = Uuid::new_v4();
-            let user: User = sqlx::query_as(
+            let mut tx = state.pool.begin().await?;
+
+            let user: User = sqlx::query_as(&format!(
                 r#"
-                INSERT INTO users (id, email, password_hash, full_name, google_id, image, created_at)
+                INSERT INTO users (id, email, password_hash, full_name, {id_column}, image, created_at)
                 VALUES ($1, $2, NULL, $3, $4, $5, NOW())
+                ON CONFLICT ({id_column}) DO UPDATE
+                SET full_name = COALESCE($6, users.full_name), image = COALESCE(users.image, EXCLUDED.image)
                 RETURNING id, email, password_hash, full_name, phone_num, image, points, rank, role, created_at
                 "#,
-            )
+            ))
             .bind(user_id)
-            .bind(&user_info.email)
-            .bind(user_info.name.as_deref().unwrap_or(&user_info.email))
-            .bind(&user_info.sub)
+            .bind(&oauth_email)
+            .bind(user_info.name.as_deref().unwrap_or(&oauth_email))
+            .bind(&user_info.subject)
             .bind(&user_info.picture)
-            .fetch_one(&state.pool)
+            .bind(&user_info.name)
+            .fetch_one(&mut *tx)
             .await?;
 
-            // Create user stats
-            sqlx::query(
-                "INSERT INTO user_stats (user_id, created_at, updated_at) VALUES ($1, NOW(), NOW())",
-            )
-            .bind(user_id)
-            .execute(&state.pool)
-            .await?;
+            // Only the request that actually created the user adds the stats row
+            if user.id == user_id {
+                sqlx::query(
+                    "INSERT INTO user_stats (user_id, created_at, updated_at) VALUES ($1, NOW(), NOW())",
+                )
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
 
             user
         }
     };
 
-    // Check if user needs to complete profile (university and major)
-    let needs_profile: Option<(bool,)> =
-        sqlx::query_as("SELECT university_major_set FROM users WHERE id = $1")
-            .bind(user.id)
-            .fetch_optional(&state.pool)
-            .await?;
-
-    let needs_completion = needs_profile.map(|(set,)| !set).unwrap_or(true);
+    let code = oauth::issue_exchange_code(&state.pool, user.id).await?;
 
-    // Create JWT token
-    let token = create_token(user.id)?;
+    // Even the code shouldn't go anywhere off the allowlist; AppConfig warned at startup
+    let frontend_url = allowed_frontend_url(&state.frontend_url, &state.frontend_allowed_origins)
+        .unwrap_or(DEFAULT_FRONTEND_URL);
 
-    // Encode user data
-    let user_json = serde_json::to_string(&UserResponse {
-        id: user.id,
-        full_name: user.full_name,
-        email: user.email,
-        image: user.image,
-        role: user.role,
-    })
-    .map_err(|e| AppError::InternalError(e.into()))?;
+    // The frontend trades the code for the token and user at POST /auth/exchange
+    Ok(Redirect::temporary(&format!(
+        "{frontend_url}/auth/callback?code={code}"
+    )))
+}
 
-    let encoded_user = urlencoding::encode(&user_json);
+pub async fn exchange_oauth_code(
+    State(state): State<AppState>,
+    AppJson(req): AppJson<OAuthExchangeRequest>,
+) -> Result<(HeaderMap, Json<OAuthExchangeResponse>), AppError> {
+    let user_id = oauth::redeem_exchange_code(&state.pool, req.code.trim())
+        .await?
+        .ok_or(AppError::AuthError)?;
 
-    // Get frontend URL from environment or use default
-    let frontend_url =
-        std::env::var("FRONTEND_URL").unwrap_or_else(|_| "https://aiclub-uj.com".to_string());
+    let user: User = sqlx::query_as(
+        "SELECT id, email, password_hash, full_name, phone_num, image, points, rank, role, created_at
+         FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::AuthError)?;
 
-    // Redirect to frontend with token and user data
-    let redirect_url = if needs_completion {
-        format!(
-            "{frontend_url}/auth/callback?token={token}&user={encoded_user}&needs_profile_completion=true"
-        )
-    } else {
-        format!("{frontend_url}/auth/callback?token={token}&user={encoded_user}")
-    };
+    // Google users have to add their university and major before using the site
+    let (university_major_set,): (bool,) =
+        sqlx::query_as("SELECT university_major_set FROM users WHERE id = $1")
+            .bind(user.id)
+            .fetch_one(&state.pool)
+            .await?;
 
-    Ok(Redirect::temporary(&redirect_url))
+    let (token, claims) = create_token(user.id)?;
+
+    Ok((
+        session_cookie(&state, &token, &claims),
+        Json(OAuthExchangeResponse {
+            auth: AuthResponse {
+                token,
+                expires_at: claims.exp,
+                expires_in: claims.expires_in(),
+                user: UserResponse {
+                    id: user.id,
+                    full_name: user.full_name,
+                    email: user.email,
+                    image: user.image,
+                    role: user.role,
+                },
+            },
+            needs_profile_completion: !university_major_set,
+        }),
+    ))
 }
 
 pub async fn complete_profile(
     auth: AuthUser,
     State(state): State<AppState>,
-    Json(req): Json<CompleteProfileRequest>,
+    AppJson(req): AppJson<CompleteProfileRequest>,
 ) -> Result<Json<CompleteProfileResponse>, AppError> {
     // Update user's university and major
     sqlx::query(
@@ -0,0 +1,105 @@
+use image::{DynamicImage, GenericImageView, ImageFormat, imageops::FilterType};
+use std::io::Cursor;
+
+use crate::error::AppError;
+
+// Width of generated thumbnails in pixels
+pub const THUMBNAIL_WIDTH: u32 = 200;
+
+// Content types /uploads may serve files as
+pub const UPLOAD_CONTENT_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/webp", "image/gif"];
+
+// What an upload really is, going by its bytes rather than the client's file name or headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageKind {
+    pub format: ImageFormat,
+    pub extension: &'static str,
+    pub content_type: &'static str,
+}
+
+fn invalid_image() -> AppError {
+    AppError::BadRequest("Uploaded file is not a valid image".to_string())
+}
+
+// Only formats in UPLOAD_CONTENT_TYPES are accepted; the stored file gets the matching
+// extension so it is served as that type whatever the client called it
+fn image_kind(data: &[u8]) -> Result<ImageKind, AppError> {
+    let format = image::guess_format(data).map_err(|_| invalid_image())?;
+    let extension = match format {
+        ImageFormat::Png => "png",
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::WebP => "webp",
+        ImageFormat::Gif => "gif",
+        _ => {
+            return Err(AppError::BadRequest(
+                "Images must be PNG, JPEG, WebP or GIF".to_string(),
+            ));
+        }
+    };
+
+    Ok(ImageKind {
+        format,
+        extension,
+        content_type: format.to_mime_type(),
+    })
+}
+
+// Decodes an upload to make sure it is an image we accept, for files stored without a thumbnail
+pub fn verify_image(data: &[u8]) -> Result<ImageKind, AppError> {
+    let kind = image_kind(data)?;
+    image::load_from_memory_with_format(data, kind.format).map_err(|_| invalid_image())?;
+    Ok(kind)
+}
+
+// A re-encoded thumbnail along with the dimensions of the image it was made from.
+// Both share the decoded format, so `kind` describes either file.
+pub struct Thumbnail {
+    pub bytes: Vec<u8>,
+    pub kind: ImageKind,
+    pub source_width: u32,
+    pub source_height: u32,
+}
+
+// Scales an image down to `width` pixels wide, keeping its aspect ratio and format.
+// Images that are already narrower are re-encoded at their original size.
+pub fn make_thumbnail(data: &[u8], width: u32) -> Result<Thumbnail, AppError> {
+    let kind = image_kind(data)?;
+    let format = kind.format;
+    let image = image::load_from_memory_with_format(data, format).map_err(|_| invalid_image())?;
+    let (source_width, source_height) = image.dimensions();
+
+    let thumbnail = if image.width() > width {
+        let height =
+            (u64::from(image.height()) * u64::from(width) / u64::from(image.width())).max(1) as u32;
+        image.resize_exact(width, height, FilterType::Triangle)
+    } else {
+        image
+    };
+
+    // The JPEG and WebP encoders only accept 8-bit pixels (and JPEG has no alpha)
+    let thumbnail = match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(thumbnail.to_rgb8()),
+        ImageFormat::WebP => DynamicImage::ImageRgba8(thumbnail.to_rgba8()),
+        _ => thumbnail,
+    };
+
+    let mut output = Cursor::new(Vec::new());
+    thumbnail
+        .write_to(&mut output, format)
+        .map_err(|e| AppError::InternalError(e.into()))?;
+
+    Ok(Thumbnail {
+        bytes: output.into_inner(),
+        kind,
+        source_width,
+        source_height,
+    })
+}
+
+// Thumbnails are stored next to the original with a `thumb_` file name prefix
+pub fn thumbnail_key(key: &str) -> String {
+    match key.rsplit_once('/') {
+        Some((directory, file_name)) => format!("{directory}/thumb_{file_name}"),
+        None => format!("thumb_{key}"),
+    }
+}
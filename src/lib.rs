@@ -1,30 +1,81 @@
+pub mod audit;
 pub mod auth;
+pub mod config;
+pub mod db;
 pub mod error;
+pub mod extract;
 pub mod handlers;
+pub mod images;
+pub mod live;
+pub mod mailer;
 pub mod models;
+pub mod notifications;
+pub mod oauth;
+pub mod ranking;
+pub mod rate_limit;
+pub mod storage;
+pub mod telemetry;
+pub mod timestamp;
+pub mod validation;
 
+use auth::SameSite;
 use axum::{
     Router,
-    extract::FromRef,
+    body::Body,
+    extract::{DefaultBodyLimit, FromRef},
+    http::{HeaderName, HeaderValue, Method, Request, Response, header},
+    middleware,
     routing::{delete, get, patch, post, put},
 };
+use config::{AdminBootstrapConfig, StorageConfig};
+pub use config::{AppConfig, BodyLimits, ContactLimits, OAuthConfig};
+use live::{LeaderboardCache, LeaderboardHub};
+use mailer::{Mailer, NoopMailer};
+use metrics_exporter_prometheus::PrometheusHandle;
+use rate_limit::RateLimiter;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use storage::{FileStorage, LocalStorage, S3Storage};
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{Any, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::normalize_path::NormalizePath;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::services::ServeDir;
+use tower_http::set_header::SetResponseHeader;
+use tower_http::trace::TraceLayer;
+use validation::PasswordPolicy;
 
-#[derive(Clone)]
-pub struct OAuthConfig {
-    pub client_id: String,
-    pub client_secret: String,
-    pub redirect_uri: String,
-    pub auth_url: String,
-    pub token_url: String,
-}
+const REQUEST_ID_HEADER: &str = "x-request-id";
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: sqlx::PgPool,
-    pub oauth_config: Arc<OAuthConfig>,
+    pub oauth_providers: Arc<HashMap<String, OAuthConfig>>,
+    pub oauth_http: reqwest::Client,
+    pub allow_oauth_account_linking: bool,
+    pub frontend_url: String,
+    pub frontend_allowed_origins: Arc<Vec<String>>,
+    pub submission_allowed_domains: Arc<Vec<String>>,
+    pub mailer: Arc<dyn Mailer>,
+    pub admin_notification_email: Option<String>,
+    pub password_policy: PasswordPolicy,
+    pub bcrypt_cost: u32,
+    pub storage: Arc<dyn FileStorage>,
+    pub metrics: Option<PrometheusHandle>,
+    pub started_at: Instant,
+    pub trust_proxy_headers: bool,
+    pub email_check_limiter: Arc<RateLimiter>,
+    pub contact_limiter: Arc<RateLimiter>,
+    pub contact_honeypot_field: Option<String>,
+    pub leaderboard_hub: Arc<LeaderboardHub>,
+    pub leaderboard_cache: Arc<LeaderboardCache>,
+    pub admin_bootstrap: Option<Arc<AdminBootstrapConfig>>,
+    pub contact_limits: ContactLimits,
+    pub auth_cookie: Option<SameSite>,
+    pub remember_me_lifetime: Duration,
+    pub featured_resources_limit: u32,
 }
 
 // Implement FromRef to allow extracting PgPool from AppState
@@ -34,73 +85,249 @@ impl FromRef<AppState> for sqlx::PgPool {
     }
 }
 
-pub fn create_app(pool: sqlx::PgPool) -> Router {
-    let google_client_id = std::env::var("GOOGLE_CLIENT_ID").expect("GOOGLE_CLIENT_ID must be set");
-    let google_client_secret =
-        std::env::var("GOOGLE_CLIENT_SECRET").expect("GOOGLE_CLIENT_SECRET must be set");
-    let google_redirect_uri =
-        std::env::var("GOOGLE_REDIRECT_URI").expect("GOOGLE_REDIRECT_URI must be set");
-
-    let oauth_config = Arc::new(OAuthConfig {
-        client_id: google_client_id,
-        client_secret: google_client_secret,
-        redirect_uri: google_redirect_uri,
-        auth_url: "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
-        token_url: "https://oauth2.googleapis.com/token".to_string(),
-    });
+// Parses a comma-separated origin list, skipping blanks and values that aren't valid headers
+pub fn parse_allowed_origins(origins: &str) -> Vec<HeaderValue> {
+    origins
+        .split(',')
+        .map(|origin| origin.trim().trim_end_matches('/'))
+        .filter(|origin| !origin.is_empty())
+        .filter_map(|origin| match HeaderValue::from_str(origin) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS origin: {}", origin);
+                None
+            }
+        })
+        .collect()
+}
+
+// Explicit origins get credentialed CORS; `Any` is only used when opted into for development
+pub fn cors_layer(allowed_origins: Vec<HeaderValue>, allow_any_origin: bool) -> CorsLayer {
+    if allowed_origins.is_empty() {
+        if allow_any_origin {
+            return CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any);
+        }
+
+        tracing::warn!("ALLOWED_ORIGINS is not set, cross-origin requests will be rejected");
+    }
+
+    CorsLayer::new()
+        .allow_origin(allowed_origins)
+        .allow_credentials(true)
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::ACCEPT,
+            HeaderName::from_static(auth::CSRF_HEADER),
+        ])
+}
+
+pub fn create_app(pool: sqlx::PgPool, config: AppConfig) -> Router {
+    let mailer = config.mailer.unwrap_or_else(|| Arc::new(NoopMailer));
+
+    let storage: Arc<dyn FileStorage> = match config.storage {
+        StorageConfig::S3 {
+            bucket,
+            region,
+            endpoint,
+            credentials,
+            public_url,
+        } => Arc::new(S3Storage::new(
+            &bucket,
+            &region,
+            endpoint.as_deref(),
+            credentials,
+            &public_url,
+        )),
+        StorageConfig::Local => Arc::new(LocalStorage::new(&config.uploads_dir, "/uploads")),
+    };
+
+    // Prometheus metrics are opt-in
+    let metrics = config
+        .metrics_enabled
+        .then(telemetry::install_prometheus_recorder);
+    let metrics_enabled = metrics.is_some();
 
     let app_state = AppState {
         pool: pool.clone(),
-        oauth_config,
+        oauth_http: oauth::http_client(config.oauth_timeout),
+        oauth_providers: Arc::new(config.oauth_providers),
+        allow_oauth_account_linking: config.allow_oauth_account_linking,
+        frontend_url: config.frontend_url,
+        frontend_allowed_origins: Arc::new(config.frontend_allowed_origins),
+        submission_allowed_domains: Arc::new(config.submission_allowed_domains),
+        mailer,
+        admin_notification_email: config.admin_notification_email,
+        password_policy: config.password_policy,
+        bcrypt_cost: config.bcrypt_cost,
+        storage,
+        metrics,
+        started_at: Instant::now(),
+        trust_proxy_headers: config.trust_proxy_headers,
+        email_check_limiter: Arc::new(RateLimiter::new(
+            config.email_check_rate_limit,
+            Duration::from_secs(60),
+        )),
+        contact_limiter: Arc::new(RateLimiter::new(
+            config.contact_rate_limit,
+            Duration::from_secs(60 * 60),
+        )),
+        contact_honeypot_field: config.contact_honeypot_field,
+        leaderboard_hub: Arc::new(LeaderboardHub::new(config.leaderboard_ws_max_connections)),
+        leaderboard_cache: Arc::new(LeaderboardCache::new(config.leaderboard_cache_ttl)),
+        admin_bootstrap: config.admin_bootstrap.map(Arc::new),
+        contact_limits: config.contact_limits,
+        auth_cookie: config.auth_cookie,
+        remember_me_lifetime: config.remember_me_lifetime,
+        featured_resources_limit: config.featured_resources_limit,
     };
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let cors = cors_layer(config.allowed_origins, config.allow_any_origin);
+
+    let body_limits = config.body_limits;
+    let upload_limit = (
+        DefaultBodyLimit::max(body_limits.max_upload_bytes),
+        RequestBodyLimitLayer::new(body_limits.max_upload_bytes),
+    );
 
-    Router::new()
+    // Uploaded files never change once written (names are UUID-prefixed), so successful
+    // responses can be cached for good. ServeDir picks the Content-Type from the extension;
+    // nosniff stops browsers second-guessing it for user-supplied files.
+    let uploads = SetResponseHeader::if_not_present(
+        ServeDir::new(&config.uploads_dir),
+        header::CACHE_CONTROL,
+        |response: &Response<_>| {
+            response
+                .status()
+                .is_success()
+                .then(|| HeaderValue::from_static("public, max-age=31536000, immutable"))
+        },
+    );
+    let uploads = SetResponseHeader::overriding(
+        uploads,
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    // Uploads are only ever images; anything else found there (HTML, SVG, ...) is served
+    // as an opaque download so it can't run scripts on our origin
+    let uploads =
+        SetResponseHeader::overriding(uploads, header::CONTENT_TYPE, |response: &Response<_>| {
+            let content_type = response.headers().get(header::CONTENT_TYPE)?;
+            let is_image = content_type
+                .to_str()
+                .is_ok_and(|value| images::UPLOAD_CONTENT_TYPES.contains(&value));
+            (!is_image).then(|| HeaderValue::from_static("application/octet-stream"))
+        });
+
+    let mut router = Router::new()
         .route("/health", get(handlers::health_check))
+        .route("/health/ready", get(handlers::readiness_check))
         .route("/auth/signup", post(handlers::signup))
         .route("/auth/login", post(handlers::login))
+        .route("/auth/logout", post(handlers::logout))
+        .route(
+            "/auth/email-available",
+            get(handlers::check_email_available),
+        )
         .route("/auth/google", get(handlers::google_auth_init))
         .route("/auth/google/callback", get(handlers::google_auth_callback))
+        .route("/auth/oauth/:provider", get(handlers::oauth_init))
+        .route(
+            "/auth/oauth/:provider/callback",
+            get(handlers::oauth_callback),
+        )
+        .route("/auth/exchange", post(handlers::exchange_oauth_code))
         .route("/auth/complete-profile", post(handlers::complete_profile))
         .route("/leaderboards", get(handlers::get_leaderboards))
+        .route("/ws/leaderboard", get(handlers::leaderboard_ws))
         .route("/resources", get(handlers::get_resources))
+        .route("/resources/featured", get(handlers::get_featured_resources))
         .route("/resources/:id", get(handlers::get_resource_by_id))
+        .route("/quotes/random", get(handlers::get_random_quote))
+        .route("/quotes/daily", get(handlers::get_daily_quote))
+        .route(
+            "/resources/:id/bookmark",
+            post(handlers::bookmark_resource).delete(handlers::remove_resource_bookmark),
+        )
         .route("/challenges/current", get(handlers::get_current_challenge))
+        .route(
+            "/challenges/current/public",
+            get(handlers::get_public_current_challenge),
+        )
         .route(
             "/challenges/leaderboard",
             get(handlers::get_challenge_leaderboard),
         )
+        .route(
+            "/v2/challenges/leaderboard",
+            get(handlers::get_challenge_leaderboard_v2),
+        )
+        .route(
+            "/challenges/:id/submissions",
+            post(handlers::create_challenge_submission),
+        )
         .route(
             "/users/profile",
-            put(handlers::update_user_profile).get(handlers::get_user_profile),
+            put(handlers::update_user_profile)
+                .get(handlers::get_user_profile)
+                .delete(handlers::delete_user_account),
+        )
+        .route(
+            "/users/me/points/timeline",
+            get(handlers::get_points_timeline),
+        )
+        .route("/users/points/history", get(handlers::get_points_history))
+        .route("/users/submissions", get(handlers::get_user_submissions))
+        .route("/users/export", get(handlers::export_user_data))
+        .route("/users/bookmarks", get(handlers::get_user_bookmarks))
+        .route("/users/notifications", get(handlers::get_notifications))
+        .route(
+            "/users/notifications/:id/read",
+            patch(handlers::mark_notification_read),
         )
-        .route("/users/avatar", post(handlers::upload_user_avatar))
+        .route("/users/:id", get(handlers::get_public_user_profile))
         .route("/users/password", put(handlers::update_user_password))
         .route("/contact", post(handlers::create_contact))
-        .route("/admin/resources", get(handlers::admin_get_resources))
+        .route("/admin/contact", get(handlers::admin_get_contact_messages))
         .route(
-            "/admin/resources",
-            post(handlers::admin_create_resource_multipart),
+            "/admin/contact/:id",
+            patch(handlers::admin_patch_contact_message),
         )
+        .route("/admin/resources", get(handlers::admin_get_resources))
         .route(
             "/admin/resources/:id",
             get(handlers::admin_get_resource_by_id),
         )
         .route(
             "/admin/resources/:id",
-            put(handlers::admin_update_resource_multipart),
+            delete(handlers::admin_delete_resource),
         )
         .route(
-            "/admin/resources/:id",
-            delete(handlers::admin_delete_resource),
+            "/admin/resources/visibility",
+            patch(handlers::admin_patch_resources_visibility),
         )
         .route(
             "/admin/resources/:id/visibility",
             patch(handlers::admin_patch_resource_visibility),
         )
+        .route(
+            "/admin/resources/:id/featured",
+            patch(handlers::admin_patch_resource_featured),
+        )
+        .route(
+            "/admin/resources/:id/restore",
+            post(handlers::admin_restore_resource),
+        )
         .route("/admin/challenges", get(handlers::admin_get_challenges))
         .route("/admin/challenges", post(handlers::admin_create_challenge))
         .route(
@@ -115,11 +342,98 @@ pub fn create_app(pool: sqlx::PgPool) -> Router {
             "/admin/challenges/:id",
             delete(handlers::admin_delete_challenge),
         )
+        .route(
+            "/admin/challenges/visibility",
+            patch(handlers::admin_patch_challenges_visibility),
+        )
         .route(
             "/admin/challenges/:id/visibility",
             patch(handlers::admin_patch_challenge_visibility),
         )
-        .nest_service("/uploads", ServeDir::new("uploads"))
+        .route(
+            "/admin/challenges/:id/restore",
+            post(handlers::admin_restore_challenge),
+        )
+        .route(
+            "/admin/challenges/:id/set-current",
+            post(handlers::admin_set_current_challenge),
+        )
+        .route(
+            "/admin/challenges/:id/submissions",
+            get(handlers::admin_get_challenge_submissions),
+        )
+        .route(
+            "/admin/challenges/:id/submissions/:submission_id/score",
+            post(handlers::admin_score_submission),
+        )
+        .route(
+            "/admin/recompute-ranks",
+            post(handlers::admin_recompute_ranks),
+        )
+        .route("/admin/bootstrap", post(handlers::admin_bootstrap))
+        .route("/admin/stats", get(handlers::admin_get_stats))
+        .route("/admin/users", get(handlers::admin_get_users))
+        .route("/admin/users/:id", patch(handlers::admin_update_user))
+        // gzip/br for clients that send Accept-Encoding. Uploads are mostly images that are
+        // already compressed, so they're mounted after this layer and go out as stored.
+        .layer(CompressionLayer::new())
+        .nest_service("/uploads", uploads)
+        // Everything above gets the global body limit; uploads below get their own
+        .layer((
+            DefaultBodyLimit::max(body_limits.max_body_bytes),
+            RequestBodyLimitLayer::new(body_limits.max_body_bytes),
+        ))
+        .route(
+            "/users/avatar",
+            post(handlers::upload_user_avatar)
+                .layer(upload_limit)
+                .delete(handlers::delete_user_avatar),
+        )
+        .route(
+            "/admin/resources",
+            post(handlers::admin_create_resource).layer(upload_limit),
+        )
+        .route(
+            "/admin/resources/:id",
+            put(handlers::admin_update_resource).layer(upload_limit),
+        )
+        // Unknown paths get the JSON error shape too; /uploads answers its own misses
+        .fallback(handlers::route_not_found)
+        .layer(middleware::map_response(error::json_payload_too_large))
+        .layer(middleware::map_response(error::json_method_not_allowed));
+
+    if metrics_enabled {
+        router = router.route_layer(middleware::from_fn(telemetry::track_metrics));
+    }
+
+    let router = router
         .layer(cors)
-        .with_state(app_state)
+        // Scrape endpoint for monitoring; registered after the CORS layer so it isn't wrapped by it
+        .route("/metrics", get(handlers::get_metrics))
+        // Layers run bottom-up: assign/propagate X-Request-Id first so the trace span
+        // (and every log line inside it) carries the id, then echo it on the response
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(
+            TraceLayer::new_for_http().make_span_with(|request: &Request<Body>| {
+                let request_id = request
+                    .headers()
+                    .get(REQUEST_ID_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default();
+
+                // The path only: query strings carry OAuth codes and email addresses
+                tracing::info_span!(
+                    "request",
+                    method = %request.method(),
+                    path = %request.uri().path(),
+                    request_id = %request_id,
+                )
+            }),
+        )
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .with_state(app_state);
+
+    // A Router's own layers only run after a route has matched, so `/resources/` has to be
+    // trimmed by a service wrapped around the whole app, /uploads included
+    Router::new().fallback_service(NormalizePath::trim_trailing_slash(router))
 }
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::handlers::LeaderboardPeriod;
+use crate::models::ChallengeLeaderboardEntry;
+
+// Fans leaderboard snapshots out to connected WebSocket clients. Snapshots are
+// pre-serialized JSON so each one is encoded once no matter how many clients listen.
+pub struct LeaderboardHub {
+    sender: broadcast::Sender<Arc<str>>,
+    connections: AtomicUsize,
+    max_connections: usize,
+}
+
+// Holds one of the hub's connection slots until dropped
+pub struct ConnectionSlot {
+    hub: Arc<LeaderboardHub>,
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.hub.connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl LeaderboardHub {
+    pub fn new(max_connections: usize) -> Self {
+        // Clients only care about the latest board, so a short buffer is plenty;
+        // a lagging client just skips to the newest snapshot
+        let (sender, _) = broadcast::channel(8);
+
+        Self {
+            sender,
+            connections: AtomicUsize::new(0),
+            max_connections,
+        }
+    }
+
+    // Reserves a connection slot, or None when the hub is full
+    pub fn try_connect(self: &Arc<Self>) -> Option<ConnectionSlot> {
+        self.connections
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                (count < self.max_connections).then_some(count + 1)
+            })
+            .ok()?;
+
+        Some(ConnectionSlot { hub: self.clone() })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<str>> {
+        self.sender.subscribe()
+    }
+
+    // Lets publishers skip building a snapshot nobody would receive
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    pub fn publish(&self, snapshot: String) {
+        // Only fails when nobody is subscribed, which is fine
+        let _ = self.sender.send(snapshot.into());
+    }
+}
+
+// Keeps each period's top 10 in memory for a short while so public board reads skip the
+// sort-and-limit. Every invalidation bumps a generation, and a board fetched under an
+// older generation is not stored, so a read racing with a points change can't re-cache
+// the stale board.
+pub struct LeaderboardCache {
+    ttl: Duration,
+    generation: AtomicU64,
+    boards: RwLock<HashMap<LeaderboardPeriod, CachedBoard>>,
+}
+
+struct CachedBoard {
+    stored_at: Instant,
+    generation: u64,
+    entries: Arc<Vec<ChallengeLeaderboardEntry>>,
+}
+
+impl LeaderboardCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            generation: AtomicU64::new(0),
+            boards: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Read before fetching a board and hand back to `store`
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    pub fn get(&self, period: LeaderboardPeriod) -> Option<Arc<Vec<ChallengeLeaderboardEntry>>> {
+        let boards = self.boards.read().unwrap_or_else(|e| e.into_inner());
+        let board = boards.get(&period)?;
+
+        (board.generation == self.generation() && board.stored_at.elapsed() < self.ttl)
+            .then(|| board.entries.clone())
+    }
+
+    pub fn store(
+        &self,
+        period: LeaderboardPeriod,
+        generation: u64,
+        entries: Arc<Vec<ChallengeLeaderboardEntry>>,
+    ) {
+        if self.ttl.is_zero() {
+            return;
+        }
+
+        let mut boards = self.boards.write().unwrap_or_else(|e| e.into_inner());
+        if generation != self.generation() {
+            return;
+        }
+
+        boards.insert(
+            period,
+            CachedBoard {
+                stored_at: Instant::now(),
+                generation,
+                entries,
+            },
+        );
+    }
+
+    // Called whenever points change
+    pub fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
@@ -1,6 +1,7 @@
-use sqlx::postgres::PgPoolOptions;
+use anyhow::Context;
+use axum_server::tls_rustls::RustlsConfig;
 use std::net::SocketAddr;
-use uj_ai_club_backend::create_app;
+use uj_ai_club_backend::{AppConfig, config::TlsConfig, create_app, db::PoolConfig};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -8,6 +9,9 @@ async fn main() -> anyhow::Result<()> {
 
     tracing_subscriber::fmt::init();
 
+    // Checked before connecting, so a bad JWT_SECRET or SMTP setup fails fast
+    let config = AppConfig::from_env()?;
+
     let database_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| {
         let pg_user = std::env::var("POSTGRES_USER").unwrap_or_else(|_| "uj_ai_club".to_string());
         let pg_pass = std::env::var("POSTGRES_PASSWORD").unwrap();
@@ -18,20 +22,48 @@ async fn main() -> anyhow::Result<()> {
     });
     let server_addr =
         std::env::var("SERVER_ADDRESS").unwrap_or_else(|_| "0.0.0.0:8000".to_string());
+    let tls = TlsConfig::from_env()?;
+
+    let pool_config = PoolConfig::from_env();
+    tracing::info!("Database pool config: {:?}", pool_config);
 
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&database_url)
-        .await?;
+    let pool = pool_config.pool_options().connect(&database_url).await?;
 
-    let app = create_app(pool);
+    let app = create_app(pool, config);
 
     let addr: SocketAddr = server_addr.parse()?;
 
-    tracing::info!("Starting server on {} yo", addr);
+    // Peer addresses feed per-IP rate limiting
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    match tls {
+        Some(tls) => {
+            // Other dependencies enable both rustls backends, so it can't pick one by itself
+            let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+            let rustls = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to load TLS certificate {} or key {}",
+                        tls.cert_path.display(),
+                        tls.key_path.display()
+                    )
+                })?;
+
+            tracing::info!("Starting HTTPS server on {}", addr);
+
+            axum_server::bind_rustls(addr, rustls)
+                .serve(service)
+                .await?;
+        }
+        None => {
+            tracing::info!("Starting server on {} yo", addr);
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            axum::serve(listener, service).await?;
+        }
+    }
 
     Ok(())
 }
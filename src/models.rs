@@ -1,5 +1,6 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 // Custom deserializer for date strings to OffsetDateTime
@@ -47,9 +48,20 @@ pub struct User {
     pub points: i32,
     pub rank: i32,
     pub role: String,
+    #[serde(with = "crate::timestamp")]
     pub created_at: time::OffsetDateTime,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct EmailAvailableQuery {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailAvailableResponse {
+    pub available: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RegisterRequest {
     #[serde(rename = "fullName")]
@@ -64,14 +76,35 @@ pub struct RegisterRequest {
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
+    #[serde(rename = "rememberMe", default)]
+    pub remember_me: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub token: String,
+    // Unix timestamp the token stops being accepted at
+    #[serde(rename = "expiresAt")]
+    pub expires_at: i64,
+    #[serde(rename = "expiresIn")]
+    pub expires_in: i64,
     pub user: UserResponse,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct OAuthExchangeRequest {
+    pub code: String,
+}
+
+// What the frontend gets for the code from the Google sign-in redirect
+#[derive(Debug, Serialize)]
+pub struct OAuthExchangeResponse {
+    #[serde(flatten)]
+    pub auth: AuthResponse,
+    #[serde(rename = "needsProfileCompletion")]
+    pub needs_profile_completion: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -86,10 +119,11 @@ pub struct UserResponse {
 pub struct Leaderboard {
     pub id: i32,
     pub title: String,
+    #[serde(with = "crate::timestamp")]
     pub created_at: time::OffsetDateTime,
 }
 
-#[derive(Debug, Serialize, FromRow)]
+#[derive(Debug, Clone, Serialize, FromRow)]
 pub struct LeaderboardEntry {
     pub name: String,
     pub points: i32,
@@ -100,6 +134,17 @@ pub struct LeaderboardResponse {
     pub id: i32,
     pub title: String,
     pub entries: Vec<LeaderboardEntry>,
+    #[serde(rename = "currentUser")]
+    pub current_user: Option<LeaderboardPosition>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct LeaderboardPosition {
+    pub position: i64,
+    pub id: Uuid,
+    pub name: String,
+    pub points: i32,
+    pub image: Option<String>,
 }
 
 #[derive(Debug, Serialize, FromRow)]
@@ -108,21 +153,40 @@ pub struct Resource {
     pub title: String,
     pub provider: String,
     pub cover_image: Option<String>,
+    pub cover_thumbnail: Option<String>,
     pub instructor_name: String,
     pub instructor_image: Option<String>,
+    pub instructor_title: Option<String>,
+    pub instructor_bio: Option<String>,
     pub notion_url: Option<String>,
     pub visible: bool,
+    // Spotlighted on the home page, see GET /resources/featured
+    pub featured: bool,
+    // Members who opened the detail page, see handlers::record_resource_view
+    pub view_count: i32,
+    // TEXT[] column; sqlx binds and decodes Postgres arrays as Vec<String> directly
+    pub tags: Vec<String>,
+    // Quote shown on the detail page; a random one is used when unset
+    pub quote_id: Option<i32>,
+    // Admin or moderator who added it; NULL for older rows
+    pub created_by: Option<Uuid>,
+    #[serde(with = "crate::timestamp")]
     pub created_at: time::OffsetDateTime,
+    #[serde(with = "crate::timestamp")]
     pub updated_at: time::OffsetDateTime,
+    #[serde(with = "crate::timestamp::option")]
+    pub deleted_at: Option<time::OffsetDateTime>,
 }
 
-#[derive(Debug, Serialize, FromRow)]
+#[derive(Debug, Clone, Serialize, FromRow)]
 pub struct Quote {
     pub id: i32,
     pub text: String,
     pub author: String,
     pub visible: bool,
+    #[serde(with = "crate::timestamp")]
     pub created_at: time::OffsetDateTime,
+    #[serde(with = "crate::timestamp")]
     pub updated_at: time::OffsetDateTime,
 }
 
@@ -133,6 +197,9 @@ pub struct ResourceListResponse {
     pub provider: String,
     #[serde(rename = "coverImage")]
     pub cover_image: Option<String>,
+    #[serde(rename = "thumbnailUrl")]
+    pub thumbnail_url: Option<String>,
+    pub tags: Vec<String>,
     pub instructor: InstructorResponse,
 }
 
@@ -151,6 +218,8 @@ pub struct ResourceDetailResponse {
 pub struct InstructorResponse {
     pub name: String,
     pub image: Option<String>,
+    pub title: Option<String>,
+    pub bio: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -167,11 +236,19 @@ pub struct Challenge {
     pub description: String,
     pub challenge_url: String,
     pub is_current: bool,
+    #[serde(with = "crate::timestamp::option")]
     pub start_date: Option<time::OffsetDateTime>,
+    #[serde(with = "crate::timestamp::option")]
     pub end_date: Option<time::OffsetDateTime>,
     pub visible: bool,
+    // Admin or moderator who added it; NULL for older rows
+    pub created_by: Option<Uuid>,
+    #[serde(with = "crate::timestamp")]
     pub created_at: time::OffsetDateTime,
+    #[serde(with = "crate::timestamp")]
     pub updated_at: time::OffsetDateTime,
+    #[serde(with = "crate::timestamp::option")]
+    pub deleted_at: Option<time::OffsetDateTime>,
 }
 
 #[derive(Debug, Serialize)]
@@ -182,9 +259,26 @@ pub struct ChallengeResponse {
     pub description: String,
     #[serde(rename = "challengeUrl")]
     pub challenge_url: String,
+    #[serde(rename = "startDate", with = "crate::timestamp::option")]
+    pub start_date: Option<time::OffsetDateTime>,
+    #[serde(rename = "endDate", with = "crate::timestamp::option")]
+    pub end_date: Option<time::OffsetDateTime>,
 }
 
-#[derive(Debug, Serialize, FromRow)]
+// ChallengeResponse minus the task link, for visitors who aren't signed in
+#[derive(Debug, Serialize)]
+pub struct PublicChallengeResponse {
+    pub id: i32,
+    pub week: i32,
+    pub title: String,
+    pub description: String,
+    #[serde(rename = "startDate", with = "crate::timestamp::option")]
+    pub start_date: Option<time::OffsetDateTime>,
+    #[serde(rename = "endDate", with = "crate::timestamp::option")]
+    pub end_date: Option<time::OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
 pub struct ChallengeLeaderboardEntry {
     pub id: Uuid,
     pub name: String,
@@ -192,6 +286,13 @@ pub struct ChallengeLeaderboardEntry {
     pub image: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ChallengeLeaderboardResponse {
+    pub entries: Vec<ChallengeLeaderboardEntry>,
+    #[serde(rename = "currentUser")]
+    pub current_user: Option<LeaderboardPosition>,
+}
+
 #[derive(Debug, Serialize, FromRow)]
 pub struct UserStats {
     pub id: Uuid,
@@ -200,7 +301,9 @@ pub struct UserStats {
     pub improveable: Option<String>,
     pub quickest_hunter: i32,
     pub challenges_taken: i32,
+    #[serde(with = "crate::timestamp")]
     pub created_at: time::OffsetDateTime,
+    #[serde(with = "crate::timestamp")]
     pub updated_at: time::OffsetDateTime,
 }
 
@@ -213,6 +316,19 @@ pub struct UserProfileResponse {
     pub stats: UserStatsResponse,
 }
 
+// What other members can see of a profile; never includes email, phone or password
+#[derive(Debug, Serialize, FromRow)]
+pub struct PublicUserProfileResponse {
+    pub id: Uuid,
+    #[serde(rename = "name")]
+    pub full_name: String,
+    pub image: Option<String>,
+    pub points: i32,
+    pub rank: i32,
+    pub university: Option<String>,
+    pub major: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserStatsResponse {
     #[serde(rename = "bestSubject")]
@@ -229,6 +345,9 @@ pub struct ContactRequest {
     pub name: String,
     pub email: String,
     pub message: String,
+    // Anything else the form sent, where the honeypot field turns up
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -244,25 +363,38 @@ pub struct AdminResourceResponse {
     pub provider: String,
     #[serde(rename = "coverImage")]
     pub cover_image: Option<String>,
+    #[serde(rename = "thumbnailUrl")]
+    pub thumbnail_url: Option<String>,
     #[serde(rename = "notionUrl")]
     pub notion_url: Option<String>,
+    pub tags: Vec<String>,
     pub instructor: Option<AdminInstructorResponse>,
     pub quote: Option<AdminQuoteResponse>,
     pub visible: bool,
-    #[serde(rename = "createdAt")]
+    pub featured: bool,
+    #[serde(rename = "viewCount")]
+    pub view_count: i32,
+    #[serde(rename = "createdBy")]
+    pub created_by: Option<Uuid>,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
     pub created_at: time::OffsetDateTime,
-    #[serde(rename = "updatedAt")]
+    #[serde(rename = "updatedAt", with = "crate::timestamp")]
     pub updated_at: time::OffsetDateTime,
+    #[serde(rename = "deletedAt", with = "crate::timestamp::option")]
+    pub deleted_at: Option<time::OffsetDateTime>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct AdminInstructorResponse {
     pub name: String,
     pub image: Option<String>,
+    pub title: Option<String>,
+    pub bio: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct AdminQuoteResponse {
+    pub id: i32,
     pub text: String,
     pub author: String,
 }
@@ -276,27 +408,48 @@ pub struct AdminCreateResourceRequest {
     #[serde(rename = "notionUrl")]
     pub notion_url: Option<String>,
     pub instructor: Option<AdminInstructorRequest>,
+    // Creates a new quote and links it; takes precedence over quoteId
     pub quote: Option<AdminQuoteRequest>,
+    #[serde(rename = "quoteId")]
+    pub quote_id: Option<i32>,
     pub visible: Option<bool>,
+    pub tags: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AdminUpdateResourceRequest {
     pub title: Option<String>,
     pub provider: Option<String>,
-    #[serde(rename = "coverImage")]
-    pub cover_image: Option<String>,
+    // `null` removes the cover, omitting the field keeps it
+    #[serde(rename = "coverImage", default, deserialize_with = "double_option")]
+    pub cover_image: Option<Option<String>>,
     #[serde(rename = "notionUrl")]
     pub notion_url: Option<String>,
     pub instructor: Option<AdminInstructorRequest>,
+    // Creates a new quote and links it; takes precedence over quoteId
     pub quote: Option<AdminQuoteRequest>,
+    // `null` unlinks the current quote, omitting the field keeps it
+    #[serde(rename = "quoteId", default, deserialize_with = "double_option")]
+    pub quote_id: Option<Option<i32>>,
     pub visible: Option<bool>,
+    pub tags: Option<Vec<String>>,
+}
+
+// Distinguishes an explicit `null` (Some(None)) from a missing field (None)
+fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AdminInstructorRequest {
     pub name: String,
     pub image: Option<String>,
+    pub title: Option<String>,
+    pub bio: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -310,20 +463,55 @@ pub struct AdminVisibilityRequest {
     pub visible: bool,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AdminFeaturedRequest {
+    pub featured: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminBatchVisibilityRequest {
+    pub ids: Vec<i32>,
+    pub visible: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AdminChallengeResponse {
     pub id: i32,
     pub title: String,
     pub description: String,
-    #[serde(rename = "startDate")]
+    #[serde(rename = "startDate", with = "crate::timestamp::option")]
     pub start_date: Option<time::OffsetDateTime>,
-    #[serde(rename = "endDate")]
+    #[serde(rename = "endDate", with = "crate::timestamp::option")]
     pub end_date: Option<time::OffsetDateTime>,
     pub visible: bool,
-    #[serde(rename = "createdAt")]
+    #[serde(rename = "isCurrent")]
+    pub is_current: bool,
+    #[serde(rename = "createdBy")]
+    pub created_by: Option<Uuid>,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
     pub created_at: time::OffsetDateTime,
-    #[serde(rename = "updatedAt")]
+    #[serde(rename = "updatedAt", with = "crate::timestamp")]
     pub updated_at: time::OffsetDateTime,
+    #[serde(rename = "deletedAt", with = "crate::timestamp::option")]
+    pub deleted_at: Option<time::OffsetDateTime>,
+}
+
+impl From<Challenge> for AdminChallengeResponse {
+    fn from(c: Challenge) -> Self {
+        Self {
+            id: c.id,
+            title: c.title,
+            description: c.description,
+            start_date: c.start_date,
+            end_date: c.end_date,
+            visible: c.visible,
+            is_current: c.is_current,
+            created_by: c.created_by,
+            created_at: c.created_at,
+            updated_at: c.updated_at,
+            deleted_at: c.deleted_at,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -333,9 +521,17 @@ pub struct AdminCreateChallengeRequest {
     pub week: Option<i32>,
     #[serde(rename = "challengeUrl")]
     pub challenge_url: Option<String>,
-    #[serde(rename = "startDate", deserialize_with = "date_format::deserialize")]
+    #[serde(
+        rename = "startDate",
+        default,
+        deserialize_with = "date_format::deserialize"
+    )]
     pub start_date: Option<time::OffsetDateTime>,
-    #[serde(rename = "endDate", deserialize_with = "date_format::deserialize")]
+    #[serde(
+        rename = "endDate",
+        default,
+        deserialize_with = "date_format::deserialize"
+    )]
     pub end_date: Option<time::OffsetDateTime>,
     pub visible: Option<bool>,
 }
@@ -347,9 +543,17 @@ pub struct AdminUpdateChallengeRequest {
     pub week: Option<i32>,
     #[serde(rename = "challengeUrl")]
     pub challenge_url: Option<String>,
-    #[serde(rename = "startDate", deserialize_with = "date_format::deserialize")]
+    #[serde(
+        rename = "startDate",
+        default,
+        deserialize_with = "date_format::deserialize"
+    )]
     pub start_date: Option<time::OffsetDateTime>,
-    #[serde(rename = "endDate", deserialize_with = "date_format::deserialize")]
+    #[serde(
+        rename = "endDate",
+        default,
+        deserialize_with = "date_format::deserialize"
+    )]
     pub end_date: Option<time::OffsetDateTime>,
     pub visible: Option<bool>,
 }
@@ -364,6 +568,24 @@ pub struct AdminItemsResponse<T> {
     pub items: Vec<T>,
 }
 
+// Result of a bulk update: how many rows changed and what they look like now
+#[derive(Debug, Serialize)]
+pub struct AdminBatchResponse<T> {
+    pub count: usize,
+    pub items: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminBootstrapRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminBootstrapResponse {
+    // False when an admin already existed and nothing changed
+    pub promoted: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AdminSuccessResponse {
     pub success: bool,
@@ -375,6 +597,8 @@ pub struct UpdateProfileRequest {
     pub full_name: Option<String>,
     pub email: Option<String>,
     pub image: Option<String>,
+    #[serde(rename = "phoneNum")]
+    pub phone_num: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -384,6 +608,8 @@ pub struct UpdateProfileResponse {
     pub full_name: String,
     pub email: String,
     pub image: Option<String>,
+    #[serde(rename = "phoneNum")]
+    pub phone_num: Option<String>,
     pub role: String,
 }
 
@@ -391,6 +617,13 @@ pub struct UpdateProfileResponse {
 pub struct UploadAvatarResponse {
     #[serde(rename = "imageUrl")]
     pub image_url: String,
+    #[serde(rename = "thumbnailUrl")]
+    pub thumbnail_url: String,
+    // Pixel dimensions and byte size of the uploaded original
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -421,6 +654,360 @@ pub struct CompleteProfileResponse {
 pub struct GoogleUserInfo {
     pub sub: String,
     pub email: String,
+    // Missing is treated as unverified
+    #[serde(default)]
+    pub email_verified: bool,
     pub name: Option<String>,
     pub picture: Option<String>,
 }
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ChallengeSubmission {
+    pub id: Uuid,
+    pub challenge_id: i32,
+    pub user_id: Uuid,
+    pub submission_url: String,
+    pub score: Option<i32>,
+    #[serde(with = "crate::timestamp::option")]
+    pub scored_at: Option<time::OffsetDateTime>,
+    #[serde(with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(with = "crate::timestamp")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSubmissionRequest {
+    #[serde(rename = "submissionUrl")]
+    pub submission_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmissionResponse {
+    pub id: Uuid,
+    #[serde(rename = "challengeId")]
+    pub challenge_id: i32,
+    #[serde(rename = "submissionUrl")]
+    pub submission_url: String,
+    pub score: Option<i32>,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(rename = "updatedAt", with = "crate::timestamp")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+// One of the caller's own submissions, as listed by GET /users/submissions
+#[derive(Debug, Serialize, FromRow)]
+pub struct UserSubmissionEntry {
+    pub id: Uuid,
+    #[serde(rename = "challengeId")]
+    pub challenge_id: i32,
+    #[serde(rename = "challengeTitle")]
+    pub challenge_title: String,
+    #[serde(rename = "challengeWeek")]
+    pub challenge_week: i32,
+    #[serde(rename = "submissionUrl")]
+    pub submission_url: String,
+    pub score: Option<i32>,
+    pub graded: bool,
+    #[serde(rename = "scoredAt", with = "crate::timestamp::option")]
+    pub scored_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(rename = "updatedAt", with = "crate::timestamp")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminScoreSubmissionRequest {
+    pub score: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminSubmissionResponse {
+    pub id: Uuid,
+    #[serde(rename = "challengeId")]
+    pub challenge_id: i32,
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    #[serde(rename = "submissionUrl")]
+    pub submission_url: String,
+    pub score: Option<i32>,
+    #[serde(rename = "scoredAt", with = "crate::timestamp::option")]
+    pub scored_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(rename = "updatedAt", with = "crate::timestamp")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+// A submission in a challenge's grading queue, with who sent it
+#[derive(Debug, Serialize, FromRow)]
+pub struct AdminChallengeSubmissionResponse {
+    pub id: Uuid,
+    #[serde(rename = "challengeId")]
+    pub challenge_id: i32,
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    #[serde(rename = "userName")]
+    pub user_name: String,
+    #[serde(rename = "userEmail")]
+    pub user_email: String,
+    #[serde(rename = "submissionUrl")]
+    pub submission_url: String,
+    pub score: Option<i32>,
+    #[serde(rename = "scoredAt", with = "crate::timestamp::option")]
+    pub scored_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(rename = "updatedAt", with = "crate::timestamp")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminRecomputeRanksResponse {
+    pub success: bool,
+    pub updated: u64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PointsTimelineBucket {
+    #[serde(with = "crate::timestamp")]
+    pub start: time::OffsetDateTime,
+    pub total: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PointsTimelineResponse {
+    pub period: String,
+    pub buckets: Vec<PointsTimelineBucket>,
+}
+
+// One row of the points ledger, with the challenge title when it came from scoring
+#[derive(Debug, Serialize, FromRow)]
+pub struct PointsHistoryEntry {
+    pub id: i64,
+    pub delta: i32,
+    pub reason: String,
+    #[serde(rename = "challengeId")]
+    pub challenge_id: Option<i32>,
+    #[serde(rename = "challengeTitle")]
+    pub challenge_title: Option<String>,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaginationQuery {
+    pub page: Option<i64>,
+    #[serde(rename = "pageSize")]
+    pub page_size: Option<i64>,
+}
+
+impl PaginationQuery {
+    pub const DEFAULT_PAGE_SIZE: i64 = 20;
+    pub const MAX_PAGE_SIZE: i64 = 100;
+
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn page_size(&self) -> i64 {
+        self.page_size
+            .unwrap_or(Self::DEFAULT_PAGE_SIZE)
+            .clamp(1, Self::MAX_PAGE_SIZE)
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page() - 1).saturating_mul(self.page_size())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaginatedResponse<T> {
+    pub items: Vec<T>,
+    pub page: i64,
+    #[serde(rename = "pageSize")]
+    pub page_size: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub read: bool,
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationResponse {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub read: bool,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+}
+
+impl From<Notification> for NotificationResponse {
+    fn from(n: Notification) -> Self {
+        Self {
+            id: n.id,
+            kind: n.kind,
+            payload: n.payload,
+            read: n.read,
+            created_at: n.created_at,
+        }
+    }
+}
+
+// A page of notifications plus the user's unread total across all pages
+#[derive(Debug, Serialize)]
+pub struct NotificationListResponse {
+    pub items: Vec<NotificationResponse>,
+    pub page: i64,
+    #[serde(rename = "pageSize")]
+    pub page_size: i64,
+    pub total: i64,
+    #[serde(rename = "unreadCount")]
+    pub unread_count: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ContactMessage {
+    pub id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub message: String,
+    pub handled: bool,
+    #[serde(with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminContactMessageResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub message: String,
+    pub handled: bool,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminContactHandledRequest {
+    pub handled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminUpdateUserRequest {
+    pub role: Option<String>,
+    pub points: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserResponse {
+    pub id: Uuid,
+    pub email: String,
+    #[serde(rename = "fullName")]
+    pub full_name: String,
+    pub role: String,
+    pub points: i32,
+    pub rank: i32,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+}
+
+// Summary counts for the admin dashboard; deleted resources and challenges are left out
+#[derive(Debug, Serialize, FromRow)]
+pub struct AdminStatsResponse {
+    #[serde(rename = "totalUsers")]
+    pub total_users: i64,
+    #[serde(rename = "completedProfiles")]
+    pub completed_profiles: i64,
+    #[serde(rename = "totalResources")]
+    pub total_resources: i64,
+    #[serde(rename = "visibleResources")]
+    pub visible_resources: i64,
+    #[serde(rename = "hiddenResources")]
+    pub hidden_resources: i64,
+    #[serde(rename = "totalChallenges")]
+    pub total_challenges: i64,
+    #[serde(rename = "unhandledContactMessages")]
+    pub unhandled_contact_messages: i64,
+    #[serde(rename = "submissionsThisWeek")]
+    pub submissions_this_week: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: Option<String>,
+    #[serde(rename = "confirmEmail")]
+    pub confirm_email: Option<String>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct UserExportProfile {
+    pub email: String,
+    #[serde(rename = "fullName")]
+    pub full_name: String,
+    #[serde(rename = "phoneNum")]
+    pub phone_num: Option<String>,
+    pub image: Option<String>,
+    pub points: i32,
+    pub rank: i32,
+    pub role: String,
+    pub university: Option<String>,
+    pub major: Option<String>,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct UserExportSubmission {
+    #[serde(rename = "challengeTitle")]
+    pub challenge_title: String,
+    #[serde(rename = "challengeWeek")]
+    pub challenge_week: i32,
+    #[serde(rename = "submissionUrl")]
+    pub submission_url: String,
+    pub score: Option<i32>,
+    #[serde(rename = "scoredAt", with = "crate::timestamp::option")]
+    pub scored_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct UserExportContactMessage {
+    pub name: String,
+    pub email: String,
+    pub message: String,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct UserExportPointsEvent {
+    pub delta: i32,
+    pub reason: String,
+    #[serde(rename = "createdAt", with = "crate::timestamp")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserExportResponse {
+    pub profile: UserExportProfile,
+    pub stats: Option<UserStatsResponse>,
+    pub submissions: Vec<UserExportSubmission>,
+    #[serde(rename = "contactMessages")]
+    pub contact_messages: Vec<UserExportContactMessage>,
+    #[serde(rename = "pointsHistory")]
+    pub points_history: Vec<UserExportPointsEvent>,
+    #[serde(rename = "exportedAt", with = "crate::timestamp")]
+    pub exported_at: time::OffsetDateTime,
+}
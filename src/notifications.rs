@@ -0,0 +1,26 @@
+use serde_json::Value;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+// Notification kinds understood by the frontend
+pub const SUBMISSION_SCORED: &str = "submission_scored";
+
+// Pass the transaction making the change so the notification is only kept if it commits
+pub async fn notify<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    kind: &str,
+    payload: Value,
+) -> Result<(), sqlx::Error>
+where
+    E: PgExecutor<'e>,
+{
+    sqlx::query("INSERT INTO notifications (user_id, kind, payload) VALUES ($1, $2, $3)")
+        .bind(user_id)
+        .bind(kind)
+        .bind(payload)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use oauth2::{HttpRequest, HttpResponse};
+use serde::{Deserialize, de::DeserializeOwned};
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::{audit, error::AppError, models::GoogleUserInfo};
+
+// How long the frontend has to redeem the code from the OAuth redirect
+pub const EXCHANGE_CODE_LIFETIME_SECS: i64 = 60;
+
+// The sign-in services we know how to talk to. Each OAuthConfig names one, which decides
+// the scopes asked for, how the profile is read and where the account id is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OAuthProvider {
+    Google,
+    GitHub,
+}
+
+impl OAuthProvider {
+    // For messages shown to users
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Google => "Google",
+            Self::GitHub => "GitHub",
+        }
+    }
+
+    pub fn scopes(self) -> &'static [&'static str] {
+        match self {
+            Self::Google => &["openid", "email", "profile"],
+            Self::GitHub => &["read:user", "user:email"],
+        }
+    }
+
+    // The users column holding the provider's account id. Only ever these fixed names,
+    // so it's safe to format into SQL.
+    pub fn id_column(self) -> &'static str {
+        match self {
+            Self::Google => "google_id",
+            Self::GitHub => "github_id",
+        }
+    }
+
+    // Audit action and details key recorded when an existing account gets linked
+    pub fn linked_action(self) -> &'static str {
+        match self {
+            Self::Google => audit::GOOGLE_ACCOUNT_LINKED,
+            Self::GitHub => audit::GITHUB_ACCOUNT_LINKED,
+        }
+    }
+
+    pub fn id_key(self) -> &'static str {
+        match self {
+            Self::Google => "googleId",
+            Self::GitHub => "githubId",
+        }
+    }
+}
+
+// The parts of a provider's profile sign-in needs, whichever provider it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthUserInfo {
+    // The provider's stable account id
+    pub subject: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+}
+
+impl From<GoogleUserInfo> for OAuthUserInfo {
+    fn from(info: GoogleUserInfo) -> Self {
+        Self {
+            subject: info.sub,
+            email: info.email,
+            email_verified: info.email_verified,
+            name: info.name,
+            picture: info.picture,
+        }
+    }
+}
+
+// GET https://api.github.com/user
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    id: i64,
+    login: String,
+    name: Option<String>,
+    avatar_url: Option<String>,
+}
+
+// One entry of GET https://api.github.com/user/emails
+#[derive(Debug, Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+// Reads the signed-in account's profile with the access token from the code exchange
+pub async fn fetch_user_info(
+    client: &reqwest::Client,
+    provider: OAuthProvider,
+    userinfo_url: &str,
+    access_token: &str,
+) -> Result<OAuthUserInfo, AppError> {
+    match provider {
+        OAuthProvider::Google => {
+            let info: GoogleUserInfo = get_json(client, userinfo_url, access_token).await?;
+            Ok(info.into())
+        }
+        OAuthProvider::GitHub => {
+            let user: GitHubUser = get_json(client, userinfo_url, access_token).await?;
+            // The profile only shows whichever address the user made public, if any;
+            // the primary one from /user/emails comes with its verification status
+            let emails: Vec<GitHubEmail> =
+                get_json(client, &format!("{userinfo_url}/emails"), access_token).await?;
+            let primary = emails
+                .into_iter()
+                .find(|email| email.primary)
+                .ok_or_else(|| {
+                    AppError::BadRequest("Your GitHub account has no primary email".to_string())
+                })?;
+
+            Ok(OAuthUserInfo {
+                subject: user.id.to_string(),
+                email: primary.email,
+                email_verified: primary.verified,
+                name: user.name.or(Some(user.login)),
+                picture: user.avatar_url,
+            })
+        }
+    }
+}
+
+async fn get_json<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    access_token: &str,
+) -> Result<T, AppError> {
+    client
+        .get(url)
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .map_err(upstream_error)?
+        .json()
+        .await
+        .map_err(upstream_error)
+}
+
+// One client for every call to a provider, so connections are pooled and no request can
+// hang a sign-in forever
+pub fn http_client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        // GitHub's API turns away requests without one
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .connect_timeout(timeout)
+        .timeout(timeout)
+        // Following redirects from the token endpoint is an SSRF vector (see the oauth2 docs)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("Failed to build OAuth HTTP client")
+}
+
+// Transport for oauth2's `request_async`. oauth2 speaks http 0.2 types while our reqwest
+// is on http 1, so the request and response are converted field by field.
+pub async fn send(
+    client: &reqwest::Client,
+    request: HttpRequest,
+) -> Result<HttpResponse, reqwest::Error> {
+    let method = reqwest::Method::from_bytes(request.method.as_str().as_bytes())
+        .unwrap_or(reqwest::Method::POST);
+    let mut builder = client
+        .request(method, request.url.as_str())
+        .body(request.body);
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+
+    let response = builder.send().await?;
+
+    let status_code = oauth2::http::StatusCode::from_u16(response.status().as_u16())
+        .unwrap_or(oauth2::http::StatusCode::BAD_GATEWAY);
+    let mut headers = oauth2::http::HeaderMap::new();
+    for (name, value) in response.headers() {
+        if let (Ok(name), Ok(value)) = (
+            oauth2::http::HeaderName::from_bytes(name.as_str().as_bytes()),
+            oauth2::http::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    let body = response.bytes().await?.to_vec();
+
+    Ok(HttpResponse {
+        status_code,
+        headers,
+        body,
+    })
+}
+
+// A provider being slow is worth telling the user about; anything else stays a plain 500
+pub fn upstream_error(error: reqwest::Error) -> AppError {
+    if error.is_timeout() {
+        AppError::UpstreamTimeout
+    } else {
+        AppError::InternalError(error.into())
+    }
+}
+
+// Issues the one-time code the OAuth callback hands to the frontend instead of the token,
+// so nothing sensitive ends up in browser history, logs or Referer headers
+pub async fn issue_exchange_code(pool: &PgPool, user_id: Uuid) -> Result<String, sqlx::Error> {
+    // Two v4 UUIDs give 244 random bits
+    let code = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+
+    // Codes nobody came back for are cleaned up here rather than by a background job
+    sqlx::query("DELETE FROM oauth_exchange_codes WHERE expires_at < NOW()")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO oauth_exchange_codes (code, user_id, expires_at)
+         VALUES ($1, $2, NOW() + make_interval(secs => $3))",
+    )
+    .bind(&code)
+    .bind(user_id)
+    .bind(EXCHANGE_CODE_LIFETIME_SECS as f64)
+    .execute(pool)
+    .await?;
+
+    Ok(code)
+}
+
+// Consumes a code, returning whose it was. Unknown, already used and expired codes all
+// give None; deleting in the same statement means two concurrent redemptions can't both win.
+pub async fn redeem_exchange_code(pool: &PgPool, code: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    let redeemed: Option<(Uuid,)> = sqlx::query_as(
+        "DELETE FROM oauth_exchange_codes WHERE code = $1 AND expires_at > NOW() RETURNING user_id",
+    )
+    .bind(code)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(redeemed.map(|(user_id,)| user_id))
+}
@@ -0,0 +1,24 @@
+use sqlx::PgExecutor;
+
+// Rank is the position by points, ties going to the earlier account. Takes a
+// transaction so callers can recompute alongside the points change.
+pub async fn recompute_ranks<'e, E>(executor: E) -> Result<u64, sqlx::Error>
+where
+    E: PgExecutor<'e>,
+{
+    let result = sqlx::query(
+        r#"
+        UPDATE users u
+        SET rank = r.position
+        FROM (
+            SELECT id, ROW_NUMBER() OVER (ORDER BY points DESC, created_at ASC, id ASC)::INTEGER AS position
+            FROM users
+        ) r
+        WHERE u.id = r.id AND u.rank <> r.position
+        "#,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Fixed-window, per-IP request counter kept in memory. Good enough for a single
+// instance; counts are not shared between replicas.
+pub struct RateLimiter {
+    max_requests: u32,
+    window: Duration,
+    hits: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+// Drop expired entries once the map grows past this many addresses
+const PRUNE_THRESHOLD: usize = 10_000;
+
+// The key an address is counted under. A single IPv6 host is usually handed a whole /64,
+// so counting each address separately would let it rotate past the limit.
+pub fn client_key(ip: IpAddr) -> IpAddr {
+    match ip.to_canonical() {
+        IpAddr::V6(ip) => {
+            let prefix = u128::from(ip) & !(u128::MAX >> 64);
+            IpAddr::V6(Ipv6Addr::from(prefix))
+        }
+        ip => ip,
+    }
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: u32, window: Duration) -> Self {
+        Self {
+            max_requests,
+            window,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Records a request from `ip`, returning false once it has used up the current window
+    pub fn check(&self, ip: IpAddr) -> bool {
+        let ip = client_key(ip);
+        let now = Instant::now();
+        let mut hits = self.hits.lock().unwrap_or_else(|e| e.into_inner());
+
+        if hits.len() > PRUNE_THRESHOLD {
+            hits.retain(|_, (started, _)| now.duration_since(*started) < self.window);
+        }
+
+        let (started, count) = hits.entry(ip).or_insert((now, 0));
+        if now.duration_since(*started) >= self.window {
+            *started = now;
+            *count = 0;
+        }
+
+        if *count >= self.max_requests {
+            return false;
+        }
+
+        *count += 1;
+        true
+    }
+}
@@ -0,0 +1,179 @@
+use aws_sdk_s3::{
+    Client,
+    config::{BehaviorVersion, Credentials, Region},
+    primitives::ByteStream,
+};
+use axum::async_trait;
+use std::path::PathBuf;
+
+#[async_trait]
+pub trait FileStorage: Send + Sync {
+    // Stores the data under `key` (e.g. "avatars/<uuid>_me.png") and returns its public URL
+    async fn save(
+        &self,
+        key: &str,
+        data: &[u8],
+        content_type: Option<&str>,
+    ) -> anyhow::Result<String>;
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+
+    fn url_for(&self, key: &str) -> String;
+
+    // Maps a public URL back to the key it was saved under. Returns None for URLs
+    // this storage doesn't own (e.g. Google profile pictures)
+    fn key_for(&self, url: &str) -> Option<String>;
+}
+
+// Rejects keys that could escape the storage root
+fn is_safe_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('/')
+        && key
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "..")
+}
+
+// Writes files to a local directory served by the app under `base_url`
+pub struct LocalStorage {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>, base_url: &str) -> Self {
+        Self {
+            root: root.into(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl FileStorage for LocalStorage {
+    async fn save(
+        &self,
+        key: &str,
+        data: &[u8],
+        _content_type: Option<&str>,
+    ) -> anyhow::Result<String> {
+        anyhow::ensure!(is_safe_key(key), "Invalid storage key: {key}");
+
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+
+            // Belt and braces on top of is_safe_key: symlinks could still point elsewhere
+            let root = tokio::fs::canonicalize(&self.root).await?;
+            let parent = tokio::fs::canonicalize(parent).await?;
+            anyhow::ensure!(
+                parent.starts_with(&root),
+                "Storage key escapes the upload directory: {key}"
+            );
+        }
+        tokio::fs::write(&path, data).await?;
+
+        Ok(self.url_for(key))
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(is_safe_key(key), "Invalid storage key: {key}");
+
+        tokio::fs::remove_file(self.root.join(key)).await?;
+        Ok(())
+    }
+
+    fn url_for(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, key)
+    }
+
+    fn key_for(&self, url: &str) -> Option<String> {
+        let key = url.strip_prefix(&self.base_url)?.strip_prefix('/')?;
+        is_safe_key(key).then(|| key.to_string())
+    }
+}
+
+// Stores files in an S3-compatible bucket (AWS, MinIO, R2, ...)
+pub struct S3Storage {
+    client: Client,
+    bucket: String,
+    public_url: String,
+}
+
+impl S3Storage {
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        endpoint: Option<&str>,
+        credentials: Option<(String, String)>,
+        public_url: &str,
+    ) -> Self {
+        let mut config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(region.to_string()));
+
+        if let Some(endpoint) = endpoint {
+            // Custom endpoints (MinIO and friends) generally don't support virtual-hosted buckets
+            config = config.endpoint_url(endpoint).force_path_style(true);
+        }
+
+        if let Some((access_key_id, secret_access_key)) = credentials {
+            config = config.credentials_provider(Credentials::new(
+                access_key_id,
+                secret_access_key,
+                None,
+                None,
+                "env",
+            ));
+        }
+
+        Self {
+            client: Client::from_conf(config.build()),
+            bucket: bucket.to_string(),
+            public_url: public_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl FileStorage for S3Storage {
+    async fn save(
+        &self,
+        key: &str,
+        data: &[u8],
+        content_type: Option<&str>,
+    ) -> anyhow::Result<String> {
+        anyhow::ensure!(is_safe_key(key), "Invalid storage key: {key}");
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(ByteStream::from(data.to_vec()))
+            .set_content_type(content_type.map(str::to_string))
+            .send()
+            .await?;
+
+        Ok(self.url_for(key))
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    fn url_for(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+
+    fn key_for(&self, url: &str) -> Option<String> {
+        let key = url.strip_prefix(&self.public_url)?.strip_prefix('/')?;
+        is_safe_key(key).then(|| key.to_string())
+    }
+}
@@ -0,0 +1,63 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+const REQUEST_DURATION_METRIC: &str = "http_request_duration_seconds";
+
+// The recorder is process-global, so it is installed once and shared by every app instance
+pub fn install_prometheus_recorder() -> PrometheusHandle {
+    static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+    HANDLE
+        .get_or_init(|| {
+            PrometheusBuilder::new()
+                .set_buckets_for_metric(
+                    Matcher::Full(REQUEST_DURATION_METRIC.to_string()),
+                    &[
+                        0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+                    ],
+                )
+                .expect("valid histogram buckets")
+                .install_recorder()
+                .expect("failed to install Prometheus recorder")
+        })
+        .clone()
+}
+
+// Records a request counter and latency histogram labelled by route template and status
+pub async fn track_metrics(request: Request, next: Next) -> Response {
+    // Use the route template (e.g. /resources/:id) so ids don't explode label cardinality
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = request.method().to_string();
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("path", path),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    metrics::counter!("http_requests_total", &labels).increment(1);
+    metrics::histogram!(REQUEST_DURATION_METRIC, &labels).record(start.elapsed().as_secs_f64());
+
+    response
+}
+
+pub fn record_pool_metrics(pool: &sqlx::PgPool) {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+
+    metrics::gauge!("db_pool_connections").set(f64::from(size));
+    metrics::gauge!("db_pool_idle_connections").set(f64::from(idle));
+    metrics::gauge!("db_pool_active_connections").set(f64::from(size.saturating_sub(idle)));
+}
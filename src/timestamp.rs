@@ -0,0 +1,48 @@
+// Serde helpers so every timestamp in a response reads as an RFC3339 UTC string,
+// e.g. `2026-10-16T09:30:00Z`. Use with `#[serde(with = "crate::timestamp")]`, or
+// `crate::timestamp::option` for nullable columns.
+use serde::{Deserialize, Deserializer, Serializer};
+use time::{OffsetDateTime, UtcOffset, format_description::well_known::Rfc3339};
+
+pub fn format(value: OffsetDateTime) -> Result<String, time::error::Format> {
+    value.to_offset(UtcOffset::UTC).format(&Rfc3339)
+}
+
+pub fn serialize<S>(value: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let s = format(*value).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_str(&s)
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<OffsetDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    OffsetDateTime::parse(&s, &Rfc3339).map_err(serde::de::Error::custom)
+}
+
+pub mod option {
+    use super::*;
+
+    pub fn serialize<S>(value: &Option<OffsetDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<OffsetDateTime>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: Option<String> = Option::deserialize(deserializer)?;
+        s.map(|s| OffsetDateTime::parse(&s, &Rfc3339).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
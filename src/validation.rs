@@ -0,0 +1,256 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::error::AppError;
+
+static EMAIL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[^\s@]+@[^\s@]+\.[^\s@.]{2,}$").expect("valid email regex"));
+
+// E.164-ish: optional leading +, no leading zero, 7 to 15 digits
+static PHONE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\+?[1-9][0-9]{6,14}$").expect("valid phone regex"));
+
+// Canonical form emails are stored and compared in; users.email is unique on LOWER(email)
+pub fn canonical_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+// Canonicalizes an email, rejecting anything that doesn't look like an address
+pub fn normalize_email(email: &str) -> Result<String, AppError> {
+    let email = canonical_email(email);
+
+    if email.len() > 255 || !EMAIL_RE.is_match(&email) {
+        return Err(AppError::ValidationError(
+            "Please enter a valid email address".to_string(),
+        ));
+    }
+
+    Ok(email)
+}
+
+// Longest client file name kept in storage keys (the UUID prefix comes on top)
+const MAX_FILE_NAME_LEN: usize = 100;
+
+// Turns a client-supplied upload name into something safe to put in a storage key:
+// only the base name is kept and anything outside [A-Za-z0-9._-] becomes `_`.
+// Names that try to climb out of the upload directory are rejected outright.
+pub fn sanitize_file_name(file_name: &str) -> Result<String, AppError> {
+    let invalid = || AppError::BadRequest("Invalid file name".to_string());
+
+    if file_name.contains('\0') || file_name.split(['/', '\\']).any(|part| part == "..") {
+        return Err(invalid());
+    }
+
+    let base_name = file_name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let sanitized: String = base_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_FILE_NAME_LEN)
+        .collect();
+    // No hidden files or names made only of dots
+    let sanitized = sanitized.trim_start_matches('.');
+
+    if sanitized.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(sanitized.to_string())
+}
+
+// Trims a link field and checks it is an absolute http(s) URL, so typos like `htpp://`
+// or plain text never reach the frontend. Blank means no link.
+pub fn normalize_http_url(label: &str, value: &str) -> Result<Option<String>, AppError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let url = url::Url::parse(value)
+        .map_err(|_| AppError::ValidationError(format!("{label} must be a valid URL")))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(AppError::ValidationError(format!(
+            "{label} must start with http:// or https://"
+        )));
+    }
+
+    Ok(Some(url.into()))
+}
+
+// Scheme, host and port of an http(s) URL, e.g. "https://aiclub-uj.com", or None if it
+// isn't one. Trailing slashes and letter case don't matter.
+pub fn url_origin(value: &str) -> Option<String> {
+    let url = url::Url::parse(value.trim()).ok()?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return None;
+    }
+    Some(url.origin().ascii_serialization())
+}
+
+// Returns the frontend URL to send a user back to if its origin is on the allowlist
+pub fn allowed_frontend_url<'a>(url: &'a str, allowed_origins: &[String]) -> Option<&'a str> {
+    let origin = url_origin(url)?;
+    allowed_origins
+        .contains(&origin)
+        .then(|| url.trim().trim_end_matches('/'))
+}
+
+// Strips common separators from a phone number and checks it against an E.164-style pattern
+pub fn normalize_phone(phone: &str) -> Result<String, AppError> {
+    let phone: String = phone
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '(' | ')' | '.'))
+        .collect();
+
+    if !PHONE_RE.is_match(&phone) {
+        return Err(AppError::ValidationError(
+            "Please enter a valid phone number, e.g. +962791234567".to_string(),
+        ));
+    }
+
+    Ok(phone)
+}
+
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: false,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    // Reads PASSWORD_MIN_LENGTH and PASSWORD_REQUIRE_{LOWERCASE,UPPERCASE,DIGIT,SYMBOL},
+    // keeping the default for anything unset or unparsable
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let flag = |name: &str, default: bool| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| match v.trim().to_lowercase().as_str() {
+                    "true" | "1" | "yes" => Some(true),
+                    "false" | "0" | "no" => Some(false),
+                    _ => None,
+                })
+                .unwrap_or(default)
+        };
+
+        Self {
+            min_length: std::env::var("PASSWORD_MIN_LENGTH")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.min_length),
+            require_lowercase: flag("PASSWORD_REQUIRE_LOWERCASE", defaults.require_lowercase),
+            require_uppercase: flag("PASSWORD_REQUIRE_UPPERCASE", defaults.require_uppercase),
+            require_digit: flag("PASSWORD_REQUIRE_DIGIT", defaults.require_digit),
+            require_symbol: flag("PASSWORD_REQUIRE_SYMBOL", defaults.require_symbol),
+        }
+    }
+}
+
+// Checks a plaintext password against the policy, listing everything that's missing
+pub fn validate_password(password: &str, policy: &PasswordPolicy) -> Result<(), AppError> {
+    let mut missing = Vec::new();
+
+    if password.chars().count() < policy.min_length {
+        missing.push(format!("at least {} characters", policy.min_length));
+    }
+    if policy.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+        missing.push("a lowercase letter".to_string());
+    }
+    if policy.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+        missing.push("an uppercase letter".to_string());
+    }
+    if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        missing.push("a digit".to_string());
+    }
+    if policy.require_symbol && !password.chars().any(|c| !c.is_alphanumeric()) {
+        missing.push("a symbol".to_string());
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::ValidationError(format!(
+            "Password must contain {}",
+            missing.join(", ")
+        )))
+    }
+}
+
+// Collects per-field validation failures so a request can report all of them at once
+#[derive(Debug, Default)]
+pub struct FieldErrors(HashMap<String, String>);
+
+impl FieldErrors {
+    // Records a failure for `field`; the first reason reported for a field wins
+    pub fn add(&mut self, field: &str, reason: impl Into<String>) {
+        self.0
+            .entry(field.to_string())
+            .or_insert_with(|| reason.into());
+    }
+
+    // Records the error of a failed check against `field`, returning the value on success
+    pub fn check<T>(&mut self, field: &str, result: Result<T, AppError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(AppError::ValidationError(reason) | AppError::BadRequest(reason)) => {
+                self.add(field, reason);
+                None
+            }
+            Err(e) => {
+                self.add(field, e.to_string());
+                None
+            }
+        }
+    }
+
+    // Records `field` as missing when the value is absent or blank
+    pub fn require(&mut self, field: &str, value: Option<String>) -> Option<String> {
+        match value {
+            Some(value) if !value.trim().is_empty() => Some(value),
+            _ => {
+                self.add(field, "This field is required");
+                None
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), AppError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.into())
+        }
+    }
+}
+
+impl From<FieldErrors> for AppError {
+    fn from(errors: FieldErrors) -> Self {
+        AppError::ValidationErrors(errors.0)
+    }
+}
@@ -0,0 +1,4810 @@
+// Needs a Postgres server, see tests/common/mod.rs
+#![cfg(feature = "integration-tests")]
+
+mod common;
+
+use axum::Router;
+use axum::http::{Method, StatusCode, header};
+use axum::response::IntoResponse;
+use futures_util::StreamExt;
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use uj_ai_club_backend::auth::{
+    Claims, DEFAULT_AUDIENCE, DEFAULT_ISSUER, SameSite, TOKEN_LIFETIME_SECS,
+};
+use uj_ai_club_backend::config::AdminBootstrapConfig;
+use uj_ai_club_backend::error::AppError;
+
+use common::{
+    JWT_SECRET, PASSWORD, RecordingMailer, app_with_config, create_challenge,
+    create_week_challenge, fake_github, fake_google, get_raw, png_bytes, redirect_location, score,
+    send, send_multipart, send_multipart_files, send_raw, send_with_headers, set_role, setup,
+    setup_db, signup, slow_google, submit, test_config,
+};
+
+#[sqlx::test(migrations = false)]
+async fn signup_login_and_fetch_profile(pool: PgPool) {
+    let app = setup(pool).await;
+
+    signup(&app, "Member@Example.com").await;
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/auth/login",
+        None,
+        Some(json!({ "email": "member@example.com", "password": PASSWORD })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["user"]["email"], "member@example.com");
+    let token = body["token"].as_str().unwrap();
+
+    let (status, body) = send(&app, Method::GET, "/users/profile", Some(token), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["name"], "Test User");
+    assert_eq!(body["points"], 0);
+}
+
+#[sqlx::test(migrations = false)]
+async fn signup_and_profile_reject_malformed_contact_details(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let token = signup(&app, "member@example.com").await;
+
+    for (email, phone) in [
+        ("notanemail", "+962791234567"),
+        ("other@example.com", "0791234567"),
+        ("other@example.com", "call me"),
+    ] {
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/auth/signup",
+            None,
+            Some(json!({
+                "fullName": "Other",
+                "phoneNum": phone,
+                "email": email,
+                "password": PASSWORD,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{email}/{phone}: {body}");
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+    }
+
+    for update in [json!({ "email": "member@" }), json!({ "phoneNum": "12" })] {
+        let (status, body) = send(
+            &app,
+            Method::PUT,
+            "/users/profile",
+            Some(&token),
+            Some(update.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{update}: {body}");
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+    }
+
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        "/users/profile",
+        Some(&token),
+        Some(json!({ "email": " Renamed@Example.COM " })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["email"], "renamed@example.com");
+}
+
+#[sqlx::test(migrations = false)]
+async fn profile_phone_numbers_are_normalized_and_persisted(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let token = signup(&app, "member@example.com").await;
+
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        "/users/profile",
+        Some(&token),
+        Some(json!({ "phoneNum": "+962 79-555-0000" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["phoneNum"], "+962795550000");
+
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        "/users/profile",
+        Some(&token),
+        Some(json!({ "phoneNum": "not a number" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+
+    // The rejected update left the stored number alone, and other edits keep it
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        "/users/profile",
+        Some(&token),
+        Some(json!({ "fullName": "Renamed" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["phoneNum"], "+962795550000");
+
+    let phone: String = sqlx::query_scalar("SELECT phone_num FROM users WHERE email = $1")
+        .bind("member@example.com")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(phone, "+962795550000");
+}
+
+#[sqlx::test(migrations = false)]
+async fn public_profiles_hide_contact_details_and_private_accounts(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let member = signup(&app, "member@example.com").await;
+    let other = signup(&app, "other@example.com").await;
+    let id = user_id(&pool, "member@example.com").await;
+    let uri = format!("/users/{id}");
+
+    let (status, body) = send(&app, Method::GET, &uri, None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        body,
+        json!({
+            "id": id,
+            "name": "Test User",
+            "image": null,
+            "points": 0,
+            "rank": 0,
+            "university": null,
+            "major": null,
+        })
+    );
+
+    let (status, _) = send(
+        &app,
+        Method::GET,
+        &format!("/users/{}", uuid::Uuid::new_v4()),
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A private profile is only visible to its owner
+    sqlx::query("UPDATE users SET profile_public = false WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    for token in [None, Some(other.as_str())] {
+        let (status, body) = send(&app, Method::GET, &uri, token, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+        assert_eq!(body["code"], "NOT_FOUND");
+    }
+    let (status, body) = send(&app, Method::GET, &uri, Some(&member), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["id"], json!(id));
+}
+
+// Rows written before emails were normalized still sign in, whatever case is typed
+#[sqlx::test(migrations = false)]
+async fn mixed_case_legacy_emails_can_log_in(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    sqlx::query(
+        "INSERT INTO users (id, email, full_name, password_hash) VALUES (gen_random_uuid(), 'Legacy@Example.com', 'Legacy', $1)",
+    )
+    .bind(bcrypt::hash(PASSWORD, 4).unwrap())
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    for email in [
+        "legacy@example.com",
+        "Legacy@Example.com",
+        " LEGACY@EXAMPLE.COM ",
+    ] {
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/auth/login",
+            None,
+            Some(json!({ "email": email, "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{email}: {body}");
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn auth_responses_report_token_expiry(pool: PgPool) {
+    let app = setup(pool).await;
+    signup(&app, "expiry@example.com").await;
+
+    let before = chrono::Utc::now().timestamp();
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/auth/login",
+        None,
+        Some(json!({ "email": "expiry@example.com", "password": PASSWORD })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    // Allow a second either way for the clock ticking during the request
+    let expires_in = body["expiresIn"].as_i64().unwrap();
+    assert!((TOKEN_LIFETIME_SECS - 1..=TOKEN_LIFETIME_SECS).contains(&expires_in));
+    let expires_at = body["expiresAt"].as_i64().unwrap();
+    assert!(
+        (before + TOKEN_LIFETIME_SECS..=before + TOKEN_LIFETIME_SECS + 1).contains(&expires_at)
+    );
+}
+
+#[sqlx::test(migrations = false)]
+async fn remember_me_logins_get_longer_lived_tokens(pool: PgPool) {
+    setup_db(&pool).await;
+    let remembered = 7 * 24 * 60 * 60;
+    let mut config = test_config();
+    config.remember_me_lifetime = Duration::from_secs(remembered as u64);
+    let app = app_with_config(pool, config);
+    signup(&app, "expiry@example.com").await;
+
+    for (remember_me, lifetime) in [
+        (json!(true), remembered),
+        (json!(false), TOKEN_LIFETIME_SECS),
+    ] {
+        let before = chrono::Utc::now().timestamp();
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/auth/login",
+            None,
+            Some(json!({
+                "email": "expiry@example.com",
+                "password": PASSWORD,
+                "rememberMe": remember_me,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+
+        let mut validation = Validation::default();
+        validation.set_audience(&[DEFAULT_AUDIENCE]);
+        let claims = jsonwebtoken::decode::<Claims>(
+            body["token"].as_str().unwrap(),
+            &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
+            &validation,
+        )
+        .unwrap()
+        .claims;
+        assert!((before + lifetime..=before + lifetime + 1).contains(&claims.exp));
+        assert_eq!(body["expiresAt"], claims.exp);
+        let expires_in = body["expiresIn"].as_i64().unwrap();
+        assert!((lifetime - 1..=lifetime).contains(&expires_in));
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn leaderboard_socket_streams_updates(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(axum::serve(listener, app.clone()).into_future());
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws/leaderboard"))
+        .await
+        .unwrap();
+    let mut next_board = async || -> Value {
+        let message = socket.next().await.unwrap().unwrap();
+        serde_json::from_str(message.to_text().unwrap()).unwrap()
+    };
+
+    let board = next_board().await;
+    assert_eq!(board["entries"].as_array().unwrap().len(), 2);
+    assert_eq!(board["entries"][0]["points"], 0);
+
+    let challenge_id = create_challenge(&app, &admin).await;
+    let submission_id = submit(&app, &member, challenge_id).await;
+    score(&app, &admin, challenge_id, &submission_id, 50).await;
+
+    let board = next_board().await;
+    assert_eq!(board["entries"][0]["points"], 50);
+    assert_eq!(board["entries"][1]["points"], 0);
+}
+
+#[sqlx::test(migrations = false)]
+async fn scoring_notifies_the_member(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+
+    let challenge_id = create_challenge(&app, &admin).await;
+    let submission_id = submit(&app, &member, challenge_id).await;
+    score(&app, &admin, challenge_id, &submission_id, 30).await;
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/users/notifications",
+        Some(&member),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["unreadCount"], 1);
+    let notification = &body["items"][0];
+    assert_eq!(notification["kind"], "submission_scored");
+    assert_eq!(notification["payload"]["score"], 30);
+    assert_eq!(notification["read"], false);
+    let id = notification["id"].as_str().unwrap();
+
+    // Only the owner can mark it read
+    let uri = format!("/users/notifications/{id}/read");
+    let (status, _) = send(&app, Method::PATCH, &uri, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = send(&app, Method::PATCH, &uri, Some(&member), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["read"], true);
+
+    let (_, body) = send(
+        &app,
+        Method::GET,
+        "/users/notifications",
+        Some(&member),
+        None,
+    )
+    .await;
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["unreadCount"], 0);
+}
+
+#[sqlx::test(migrations = false)]
+async fn login_upgrades_weaker_password_hashes(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+
+    let stored_cost = async || -> u32 {
+        let (hash,): (String,) =
+            sqlx::query_as("SELECT password_hash FROM users WHERE email = 'member@example.com'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        hash.parse::<bcrypt::HashParts>().unwrap().get_cost()
+    };
+    let login = json!({ "email": "member@example.com", "password": PASSWORD });
+
+    // The test config hashes at cost 4; raise it as a deployment would
+    let mut config = test_config();
+    config.bcrypt_cost = 5;
+    let upgraded_app = app_with_config(pool.clone(), config);
+    assert_eq!(stored_cost().await, 4);
+
+    let (status, body) = send(
+        &upgraded_app,
+        Method::POST,
+        "/auth/login",
+        None,
+        Some(login.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(stored_cost().await, 5);
+
+    // The upgraded hash still accepts the same password
+    let (status, body) = send(&app, Method::POST, "/auth/login", None, Some(login)).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(stored_cost().await, 5);
+}
+
+#[sqlx::test(migrations = false)]
+async fn google_sign_in_does_not_take_over_password_accounts_by_default(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+
+    let config = fake_google(test_config(), "google-123", "Member@Example.com", true).await;
+    let app = app_with_config(pool.clone(), config);
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/auth/google/callback?code=abc&state=xyz",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    assert_eq!(body["code"], "ACCOUNT_LINKING_DISABLED");
+
+    let (google_id,): (Option<String>,) =
+        sqlx::query_as("SELECT google_id FROM users WHERE email = 'member@example.com'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(google_id, None);
+}
+
+#[sqlx::test(migrations = false)]
+async fn google_sign_in_links_accounts_when_allowed(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+
+    let mut config = fake_google(test_config(), "google-123", "Member@Example.com", true).await;
+    config.allow_oauth_account_linking = true;
+    let app = app_with_config(pool.clone(), config);
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/auth/google/callback?code=abc&state=xyz",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::TEMPORARY_REDIRECT, "{body}");
+
+    let (user_id, google_id): (uuid::Uuid, Option<String>) =
+        sqlx::query_as("SELECT id, google_id FROM users WHERE email = 'member@example.com'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(google_id.as_deref(), Some("google-123"));
+
+    let (actor_id, action, details): (Option<uuid::Uuid>, String, Value) =
+        sqlx::query_as("SELECT actor_id, action, details FROM audit_log")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(actor_id, Some(user_id));
+    assert_eq!(action, "google_account_linked");
+    assert_eq!(details["googleId"], "google-123");
+}
+
+// Later sign-ins don't copy the provider's email or picture over what the account has
+#[sqlx::test(migrations = false)]
+async fn returning_google_sign_ins_keep_the_stored_email_and_avatar(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+    signup(&app, "taken@example.com").await;
+    sqlx::query(
+        "UPDATE users SET google_id = 'google-123', image = '/uploads/avatars/me.png' WHERE email = 'member@example.com'",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // The Google account's address changed to an unverified one another member uses
+    let config = fake_google(test_config(), "google-123", "taken@example.com", false).await;
+    let app = app_with_config(pool.clone(), config);
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/auth/google/callback?code=abc&state=xyz",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::TEMPORARY_REDIRECT, "{body}");
+
+    let (email, image, full_name): (String, Option<String>, String) =
+        sqlx::query_as("SELECT email, image, full_name FROM users WHERE google_id = 'google-123'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(email, "member@example.com");
+    assert_eq!(image.as_deref(), Some("/uploads/avatars/me.png"));
+    assert_eq!(full_name, "Google User");
+}
+
+#[sqlx::test(migrations = false)]
+async fn concurrent_first_google_sign_ins_create_one_user(pool: PgPool) {
+    setup_db(&pool).await;
+    let config = fake_google(test_config(), "google-123", "member@example.com", true).await;
+    let app = app_with_config(pool.clone(), config);
+
+    let responses = futures_util::future::join_all((0..5).map(|_| {
+        send(
+            &app,
+            Method::GET,
+            "/auth/google/callback?code=abc&state=xyz",
+            None,
+            None,
+        )
+    }))
+    .await;
+    for (status, body) in responses {
+        assert_eq!(status, StatusCode::TEMPORARY_REDIRECT, "{body}");
+    }
+
+    let (users, stats): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(DISTINCT u.id), COUNT(s.id)
+        FROM users u LEFT JOIN user_stats s ON s.user_id = u.id
+        WHERE u.google_id = 'google-123'
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!((users, stats), (1, 1));
+}
+
+#[sqlx::test(migrations = false)]
+async fn google_sign_in_does_not_relink_a_linked_account(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+    sqlx::query("UPDATE users SET google_id = 'google-old' WHERE email = 'member@example.com'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let mut config = fake_google(test_config(), "google-new", "member@example.com", true).await;
+    config.allow_oauth_account_linking = true;
+    let app = app_with_config(pool.clone(), config);
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/auth/google/callback?code=abc&state=xyz",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    assert_eq!(body["code"], "OAUTH_ACCOUNT_CONFLICT");
+
+    let (google_id,): (Option<String>,) =
+        sqlx::query_as("SELECT google_id FROM users WHERE email = 'member@example.com'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(google_id.as_deref(), Some("google-old"));
+}
+
+#[sqlx::test(migrations = false)]
+async fn duplicate_google_ids_are_a_conflict(pool: PgPool) {
+    setup_db(&pool).await;
+    let insert = |email: &'static str| {
+        sqlx::query(
+            "INSERT INTO users (id, email, full_name, google_id) VALUES (gen_random_uuid(), $1, 'Google User', 'google-123')",
+        )
+        .bind(email)
+        .execute(&pool)
+    };
+    insert("first@example.com").await.unwrap();
+    let error = insert("second@example.com").await.unwrap_err();
+
+    let response = AppError::from(error).into_response();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "OAUTH_ACCOUNT_CONFLICT");
+}
+
+#[sqlx::test(migrations = false)]
+async fn google_sign_in_rejects_unverified_emails(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+
+    let mut config = fake_google(test_config(), "google-123", "member@example.com", false).await;
+    config.allow_oauth_account_linking = true;
+    let app = app_with_config(pool.clone(), config);
+
+    // Neither links to the existing account...
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/auth/google/callback?code=abc&state=xyz",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    let (google_id,): (Option<String>,) =
+        sqlx::query_as("SELECT google_id FROM users WHERE email = 'member@example.com'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(google_id, None);
+
+    // ...nor creates a new one
+    let config = fake_google(test_config(), "google-456", "newcomer@example.com", false).await;
+    let app = app_with_config(pool.clone(), config);
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/auth/google/callback?code=abc&state=xyz",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    let (users,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(users, 1);
+}
+
+#[sqlx::test(migrations = false)]
+async fn oauth_routes_dispatch_on_the_provider(pool: PgPool) {
+    setup_db(&pool).await;
+    let app = app_with_config(pool.clone(), test_config());
+
+    // GitHub isn't offered until it's configured
+    for uri in [
+        "/auth/oauth/github",
+        "/auth/oauth/github/callback?code=abc&state=xyz",
+        "/auth/oauth/nope",
+        "/auth/oauth/nope/callback?code=abc&state=xyz",
+    ] {
+        let (status, body) = send(&app, Method::GET, uri, None, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{uri}");
+        assert_eq!(body["code"], "NOT_FOUND");
+    }
+
+    let config = fake_github(test_config(), 583231, "Octo@Example.com", true).await;
+    let app = app_with_config(pool.clone(), config);
+
+    let location = redirect_location(&app, "/auth/oauth/github").await;
+    assert!(
+        location.starts_with("https://github.com/login/oauth/authorize?"),
+        "{location}"
+    );
+    assert!(location.contains("client_id=github-client"), "{location}");
+    assert!(
+        location.contains("scope=read%3Auser+user%3Aemail"),
+        "{location}"
+    );
+    let location = redirect_location(&app, "/auth/oauth/google").await;
+    assert!(
+        location.starts_with("https://accounts.google.com/"),
+        "{location}"
+    );
+
+    // The callback signs in with the primary, verified GitHub address
+    let location = redirect_location(&app, "/auth/oauth/github/callback?code=abc&state=xyz").await;
+    assert!(location.contains("/auth/callback?code="), "{location}");
+    let (name, github_id, google_id): (String, Option<String>, Option<String>) = sqlx::query_as(
+        "SELECT full_name, github_id, google_id FROM users WHERE email = 'octo@example.com'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(name, "octocat");
+    assert_eq!(github_id.as_deref(), Some("583231"));
+    assert_eq!(google_id, None);
+
+    // Signing in again finds the same account
+    redirect_location(&app, "/auth/oauth/github/callback?code=abc&state=xyz").await;
+    let (users,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(users, 1);
+}
+
+#[sqlx::test(migrations = false)]
+async fn github_sign_in_rejects_unverified_primary_emails(pool: PgPool) {
+    setup_db(&pool).await;
+    let config = fake_github(test_config(), 583231, "octo@example.com", false).await;
+    let app = app_with_config(pool.clone(), config);
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/auth/oauth/github/callback?code=abc&state=xyz",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["message"], "Your GitHub account email is not verified");
+}
+
+#[sqlx::test(migrations = false)]
+async fn google_sign_in_times_out_on_a_hung_userinfo_response(pool: PgPool) {
+    // Fails before the database is touched, so no migrations needed
+    let mut config = slow_google(test_config(), Duration::from_secs(5)).await;
+    config.oauth_timeout = Duration::from_millis(200);
+    let app = app_with_config(pool, config);
+
+    let started = std::time::Instant::now();
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/auth/google/callback?code=abc&state=xyz",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::GATEWAY_TIMEOUT, "{body}");
+    assert_eq!(body["code"], "UPSTREAM_TIMEOUT");
+    assert!(!body["message"].as_str().unwrap().contains("Google"));
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[sqlx::test(migrations = false)]
+async fn google_sign_in_only_redirects_to_allowed_frontends(pool: PgPool) {
+    setup_db(&pool).await;
+    let callback = "/auth/google/callback?code=abc&state=xyz";
+
+    let mut config = fake_google(test_config(), "google-123", "member@example.com", true).await;
+    config.frontend_url = "https://evil.example/".to_string();
+    let app = app_with_config(pool.clone(), config.clone());
+
+    let location = redirect_location(&app, callback).await;
+    assert!(
+        location.starts_with("https://aiclub-uj.com/auth/callback?code="),
+        "{location}"
+    );
+
+    config.frontend_allowed_origins = vec!["https://evil.example".to_string()];
+    let app = app_with_config(pool, config);
+
+    let location = redirect_location(&app, callback).await;
+    assert!(
+        location.starts_with("https://evil.example/auth/callback?code="),
+        "{location}"
+    );
+}
+
+#[sqlx::test(migrations = false)]
+async fn signup_and_password_changes_enforce_the_password_policy(pool: PgPool) {
+    setup_db(&pool).await;
+    let mut config = test_config();
+    config.password_policy.require_symbol = true;
+    let app = app_with_config(pool.clone(), config);
+    let token = signup(&app, "member@example.com").await;
+
+    for (password, rule) in [
+        ("Pa0rd!", "at least 8 characters"),
+        ("PASSW0RD!", "a lowercase letter"),
+        ("passw0rd!", "an uppercase letter"),
+        ("Password!", "a digit"),
+        ("Passw0rdd", "a symbol"),
+    ] {
+        let expected = format!("Password must contain {rule}");
+
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/auth/signup",
+            None,
+            Some(json!({
+                "fullName": "Weak Password",
+                "phoneNum": "+962791234567",
+                "email": "weak@example.com",
+                "password": password,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(body["errors"]["password"], expected.as_str(), "{body}");
+
+        let (status, body) = send(
+            &app,
+            Method::PUT,
+            "/users/password",
+            Some(&token),
+            Some(json!({ "currentPassword": PASSWORD, "newPassword": password })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+        assert_eq!(body["message"], expected.as_str(), "{body}");
+    }
+
+    let (users,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(users, 1);
+
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        "/users/password",
+        Some(&token),
+        Some(json!({ "currentPassword": PASSWORD, "newPassword": "N3w-password!" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[sqlx::test(migrations = false)]
+async fn password_less_messages_do_not_assume_google(pool: PgPool) {
+    setup_db(&pool).await;
+    let config = fake_github(test_config(), 583231, "octo@example.com", true).await;
+    let app = app_with_config(pool.clone(), config);
+
+    let location = redirect_location(&app, "/auth/oauth/github/callback?code=abc&state=xyz").await;
+    let (_, code) = location.split_once("?code=").expect("code in redirect");
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/auth/exchange",
+        None,
+        Some(json!({ "code": code })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let (status, login) = send(
+        &app,
+        Method::POST,
+        "/auth/login",
+        None,
+        Some(json!({ "email": "octo@example.com", "password": PASSWORD })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{login}");
+
+    let (status, change) = send(
+        &app,
+        Method::PUT,
+        "/users/password",
+        Some(&token),
+        Some(json!({ "currentPassword": PASSWORD, "newPassword": "N3w-password!" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{change}");
+
+    for body in [login, change] {
+        let message = body["message"].as_str().unwrap();
+        assert!(message.contains("sign-in provider"), "{message}");
+        assert!(!message.contains("Google"), "{message}");
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn failed_stats_inserts_roll_back_the_new_account(pool: PgPool) {
+    setup_db(&pool).await;
+    let config = fake_google(test_config(), "google-123", "newcomer@example.com", true).await;
+    let app = app_with_config(pool.clone(), config);
+    let member = signup(&app, "member@example.com").await;
+
+    sqlx::raw_sql(
+        r#"
+        CREATE FUNCTION fail_stats_insert() RETURNS trigger AS $$
+        BEGIN RAISE EXCEPTION 'injected failure'; END
+        $$ LANGUAGE plpgsql;
+        CREATE TRIGGER fail_stats_insert BEFORE INSERT ON user_stats
+            FOR EACH ROW EXECUTE FUNCTION fail_stats_insert();
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/auth/signup",
+        None,
+        Some(json!({
+            "fullName": "Test User",
+            "phoneNum": "+962791234567",
+            "email": "orphan@example.com",
+            "password": PASSWORD,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let response = get_raw(&app, "/auth/google/callback?code=abc&state=xyz", &[]).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let emails: Vec<String> = sqlx::query_scalar("SELECT email FROM users")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(emails, ["member@example.com"]);
+
+    // Accounts that lost their stats row get a fresh one instead of a 404
+    sqlx::raw_sql("DROP TRIGGER fail_stats_insert ON user_stats; DELETE FROM user_stats")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, profile) = send(&app, Method::GET, "/users/profile", Some(&member), None).await;
+    assert_eq!(status, StatusCode::OK, "{profile}");
+    assert_eq!(profile["stats"]["challengesTaken"], 0);
+}
+
+// Signs in through the fake Google and returns the one-time code from the redirect
+async fn google_sign_in_code(pool: &PgPool, email: &str) -> String {
+    let config = fake_google(test_config(), "google-123", email, true).await;
+    let app = app_with_config(pool.clone(), config);
+
+    let location = redirect_location(&app, "/auth/google/callback?code=abc&state=xyz").await;
+    let (base, code) = location.split_once("?code=").expect("code in redirect");
+    assert_eq!(base, "https://aiclub-uj.com/auth/callback");
+    code.to_string()
+}
+
+#[sqlx::test(migrations = false)]
+async fn google_sign_in_code_exchanges_for_a_token(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let code = google_sign_in_code(&pool, "newcomer@example.com").await;
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/auth/exchange",
+        None,
+        Some(json!({ "code": code })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["user"]["email"], "newcomer@example.com");
+    assert_eq!(body["needsProfileCompletion"], true);
+    assert!(body["expiresIn"].as_i64().unwrap() > 0);
+
+    let token = body["token"].as_str().unwrap();
+    let (status, body) = send(&app, Method::GET, "/users/profile", Some(token), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[sqlx::test(migrations = false)]
+async fn google_sign_in_codes_are_single_use_and_expire(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let exchange = |code: String| {
+        let app = app.clone();
+        async move {
+            send(
+                &app,
+                Method::POST,
+                "/auth/exchange",
+                None,
+                Some(json!({ "code": code })),
+            )
+            .await
+        }
+    };
+
+    let code = google_sign_in_code(&pool, "member@example.com").await;
+    let (status, body) = exchange(code.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let (status, body) = exchange(code).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+
+    let code = google_sign_in_code(&pool, "member@example.com").await;
+    sqlx::query("UPDATE oauth_exchange_codes SET expires_at = NOW() - INTERVAL '1 second'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, body) = exchange(code).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+
+    let (status, body) = exchange("not-a-code".to_string()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+}
+
+#[sqlx::test(migrations = false)]
+async fn resources_can_be_sorted(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+
+    // Inserted out of both date and title order
+    sqlx::query(
+        r#"
+        INSERT INTO resources (title, provider, instructor_name, created_at) VALUES
+            ('Beta', 'Provider', 'Instructor', NOW() - INTERVAL '2 days'),
+            ('alpha', 'Provider', 'Instructor', NOW() - INTERVAL '1 day'),
+            ('Gamma', 'Provider', 'Instructor', NOW() - INTERVAL '3 days')
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    for (uri, expected) in [
+        ("/resources", ["Beta", "alpha", "Gamma"]),
+        ("/resources?sort=newest", ["alpha", "Beta", "Gamma"]),
+        ("/resources?sort=oldest", ["Gamma", "Beta", "alpha"]),
+        ("/resources?sort=title", ["alpha", "Beta", "Gamma"]),
+    ] {
+        let (status, body) = send(&app, Method::GET, uri, None, None).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let titles: Vec<&str> = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|resource| resource["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, expected, "{uri}");
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn trailing_slashes_reach_the_same_route(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    sqlx::query("INSERT INTO resources (title, provider, instructor_name) VALUES ('Intro', 'Provider', 'Instructor')")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, plain) = send(&app, Method::GET, "/resources", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{plain}");
+    assert_eq!(plain.as_array().unwrap().len(), 1);
+    let (status, slashed) = send(&app, Method::GET, "/resources/", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{slashed}");
+    assert_eq!(slashed, plain);
+
+    let (status, body) = send(&app, Method::GET, "/resources/?sort=title", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body, plain);
+}
+
+#[sqlx::test(migrations = false)]
+async fn featured_resources_are_toggled_and_listed(pool: PgPool) {
+    setup_db(&pool).await;
+    let mut config = test_config();
+    config.featured_resources_limit = 2;
+    let app = app_with_config(pool.clone(), config);
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+
+    let ids: Vec<i32> = sqlx::query_scalar(
+        r#"
+        INSERT INTO resources (title, provider, instructor_name, visible) VALUES
+            ('One', 'Provider', 'Instructor', true),
+            ('Two', 'Provider', 'Instructor', true),
+            ('Three', 'Provider', 'Instructor', true),
+            ('Hidden', 'Provider', 'Instructor', false)
+        RETURNING id
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let feature = |id: i32, featured: bool, token: String| {
+        let app = app.clone();
+        async move {
+            send(
+                &app,
+                Method::PATCH,
+                &format!("/admin/resources/{id}/featured"),
+                Some(&token),
+                Some(json!({ "featured": featured })),
+            )
+            .await
+        }
+    };
+    let titles = |body: &Value| -> Vec<String> {
+        body.as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["title"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (status, _) = feature(ids[0], true, member.clone()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    for &id in &ids {
+        let (status, body) = feature(id, true, admin.clone()).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["item"]["featured"], true);
+    }
+
+    // Hidden resources stay out, and only the newest ones up to the limit are returned
+    let (status, body) = send(&app, Method::GET, "/resources/featured", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(titles(&body), ["Three", "Two"]);
+
+    let (status, body) = feature(ids[2], false, admin.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["featured"], false);
+    let (_, body) = send(&app, Method::GET, "/resources/featured", None, None).await;
+    assert_eq!(titles(&body), ["Two", "One"]);
+
+    let (status, _) = feature(9999, true, admin.clone()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(migrations = false)]
+async fn visibility_changes_respond_with_the_linked_quote(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    let quote = json!({ "text": "Stay curious", "author": "Dana" });
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&admin),
+        Some(json!({
+            "title": "Intro to ML",
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+            "quote": quote,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let id = body["item"]["id"].as_i64().unwrap();
+    let quote = body["item"]["quote"].clone();
+    assert_eq!(quote["text"], "Stay curious");
+
+    let (status, body) = send(
+        &app,
+        Method::PATCH,
+        &format!("/admin/resources/{id}/visibility"),
+        Some(&admin),
+        Some(json!({ "visible": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["visible"], false);
+    assert_eq!(body["item"]["quote"], quote);
+
+    let (status, body) = send(
+        &app,
+        Method::PATCH,
+        "/admin/resources/visibility",
+        Some(&admin),
+        Some(json!({ "ids": [id], "visible": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["items"][0]["visible"], true);
+    assert_eq!(body["items"][0]["quote"], quote);
+}
+
+#[sqlx::test(migrations = false)]
+async fn batch_visibility_skips_unknown_ids(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    let resource_ids: Vec<i32> = sqlx::query_scalar(
+        r#"
+        INSERT INTO resources (title, provider, instructor_name) VALUES
+            ('One', 'Provider', 'Instructor'),
+            ('Two', 'Provider', 'Instructor'),
+            ('Three', 'Provider', 'Instructor')
+        RETURNING id
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    let (status, body) = send(
+        &app,
+        Method::PATCH,
+        "/admin/resources/visibility",
+        Some(&admin),
+        Some(json!({ "ids": [resource_ids[2], 9999, resource_ids[0]], "visible": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["count"], 2);
+    let updated: Vec<(i64, bool)> = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| {
+            (
+                item["id"].as_i64().unwrap(),
+                item["visible"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        updated,
+        [
+            (resource_ids[0] as i64, false),
+            (resource_ids[2] as i64, false)
+        ]
+    );
+
+    let (_, body) = send(&app, Method::GET, "/resources", None, None).await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["title"], "Two");
+
+    let first = create_challenge(&app, &admin).await;
+    let second = create_week_challenge(&app, &admin, 2).await;
+    sqlx::query("UPDATE challenges SET deleted_at = NOW() WHERE id = $1")
+        .bind(second as i32)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Deleted challenges count as unknown too
+    let (status, body) = send(
+        &app,
+        Method::PATCH,
+        "/admin/challenges/visibility",
+        Some(&admin),
+        Some(json!({ "ids": [first, second, -1], "visible": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["items"][0]["id"], first);
+    assert_eq!(body["items"][0]["visible"], false);
+
+    let member = signup(&app, "member@example.com").await;
+    let (status, _) = send(
+        &app,
+        Method::PATCH,
+        "/admin/challenges/visibility",
+        Some(&member),
+        Some(json!({ "ids": [first], "visible": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(migrations = false)]
+async fn resource_views_count_each_member_once(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let alice = signup(&app, "alice@example.com").await;
+    let bob = signup(&app, "bob@example.com").await;
+
+    let (id,): (i32,) = sqlx::query_as(
+        "INSERT INTO resources (title, provider, instructor_name) VALUES ('One', 'Provider', 'Instructor') RETURNING id",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let uri = format!("/resources/{id}");
+    let view_count = || async {
+        let (status, body) = send(
+            &app,
+            Method::GET,
+            &format!("/admin/resources/{id}"),
+            Some(&admin),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        body["item"]["viewCount"].clone()
+    };
+
+    // Anonymous views aren't counted
+    let (status, _) = send(&app, Method::GET, &uri, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(view_count().await, 0);
+
+    for _ in 0..2 {
+        let (status, _) = send(&app, Method::GET, &uri, Some(&alice), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    assert_eq!(view_count().await, 1);
+
+    let (status, _) = send(&app, Method::GET, &uri, Some(&bob), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(view_count().await, 2);
+}
+
+#[sqlx::test(migrations = false)]
+async fn resource_detail_supports_conditional_get(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+
+    let (linked, unlinked): (i32, i32) = sqlx::query_as(
+        r#"
+        WITH inserted AS (
+            INSERT INTO resources (title, provider, instructor_name, quote_id) VALUES
+                ('Linked', 'Provider', 'Instructor', (SELECT MIN(id) FROM quotes WHERE visible)),
+                ('Unlinked', 'Provider', 'Instructor', NULL)
+            RETURNING id, quote_id
+        )
+        SELECT
+            (SELECT id FROM inserted WHERE quote_id IS NOT NULL),
+            (SELECT id FROM inserted WHERE quote_id IS NULL)
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let uri = format!("/resources/{linked}");
+
+    let response = get_raw(&app, &uri, &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()[header::ETAG]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let response = get_raw(&app, &uri, &[(header::IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[header::ETAG], etag.as_str());
+
+    // Any edit gives a new version
+    sqlx::query("UPDATE resources SET title = 'Renamed', updated_at = NOW() WHERE id = $1")
+        .bind(linked)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let response = get_raw(&app, &uri, &[(header::IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(response.headers()[header::ETAG], etag.as_str());
+
+    // Random fallback quotes aren't cacheable
+    let response = get_raw(&app, &format!("/resources/{unlinked}"), &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(header::ETAG).is_none());
+}
+
+#[sqlx::test(migrations = false)]
+async fn resource_detail_prefers_its_linked_quote(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&admin),
+        Some(json!({
+            "title": "Intro to ML",
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+            "quote": { "text": "Stay curious", "author": "Dana" },
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let uri = format!("/resources/{}", body["item"]["id"]);
+
+    for _ in 0..5 {
+        let (status, body) = send(&app, Method::GET, &uri, None, None).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(
+            body["quote"],
+            json!({ "text": "Stay curious", "author": "Dana" })
+        );
+    }
+
+    // A hidden linked quote falls back to one of the visible ones
+    sqlx::query("UPDATE quotes SET visible = false WHERE text = 'Stay curious'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, body) = send(&app, Method::GET, &uri, None, None).await;
+    assert!(body["quote"]["text"].is_string(), "{body}");
+    assert_ne!(body["quote"]["text"], "Stay curious");
+
+    sqlx::query("UPDATE quotes SET visible = false")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, body) = send(&app, Method::GET, &uri, None, None).await;
+    assert_eq!(body["quote"], Value::Null);
+}
+
+// The inline quote is written in the same transaction as the resource
+#[sqlx::test(migrations = false)]
+async fn failed_resource_writes_leave_no_inline_quote_behind(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let quote_count = || async {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM quotes")
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+    };
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&admin),
+        Some(json!({
+            "title": "Intro to ML",
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let uri = format!("/admin/resources/{}", body["item"]["id"]);
+    let before = quote_count().await;
+
+    sqlx::raw_sql(
+        r#"
+        CREATE FUNCTION fail_resource_write() RETURNS trigger AS $$
+        BEGIN RAISE EXCEPTION 'injected failure'; END
+        $$ LANGUAGE plpgsql;
+        CREATE TRIGGER fail_resource_write BEFORE INSERT OR UPDATE ON resources
+            FOR EACH ROW EXECUTE FUNCTION fail_resource_write();
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let quote = json!({ "text": "Stay curious", "author": "Dana" });
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&admin),
+        Some(json!({
+            "title": "Deep Learning",
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+            "quote": quote,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    let (status, _) = send(
+        &app,
+        Method::PUT,
+        &uri,
+        Some(&admin),
+        Some(json!({ "quote": quote })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    assert_eq!(quote_count().await, before);
+}
+
+#[sqlx::test(migrations = false)]
+async fn resource_instructor_title_and_bio_round_trip(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    let (status, body) = send_multipart(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        &admin,
+        &[
+            ("title", "Intro to ML"),
+            ("provider", "UJ AI Club"),
+            ("instructorName", "Dana"),
+            ("instructorTitle", "Senior ML Engineer @ X"),
+            ("instructorBio", "  Ten years of shipping models.  "),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    assert_eq!(
+        body["item"]["instructor"]["title"],
+        "Senior ML Engineer @ X"
+    );
+    let id = body["item"]["id"].as_i64().unwrap();
+
+    let (status, body) = send(&app, Method::GET, &format!("/resources/{id}"), None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["instructor"]["name"], "Dana");
+    assert_eq!(body["instructor"]["title"], "Senior ML Engineer @ X");
+    assert_eq!(body["instructor"]["bio"], "Ten years of shipping models.");
+
+    // Leaving the fields out keeps them; sending them empty clears them
+    let uri = format!("/admin/resources/{id}");
+    let (_, body) = send_multipart(&app, Method::PUT, &uri, &admin, &[("title", "ML 101")]).await;
+    assert_eq!(
+        body["item"]["instructor"]["bio"],
+        "Ten years of shipping models."
+    );
+
+    let (status, body) =
+        send_multipart(&app, Method::PUT, &uri, &admin, &[("instructorBio", "")]).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["instructor"]["bio"], Value::Null);
+    assert_eq!(
+        body["item"]["instructor"]["title"],
+        "Senior ML Engineer @ X"
+    );
+}
+
+// JSON and multipart bodies go through the same required-field checks
+#[sqlx::test(migrations = false)]
+async fn resource_writes_require_title_provider_and_instructor(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&admin),
+        Some(json!({ "title": "  ", "provider": "" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    let errors = body["errors"].as_object().unwrap();
+    for field in ["title", "provider", "instructorName"] {
+        assert!(errors.contains_key(field), "{field} missing from {body}");
+    }
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&admin),
+        Some(json!({
+            "title": "Intro to ML",
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let uri = format!("/admin/resources/{}", body["item"]["id"]);
+
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &uri,
+        Some(&admin),
+        Some(json!({ "title": " ", "instructor": { "name": "" } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["errors"]["title"], "This field is required");
+    assert_eq!(body["errors"]["instructorName"], "This field is required");
+    let (status, body) =
+        send_multipart(&app, Method::PUT, &uri, &admin, &[("provider", "  ")]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["errors"]["provider"], "This field is required");
+
+    let (_, body) = send(&app, Method::GET, &uri, Some(&admin), None).await;
+    assert_eq!(body["item"]["title"], "Intro to ML");
+    assert_eq!(body["item"]["provider"], "UJ AI Club");
+}
+
+#[sqlx::test(migrations = false)]
+async fn resource_forms_report_every_missing_field(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    let (status, body) = send_multipart(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        &admin,
+        &[("instructorName", "Dana"), ("notionUrl", "not a url")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+    let mut fields: Vec<&str> = body["errors"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    fields.sort_unstable();
+    assert_eq!(fields, ["notionUrl", "provider", "title"]);
+    assert_eq!(body["errors"]["title"], "This field is required");
+    assert_eq!(body["errors"]["provider"], "This field is required");
+
+    let (resources,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM resources")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(resources, 0);
+}
+
+// Creates a resource from a JSON body and returns its id
+async fn create_tagged_resource(
+    app: &Router,
+    admin: &str,
+    title: &str,
+    tags: &[&str],
+    visible: bool,
+) -> i64 {
+    let (status, body) = send(
+        app,
+        Method::POST,
+        "/admin/resources",
+        Some(admin),
+        Some(json!({
+            "title": title,
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+            "tags": tags,
+            "visible": visible,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    body["item"]["id"].as_i64().unwrap()
+}
+
+fn resource_titles(body: &Value) -> Vec<&str> {
+    body.as_array()
+        .unwrap()
+        .iter()
+        .map(|resource| resource["title"].as_str().unwrap())
+        .collect()
+}
+
+#[sqlx::test(migrations = false)]
+async fn resources_filter_by_tag_ignoring_case(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    create_tagged_resource(
+        &app,
+        &admin,
+        "Transformers",
+        &["NLP", "Deep Learning"],
+        true,
+    )
+    .await;
+    create_tagged_resource(&app, &admin, "CNNs", &["Vision", "Deep Learning"], true).await;
+    create_tagged_resource(&app, &admin, "Draft", &["NLP"], false).await;
+    let deleted = create_tagged_resource(&app, &admin, "Old", &["NLP"], true).await;
+    let (status, body) = send(
+        &app,
+        Method::DELETE,
+        &format!("/admin/resources/{deleted}"),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert!(status.is_success(), "{body}");
+
+    let list = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let (status, body) = send(&app, Method::GET, uri, None, None).await;
+            assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+            body
+        }
+    };
+
+    // Hidden and deleted resources stay out of the filtered list too
+    assert_eq!(
+        resource_titles(&list("/resources?tag=nlp").await),
+        ["Transformers"]
+    );
+    assert_eq!(
+        resource_titles(&list("/resources?tag=%20deep%20learning%20&sort=title").await),
+        ["CNNs", "Transformers"]
+    );
+    assert!(resource_titles(&list("/resources?tag=NL").await).is_empty());
+    assert!(resource_titles(&list("/resources?tag=Robotics").await).is_empty());
+
+    // A blank tag is no filter at all
+    assert_eq!(resource_titles(&list("/resources?tag=%20").await).len(), 2);
+}
+
+#[sqlx::test(migrations = false)]
+async fn members_bookmark_visible_resources(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+    let other = signup(&app, "other@example.com").await;
+    let first = create_tagged_resource(&app, &admin, "First", &[], true).await;
+    let second = create_tagged_resource(&app, &admin, "Second", &[], true).await;
+    let hidden = create_tagged_resource(&app, &admin, "Hidden", &[], false).await;
+
+    let bookmark = |method: Method, id: i64, token: Option<&str>| {
+        let (app, token) = (app.clone(), token.map(str::to_string));
+        async move {
+            let uri = format!("/resources/{id}/bookmark");
+            send(&app, method, &uri, token.as_deref(), None).await
+        }
+    };
+    let bookmarks = |token: &str| {
+        let (app, token) = (app.clone(), token.to_string());
+        async move {
+            let (status, body) =
+                send(&app, Method::GET, "/users/bookmarks", Some(&token), None).await;
+            assert_eq!(status, StatusCode::OK, "{body}");
+            resource_titles(&body)
+                .into_iter()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        }
+    };
+
+    let (status, _) = bookmark(Method::POST, first, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = bookmark(Method::POST, hidden, Some(&member)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = bookmark(Method::POST, 999_999, Some(&member)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Newest first; bookmarking again is a no-op that keeps the original place
+    for id in [first, second, first] {
+        let (status, body) = bookmark(Method::POST, id, Some(&member)).await;
+        assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+    }
+    assert_eq!(bookmarks(&member).await, ["Second", "First"]);
+    assert!(bookmarks(&other).await.is_empty());
+
+    // Hiding a resource keeps the bookmark but leaves it off the list until it's back
+    let hide = |visible: bool| {
+        let (app, admin) = (app.clone(), admin.clone());
+        async move {
+            let uri = format!("/admin/resources/{first}");
+            let body = json!({ "visible": visible });
+            let (status, body) = send(&app, Method::PUT, &uri, Some(&admin), Some(body)).await;
+            assert_eq!(status, StatusCode::OK, "{body}");
+        }
+    };
+    hide(false).await;
+    assert_eq!(bookmarks(&member).await, ["Second"]);
+    hide(true).await;
+    assert_eq!(bookmarks(&member).await, ["Second", "First"]);
+
+    // Removing is idempotent too
+    for _ in 0..2 {
+        let (status, body) = bookmark(Method::DELETE, second, Some(&member)).await;
+        assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+    }
+    assert_eq!(bookmarks(&member).await, ["First"]);
+}
+
+#[sqlx::test(migrations = false)]
+async fn resource_notion_url_must_be_http(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let create = |notion_url: &'static str| {
+        let app = app.clone();
+        let admin = admin.clone();
+        async move {
+            send_multipart(
+                &app,
+                Method::POST,
+                "/admin/resources",
+                &admin,
+                &[
+                    ("title", "Intro to ML"),
+                    ("provider", "UJ AI Club"),
+                    ("instructorName", "Dana"),
+                    ("notionUrl", notion_url),
+                ],
+            )
+            .await
+        }
+    };
+
+    let (status, body) = create("  https://www.Notion.so/intro-to-ml  ").await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    assert_eq!(
+        body["item"]["notionUrl"],
+        "https://www.notion.so/intro-to-ml"
+    );
+    let id = body["item"]["id"].as_i64().unwrap();
+
+    for bad in [
+        "htpp://www.notion.so/page",
+        "javascript:alert(1)",
+        "notion page",
+    ] {
+        let (status, body) = create(bad).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{bad}: {body}");
+        assert!(body["errors"]["notionUrl"].is_string(), "{bad}: {body}");
+    }
+
+    let (status, body) = create("   ").await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    assert_eq!(body["item"]["notionUrl"], Value::Null);
+
+    // Updates are checked the same way, and an empty value removes the link
+    let uri = format!("/admin/resources/{id}");
+    let (status, body) = send_multipart(
+        &app,
+        Method::PUT,
+        &uri,
+        &admin,
+        &[("notionUrl", "ftp://notion.so")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    let (status, body) =
+        send_multipart(&app, Method::PUT, &uri, &admin, &[("notionUrl", "")]).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["notionUrl"], Value::Null);
+}
+
+#[sqlx::test(migrations = false)]
+async fn created_items_record_their_creator(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let (admin_id,): (uuid::Uuid,) =
+        sqlx::query_as("SELECT id FROM users WHERE email = 'admin@example.com'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    let (status, body) = send_multipart(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        &admin,
+        &[
+            ("title", "Intro to ML"),
+            ("provider", "UJ AI Club"),
+            ("instructorName", "Dana"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    assert_eq!(body["item"]["createdBy"], admin_id.to_string());
+
+    let challenge_id = create_challenge(&app, &admin).await;
+    let (_, body) = send(
+        &app,
+        Method::GET,
+        &format!("/admin/challenges/{challenge_id}"),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(body["item"]["createdBy"], admin_id.to_string());
+
+    // Rows from before the column existed have no creator
+    sqlx::query(
+        "INSERT INTO resources (title, provider, instructor_name) VALUES ('Old', 'P', 'I')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let (_, body) = send(&app, Method::GET, "/admin/resources", Some(&admin), None).await;
+    let old = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["title"] == "Old")
+        .unwrap();
+    assert_eq!(old["createdBy"], Value::Null);
+}
+
+async fn user_id(pool: &PgPool, email: &str) -> uuid::Uuid {
+    sqlx::query_scalar("SELECT id FROM users WHERE email = $1")
+        .bind(email)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[sqlx::test(migrations = false)]
+async fn admins_list_and_search_users(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    for (email, name) in [
+        ("alice@example.com", "Alice Smith"),
+        ("bob@club.org", "Bob Jones"),
+        ("carol@example.com", "Carol 100% Real"),
+    ] {
+        signup(&app, email).await;
+        sqlx::query("UPDATE users SET full_name = $1 WHERE email = $2")
+            .bind(name)
+            .bind(email)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let list = |uri: &str| {
+        let (app, admin, uri) = (app.clone(), admin.clone(), uri.to_string());
+        async move {
+            let (status, body) = send(&app, Method::GET, &uri, Some(&admin), None).await;
+            assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+            let emails: Vec<String> = body["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|user| user["email"].as_str().unwrap().to_string())
+                .collect();
+            (body, emails)
+        }
+    };
+
+    let (body, emails) = list("/admin/users?sort=email").await;
+    assert_eq!(body["total"], 4);
+    assert_eq!(
+        emails,
+        [
+            "admin@example.com",
+            "alice@example.com",
+            "bob@club.org",
+            "carol@example.com",
+        ]
+    );
+    let alice = &body["items"][1];
+    assert_eq!(alice["fullName"], "Alice Smith");
+    assert_eq!(alice["role"], "user");
+    assert!(alice.get("passwordHash").is_none() && alice.get("password_hash").is_none());
+
+    // Search matches names and emails, ignoring case
+    let (body, emails) = list("/admin/users?q=SMITH").await;
+    assert_eq!(
+        (body["total"].as_i64(), emails),
+        (Some(1), vec!["alice@example.com".into()])
+    );
+    let (_, emails) = list("/admin/users?q=club.org").await;
+    assert_eq!(emails, ["bob@club.org"]);
+    let (_, emails) = list("/admin/users?q=%20%20").await;
+    assert_eq!(emails.len(), 4);
+
+    // Wildcards in the term are matched literally
+    let (_, emails) = list("/admin/users?q=100%25").await;
+    assert_eq!(emails, ["carol@example.com"]);
+    let (_, emails) = list("/admin/users?q=_").await;
+    assert!(emails.is_empty(), "{emails:?}");
+
+    // Pages count the whole match, not just the page; "Test User" sorts after the others
+    let (body, emails) = list("/admin/users?q=example.com&sort=name&page=2&pageSize=2").await;
+    assert_eq!(body["total"], 3);
+    assert_eq!(body["page"], 2);
+    assert_eq!(emails, ["admin@example.com"]);
+}
+
+// Moderators manage content; points, roles and grading stay with admins
+#[sqlx::test(migrations = false)]
+async fn moderators_reach_content_routes_but_not_admin_only_ones(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let moderator = signup(&app, "moderator@example.com").await;
+    set_role(&pool, "moderator@example.com", "moderator").await;
+    let member = signup(&app, "member@example.com").await;
+    let member_id = user_id(&pool, "member@example.com").await;
+
+    let challenge_id = create_challenge(&app, &moderator).await;
+    let submission_id = submit(&app, &member, challenge_id).await;
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&moderator),
+        Some(json!({
+            "title": "Intro",
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+
+    let admin_only = [
+        (Method::GET, "/admin/users".to_string(), None),
+        (
+            Method::PATCH,
+            format!("/admin/users/{member_id}"),
+            Some(json!({ "points": 100 })),
+        ),
+        (
+            Method::GET,
+            format!("/admin/challenges/{challenge_id}/submissions"),
+            None,
+        ),
+        (
+            Method::POST,
+            format!("/admin/challenges/{challenge_id}/submissions/{submission_id}/score"),
+            Some(json!({ "score": 50 })),
+        ),
+    ];
+    for (method, uri, body) in &admin_only {
+        let (status, response) =
+            send(&app, method.clone(), uri, Some(&moderator), body.clone()).await;
+        assert_eq!(
+            status,
+            StatusCode::UNAUTHORIZED,
+            "{method} {uri}: {response}"
+        );
+    }
+    for (method, uri, body) in admin_only {
+        let (status, response) = send(&app, method.clone(), &uri, Some(&admin), body).await;
+        assert_eq!(status, StatusCode::OK, "{method} {uri}: {response}");
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn admins_change_roles_and_points(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    signup(&app, "member@example.com").await;
+    let admin_uri = format!("/admin/users/{}", user_id(&pool, "admin@example.com").await);
+    let member_uri = format!(
+        "/admin/users/{}",
+        user_id(&pool, "member@example.com").await
+    );
+
+    let (status, body) = send(
+        &app,
+        Method::PATCH,
+        &member_uri,
+        Some(&admin),
+        Some(json!({ "role": "superuser" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    // Extra points move the member above the admin
+    let (status, body) = send(
+        &app,
+        Method::PATCH,
+        &member_uri,
+        Some(&admin),
+        Some(json!({ "points": 30 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["points"], 30);
+    assert_eq!(body["item"]["rank"], 1);
+
+    // The only admin can't step down
+    let (status, body) = send(
+        &app,
+        Method::PATCH,
+        &admin_uri,
+        Some(&admin),
+        Some(json!({ "role": "user" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    let (status, body) = send(
+        &app,
+        Method::PATCH,
+        &member_uri,
+        Some(&admin),
+        Some(json!({ "role": "admin" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["role"], "admin");
+
+    // With a second admin in place it can
+    let (status, body) = send(
+        &app,
+        Method::PATCH,
+        &admin_uri,
+        Some(&admin),
+        Some(json!({ "role": "user" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["role"], "user");
+}
+
+// Demoting the other admin locks their row first, deleting your own account locks yours
+// first; both then need the other's, which used to deadlock
+#[sqlx::test(migrations = false)]
+async fn concurrent_demotion_and_deletion_keep_an_admin(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "first@example.com").await;
+    signup(&app, "second@example.com").await;
+    set_role(&pool, "first@example.com", "admin").await;
+    set_role(&pool, "second@example.com", "admin").await;
+    let second_uri = format!(
+        "/admin/users/{}",
+        user_id(&pool, "second@example.com").await
+    );
+
+    let (demoted, deleted) = tokio::join!(
+        send(
+            &app,
+            Method::PATCH,
+            &second_uri,
+            Some(&admin),
+            Some(json!({ "role": "user" })),
+        ),
+        send(
+            &app,
+            Method::DELETE,
+            "/users/profile",
+            Some(&admin),
+            Some(json!({ "password": PASSWORD })),
+        ),
+    );
+
+    // Exactly one goes through; the other is refused rather than failing with a deadlock
+    let succeeded = [
+        demoted.0 == StatusCode::OK,
+        deleted.0 == StatusCode::NO_CONTENT,
+    ];
+    assert_eq!(
+        succeeded.iter().filter(|ok| **ok).count(),
+        1,
+        "{demoted:?} / {deleted:?}"
+    );
+    for (status, _) in [&demoted, &deleted] {
+        assert!(!status.is_server_error(), "{demoted:?} / {deleted:?}");
+    }
+    let (admins,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE role = 'admin'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(admins, 1);
+}
+
+#[sqlx::test(migrations = false)]
+async fn bootstrap_promotes_the_first_admin_only_once(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+
+    // Disabled unless configured
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/admin/bootstrap",
+        None,
+        Some(json!({ "token": "" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let mut config = test_config();
+    config.admin_bootstrap = Some(AdminBootstrapConfig {
+        email: "founder@example.com".to_string(),
+        token: "bootstrap-secret".to_string(),
+    });
+    let app = app_with_config(pool.clone(), config);
+    let founder = signup(&app, "Founder@Example.com").await;
+    let bootstrap = |token: &'static str| {
+        let app = app.clone();
+        async move {
+            send(
+                &app,
+                Method::POST,
+                "/admin/bootstrap",
+                None,
+                Some(json!({ "token": token })),
+            )
+            .await
+        }
+    };
+
+    let (status, _) = bootstrap("wrong-secret").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = bootstrap("bootstrap-secret").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["promoted"], true);
+
+    let (status, body) = send(&app, Method::GET, "/admin/users", Some(&founder), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    // Once any admin exists it's a no-op, even if the founder has since been demoted
+    set_role(&pool, "founder@example.com", "user").await;
+    signup(&app, "other@example.com").await;
+    set_role(&pool, "other@example.com", "admin").await;
+    let (status, body) = bootstrap("bootstrap-secret").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["promoted"], false);
+
+    let (role,): (String,) =
+        sqlx::query_as("SELECT role FROM users WHERE email = 'founder@example.com'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(role, "user");
+}
+
+#[sqlx::test(migrations = false)]
+async fn points_history_explains_the_points_total(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+
+    let challenge_id = create_challenge(&app, &admin).await;
+    let submission_id = submit(&app, &member, challenge_id).await;
+    score(&app, &admin, challenge_id, &submission_id, 40).await;
+    // Rescoring records only the difference
+    score(&app, &admin, challenge_id, &submission_id, 70).await;
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/users/points/history",
+        Some(&member),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["total"], 2);
+    let entries = body["items"].as_array().unwrap();
+    assert_eq!(entries[0]["delta"], 30);
+    assert_eq!(entries[1]["delta"], 40);
+    for entry in entries {
+        assert_eq!(entry["reason"], "challenge_score");
+        assert_eq!(entry["challengeId"], challenge_id);
+        assert_eq!(entry["challengeTitle"], "Week 1");
+    }
+
+    let ledger_sum: i64 = entries.iter().map(|e| e["delta"].as_i64().unwrap()).sum();
+    let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&member), None).await;
+    assert_eq!(profile["points"].as_i64().unwrap(), ledger_sum);
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/users/points/history?page=2&pageSize=1",
+        Some(&member),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["items"].as_array().unwrap().len(), 1);
+    assert_eq!(body["items"][0]["delta"], 40);
+}
+
+#[sqlx::test(migrations = false)]
+async fn submission_urls_must_match_the_allowed_domains(pool: PgPool) {
+    setup_db(&pool).await;
+    let mut config = test_config();
+    config.submission_allowed_domains = vec!["github.com".to_string(), "kaggle.com".to_string()];
+    let app = app_with_config(pool.clone(), config);
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+    let challenge_id = create_challenge(&app, &admin).await;
+    let uri = format!("/challenges/{challenge_id}/submissions");
+
+    for url in [
+        "https://github.com/member/week1",
+        "https://GIST.GitHub.com/member/abc",
+        "http://www.kaggle.com/code/member/notebook",
+    ] {
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            &uri,
+            Some(&member),
+            Some(json!({ "submissionUrl": url })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{url} should be accepted: {body}");
+    }
+
+    for url in [
+        "https://evil.example/github.com",
+        "https://github.com.evil.example/member",
+        "https://notgithub.com/member",
+        "ftp://github.com/member",
+        "not a url",
+    ] {
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            &uri,
+            Some(&member),
+            Some(json!({ "submissionUrl": url })),
+        )
+        .await;
+        assert_eq!(
+            status,
+            StatusCode::BAD_REQUEST,
+            "{url} should be rejected: {body}"
+        );
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn rescoring_applies_only_the_score_delta(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+
+    let challenge_id = create_challenge(&app, &admin).await;
+    let submission_id = submit(&app, &member, challenge_id).await;
+    for points in [10, 25, 5] {
+        score(&app, &admin, challenge_id, &submission_id, points).await;
+        let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&member), None).await;
+        assert_eq!(profile["points"], points);
+    }
+
+    let deltas: Vec<i32> = sqlx::query_scalar(
+        "SELECT delta FROM points_history ph JOIN users u ON u.id = ph.user_id
+         WHERE u.email = 'member@example.com' ORDER BY ph.id",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(deltas, [10, 15, -20]);
+
+    // Out-of-range scores are form errors, not an overflowing points column
+    for points in [-1, 10_001, i32::MAX] {
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            &format!("/admin/challenges/{challenge_id}/submissions/{submission_id}/score"),
+            Some(&admin),
+            Some(json!({ "score": points })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+    }
+    let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&member), None).await;
+    assert_eq!(profile["points"], 5);
+
+    // Rescoring is not another challenge taken
+    let taken: i32 = sqlx::query_scalar(
+        "SELECT s.challenges_taken FROM user_stats s JOIN users u ON u.id = s.user_id
+         WHERE u.email = 'member@example.com'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(taken, 1);
+}
+
+#[sqlx::test(migrations = false)]
+async fn submission_urls_longer_than_the_column_are_rejected(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+    let challenge_id = create_challenge(&app, &admin).await;
+
+    let url = format!("https://github.com/{}", "a".repeat(500));
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        &format!("/challenges/{challenge_id}/submissions"),
+        Some(&member),
+        Some(json!({ "submissionUrl": url })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+}
+
+#[sqlx::test(migrations = false)]
+async fn ranks_follow_points_with_ties_going_to_the_earlier_account(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let first = signup(&app, "first@example.com").await;
+    let second = signup(&app, "second@example.com").await;
+    let third = signup(&app, "third@example.com").await;
+
+    let challenge_id = create_challenge(&app, &admin).await;
+    for (token, points) in [(&first, 10), (&second, 50), (&third, 50)] {
+        let submission_id = submit(&app, token, challenge_id).await;
+        score(&app, &admin, challenge_id, &submission_id, points).await;
+    }
+
+    let ranks: Vec<(String, i32)> = sqlx::query_as("SELECT email, rank FROM users ORDER BY rank")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    let ranks: Vec<(&str, i32)> = ranks.iter().map(|(e, r)| (e.as_str(), *r)).collect();
+    assert_eq!(
+        ranks,
+        [
+            ("second@example.com", 1),
+            ("third@example.com", 2),
+            ("first@example.com", 3),
+            ("admin@example.com", 4),
+        ]
+    );
+
+    let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&third), None).await;
+    assert_eq!(profile["rank"], 2);
+
+    // Deleting the leader closes the gap
+    let (status, body) = send(
+        &app,
+        Method::DELETE,
+        "/users/profile",
+        Some(&second),
+        Some(json!({ "password": PASSWORD })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+
+    let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&third), None).await;
+    assert_eq!(profile["rank"], 1);
+    let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&first), None).await;
+    assert_eq!(profile["rank"], 2);
+}
+
+#[sqlx::test(migrations = false)]
+async fn data_exports_cover_every_section_without_internal_fields(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+
+    let challenge_id = create_challenge(&app, &admin).await;
+    let submission_id = submit(&app, &member, challenge_id).await;
+    score(&app, &admin, challenge_id, &submission_id, 10).await;
+    let body = json!({ "name": "Member", "email": "Member@Example.com", "message": "Hi" });
+    let (status, body) = send(&app, Method::POST, "/contact", None, Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    let (status, export) = send(&app, Method::GET, "/users/export", Some(&member), None).await;
+    assert_eq!(status, StatusCode::OK, "{export}");
+
+    let mut sections: Vec<&str> = export
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    sections.sort_unstable();
+    assert_eq!(
+        sections,
+        [
+            "contactMessages",
+            "exportedAt",
+            "pointsHistory",
+            "profile",
+            "stats",
+            "submissions",
+        ]
+    );
+    assert_eq!(export["profile"]["email"], "member@example.com");
+    assert_eq!(export["stats"]["challengesTaken"], 1);
+    assert_eq!(export["submissions"][0]["score"], 10);
+    assert_eq!(export["contactMessages"][0]["message"], "Hi");
+    assert_eq!(export["pointsHistory"][0]["delta"], 10);
+
+    // Nothing internal leaks: no hashes, no database ids, not even the user's own
+    let id = user_id(&pool, "member@example.com").await.to_string();
+    fn check(value: &Value, id: &str) {
+        match value {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    let key = key.to_lowercase();
+                    assert!(!key.contains("password"), "{key}");
+                    assert!(key != "id" && !key.ends_with("id"), "{key}");
+                    check(value, id);
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| check(item, id)),
+            Value::String(text) => assert!(!text.contains(id), "{text}"),
+            _ => {}
+        }
+    }
+    check(&export, &id);
+}
+
+#[sqlx::test(migrations = false)]
+async fn bounded_leaderboards_skip_points_from_earlier_periods(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    signup(&app, "veteran@example.com").await;
+    let newcomer = signup(&app, "newcomer@example.com").await;
+
+    // The veteran's points were all awarded well before this week and month began
+    let veteran = user_id(&pool, "veteran@example.com").await;
+    sqlx::query(
+        "INSERT INTO points_history (user_id, delta, reason, created_at)
+         VALUES ($1, 100, 'admin_adjustment', NOW() - INTERVAL '40 days')",
+    )
+    .bind(veteran)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE users SET points = 100, full_name = 'Veteran' WHERE id = $1")
+        .bind(veteran)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    sqlx::query("UPDATE users SET full_name = 'Newcomer' WHERE email = 'newcomer@example.com'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let challenge_id = create_challenge(&app, &admin).await;
+    let submission_id = submit(&app, &newcomer, challenge_id).await;
+    score(&app, &admin, challenge_id, &submission_id, 10).await;
+
+    let board = |body: &Value| -> Vec<(String, i64)> {
+        body[0]["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| {
+                (
+                    entry["name"].as_str().unwrap().to_string(),
+                    entry["points"].as_i64().unwrap(),
+                )
+            })
+            .collect()
+    };
+
+    for period in ["weekly", "monthly"] {
+        let (status, body) = send(
+            &app,
+            Method::GET,
+            &format!("/leaderboards?period={period}"),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let entries = board(&body);
+        assert_eq!(entries[0], ("Newcomer".to_string(), 10), "{period}");
+        assert!(
+            entries.contains(&("Veteran".to_string(), 0)),
+            "{period}: {entries:?}"
+        );
+    }
+
+    let (_, body) = send(&app, Method::GET, "/leaderboards", None, None).await;
+    assert_eq!(board(&body)[0], ("Veteran".to_string(), 100));
+}
+
+#[sqlx::test(migrations = false)]
+async fn leaderboards_come_from_the_leaderboards_table(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+    sqlx::query("INSERT INTO leaderboards (title) VALUES ('Hackathon')")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, body) = send(&app, Method::GET, "/leaderboards", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let boards = body.as_array().unwrap();
+    assert_eq!(boards.len(), 2);
+    assert_eq!(boards[0]["title"], "Top Users");
+    assert_eq!(boards[1]["title"], "Hackathon");
+    assert_ne!(boards[0]["id"], boards[1]["id"]);
+    assert_eq!(boards[0]["entries"], boards[1]["entries"]);
+    assert_eq!(boards[1]["entries"][0]["name"], "Test User");
+
+    // Every period and the live feed use the same titles
+    let (_, weekly) = send(&app, Method::GET, "/leaderboards?period=weekly", None, None).await;
+    assert_eq!(weekly[0]["title"], "Top Users");
+    assert_eq!(weekly[1]["title"], "Hackathon");
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(axum::serve(listener, app.clone()).into_future());
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws/leaderboard"))
+        .await
+        .unwrap();
+    let message = socket.next().await.unwrap().unwrap();
+    let board: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    assert_eq!(board["id"], boards[0]["id"]);
+    assert_eq!(board["title"], "Top Users");
+}
+
+#[sqlx::test(migrations = false)]
+async fn leaderboards_filter_by_university_and_major(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let mut tokens = Vec::new();
+    for (email, name, university, major, points) in [
+        (
+            "alice@example.com",
+            "Alice",
+            "University of Jordan",
+            "AI",
+            30,
+        ),
+        ("bob@example.com", "Bob", "University of Jordan", "CS", 20),
+        ("carol@example.com", "Carol", "PSUT", "AI", 40),
+    ] {
+        tokens.push(signup(&app, email).await);
+        sqlx::query(
+            "UPDATE users SET full_name = $1, university = $2, major = $3, points = $4 WHERE email = $5",
+        )
+        .bind(name)
+        .bind(university)
+        .bind(major)
+        .bind(points)
+        .bind(email)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    let names = |body: &Value| -> Vec<String> {
+        body[0]["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Matches are exact but ignore case
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/leaderboards?university=university%20of%20jordan",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(names(&body), ["Alice", "Bob"]);
+
+    let (_, body) = send(
+        &app,
+        Method::GET,
+        "/leaderboards?university=Jordan",
+        None,
+        None,
+    )
+    .await;
+    assert!(names(&body).is_empty());
+
+    let (_, body) = send(
+        &app,
+        Method::GET,
+        "/leaderboards?university=University%20of%20Jordan&major=ai",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(names(&body), ["Alice"]);
+
+    // Positions are within the filtered board, and users outside it have none
+    let uri = "/leaderboards?major=AI";
+    let (_, body) = send(&app, Method::GET, uri, Some(&tokens[0]), None).await;
+    assert_eq!(names(&body), ["Carol", "Alice"]);
+    assert_eq!(body[0]["currentUser"]["position"], 2);
+    let (_, body) = send(&app, Method::GET, uri, Some(&tokens[1]), None).await;
+    assert_eq!(body[0]["currentUser"], Value::Null);
+
+    let (_, body) = send(&app, Method::GET, "/leaderboards", None, None).await;
+    assert_eq!(names(&body), ["Carol", "Alice", "Bob"]);
+
+    let uri = format!("/leaderboards?major={}", "a".repeat(256));
+    let (status, body) = send(&app, Method::GET, &uri, None, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+}
+
+#[sqlx::test(migrations = false)]
+async fn leaderboards_show_a_caller_outside_the_top_ten(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let member = signup(&app, "member@example.com").await;
+    sqlx::query("UPDATE users SET points = 50 WHERE email = 'member@example.com'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    // 41 users ahead of the member and 20 behind
+    sqlx::query(
+        r#"
+        INSERT INTO users (id, email, full_name, points)
+        SELECT gen_random_uuid(), 'user' || n || '@example.com', 'User ' || n,
+               CASE WHEN n <= 41 THEN 100 + n ELSE 10 END
+        FROM generate_series(1, 61) n
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (status, body) = send(&app, Method::GET, "/leaderboards", Some(&member), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body[0]["entries"].as_array().unwrap().len(), 10);
+    assert_eq!(body[0]["currentUser"]["position"], 42);
+    assert_eq!(body[0]["currentUser"]["points"], 50);
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/v2/challenges/leaderboard",
+        Some(&member),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["entries"].as_array().unwrap().len(), 10);
+    assert_eq!(body["entries"][0]["points"], 141);
+    assert_eq!(body["currentUser"]["position"], 42);
+    assert_eq!(body["currentUser"]["name"], "Test User");
+
+    // The original endpoint keeps its bare array
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/challenges/leaderboard",
+        Some(&member),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body.as_array().unwrap().len(), 10);
+
+    let (_, body) = send(&app, Method::GET, "/leaderboards", None, None).await;
+    assert_eq!(body[0]["currentUser"], Value::Null);
+
+    // A bad token is an error, not an anonymous request
+    let (status, body) = send(&app, Method::GET, "/leaderboards", Some("not-a-jwt"), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+    assert_eq!(body["code"], "TOKEN_INVALID");
+}
+
+#[sqlx::test(migrations = false)]
+async fn leaderboard_is_cached_until_points_change(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+    let challenge_id = create_challenge(&app, &admin).await;
+    let submission_id = submit(&app, &member, challenge_id).await;
+
+    let top_points = |body: &Value| body[0]["entries"][0]["points"].clone();
+
+    let (status, body) = send(&app, Method::GET, "/leaderboards", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(top_points(&body), 0);
+
+    // Writes behind the API's back stay invisible while the cached board is fresh
+    sqlx::query("UPDATE users SET points = 5 WHERE email = 'member@example.com'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, body) = send(&app, Method::GET, "/leaderboards", None, None).await;
+    assert_eq!(top_points(&body), 0);
+
+    score(&app, &admin, challenge_id, &submission_id, 10).await;
+
+    let (_, body) = send(&app, Method::GET, "/leaderboards", None, None).await;
+    assert_eq!(top_points(&body), 15);
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/challenges/leaderboard",
+        Some(&member),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body[0]["points"], 15);
+}
+
+#[sqlx::test(migrations = false)]
+async fn users_list_only_their_own_submissions(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let alice = signup(&app, "alice@example.com").await;
+    let bob = signup(&app, "bob@example.com").await;
+
+    let challenge_id = create_challenge(&app, &admin).await;
+    let alice_submission = submit(&app, &alice, challenge_id).await;
+    let bob_submission = submit(&app, &bob, challenge_id).await;
+    score(&app, &admin, challenge_id, &alice_submission, 25).await;
+
+    let (status, body) = send(&app, Method::GET, "/users/submissions", Some(&alice), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["total"], 1);
+    let item = &body["items"][0];
+    assert_eq!(item["id"], alice_submission);
+    assert_eq!(item["challengeTitle"], "Week 1");
+    assert_eq!(item["score"], 25);
+    assert_eq!(item["graded"], true);
+
+    let (_, body) = send(&app, Method::GET, "/users/submissions", Some(&bob), None).await;
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["items"][0]["id"], bob_submission);
+    assert_eq!(body["items"][0]["graded"], false);
+    assert!(body["items"][0]["score"].is_null());
+
+    let (status, body) = send(&app, Method::GET, "/users/submissions", Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["total"], 0);
+    assert_eq!(body["items"], json!([]));
+}
+
+#[sqlx::test(migrations = false)]
+async fn admin_filters_challenge_submissions_by_grading(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let alice = signup(&app, "alice@example.com").await;
+    let bob = signup(&app, "bob@example.com").await;
+
+    let challenge_id = create_challenge(&app, &admin).await;
+    let uri = format!("/admin/challenges/{challenge_id}/submissions");
+
+    let (status, body) = send(&app, Method::GET, &uri, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["total"], 0);
+    assert_eq!(body["items"], json!([]));
+
+    let graded = submit(&app, &alice, challenge_id).await;
+    let ungraded = submit(&app, &bob, challenge_id).await;
+    score(&app, &admin, challenge_id, &graded, 50).await;
+
+    let (_, body) = send(&app, Method::GET, &uri, Some(&admin), None).await;
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["items"][0]["id"], graded);
+    assert_eq!(body["items"][0]["userEmail"], "alice@example.com");
+
+    let (_, body) = send(
+        &app,
+        Method::GET,
+        &format!("{uri}?status=graded"),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["items"][0]["id"], graded);
+    assert_eq!(body["items"][0]["score"], 50);
+
+    let (_, body) = send(
+        &app,
+        Method::GET,
+        &format!("{uri}?status=ungraded"),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["items"][0]["id"], ungraded);
+    assert_eq!(body["items"][0]["userName"], "Test User");
+
+    let (status, _) = send(&app, Method::GET, &uri, Some(&alice), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(migrations = false)]
+async fn challenge_weeks_are_unique_among_live_challenges(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    let week_one = create_challenge(&app, &admin).await;
+    let duplicate = json!({
+        "title": "Again",
+        "description": "Clash",
+        "week": 1,
+        "startDate": null,
+        "endDate": null,
+    });
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/challenges",
+        Some(&admin),
+        Some(duplicate.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(
+        body["message"],
+        format!("Week 1 is already used by challenge {week_one}")
+    );
+
+    // Updates may keep their own week but not take another challenge's
+    let week_two = create_week_challenge(&app, &admin, 2).await;
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &format!("/admin/challenges/{week_two}"),
+        Some(&admin),
+        Some(json!({ "week": 1, "startDate": null, "endDate": null })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &format!("/admin/challenges/{week_two}"),
+        Some(&admin),
+        Some(json!({ "week": 2, "title": "Renamed", "startDate": null, "endDate": null })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    // A deleted challenge frees its week, and can't be restored while it's taken again
+    let (status, _) = send(
+        &app,
+        Method::DELETE,
+        &format!("/admin/challenges/{week_one}"),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/challenges",
+        Some(&admin),
+        Some(duplicate),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        &format!("/admin/challenges/{week_one}/restore"),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+}
+
+// A writer that skips the app's check still can't add a second live challenge for a week
+#[sqlx::test(migrations = false)]
+async fn the_database_rejects_duplicate_live_weeks(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    // An uncommitted row the app's check can't see yet, so its insert waits on the index
+    let mut other = pool.begin().await.unwrap();
+    sqlx::query(
+        "INSERT INTO challenges (title, description, challenge_url, week) VALUES ('Other', '', '', 5)",
+    )
+    .execute(&mut *other)
+    .await
+    .unwrap();
+    let create = tokio::spawn({
+        let app = app.clone();
+        async move {
+            send(
+                &app,
+                Method::POST,
+                "/admin/challenges",
+                Some(&admin),
+                Some(json!({ "title": "Week 5", "description": "Clash", "week": 5 })),
+            )
+            .await
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    other.commit().await.unwrap();
+
+    let (status, body) = create.await.unwrap();
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(
+        body["message"],
+        "That week is already used by another challenge"
+    );
+
+    // Deleted challenges don't hold on to their week
+    sqlx::query("UPDATE challenges SET deleted_at = NOW() WHERE week = 5")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO challenges (title, description, challenge_url, week) VALUES ('Again', '', '', 5)")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+#[sqlx::test(migrations = false)]
+async fn migration_renumbers_duplicate_live_weeks(pool: PgPool) {
+    setup_db(&pool).await;
+    sqlx::raw_sql(
+        r#"
+        DROP INDEX challenges_live_week_key;
+        INSERT INTO challenges (title, description, challenge_url, week, is_current, created_at, deleted_at) VALUES
+            ('Oldest', '', '', 1, false, NOW() - INTERVAL '3 days', NULL),
+            ('Pinned', '', '', 1, true, NOW() - INTERVAL '2 days', NULL),
+            ('Newest', '', '', 1, false, NOW() - INTERVAL '1 day', NULL),
+            ('Alone', '', '', 4, false, NOW(), NULL),
+            ('Deleted', '', '', 4, false, NOW(), NOW());
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    sqlx::raw_sql(include_str!(
+        "../migrations/update_22_unique_live_weeks.sql"
+    ))
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let weeks: Vec<(String, i32)> =
+        sqlx::query_as("SELECT title, week FROM challenges ORDER BY title")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        weeks,
+        [
+            ("Alone".to_string(), 4),
+            ("Deleted".to_string(), 4),
+            ("Newest".to_string(), 6),
+            ("Oldest".to_string(), 5),
+            ("Pinned".to_string(), 1),
+        ]
+    );
+}
+
+#[sqlx::test(migrations = false)]
+async fn resources_can_be_managed_with_json_bodies(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&admin),
+        Some(json!({
+            "title": "Intro to ML",
+            "provider": "UJ AI Club",
+            "coverImage": "https://cdn.example.com/cover.png",
+            "notionUrl": "https://www.notion.so/intro",
+            "instructor": { "name": "Dana", "title": "ML Engineer" },
+            "quote": { "text": "Stay curious", "author": "Dana" },
+            "tags": ["NLP", "nlp", " Vision "],
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let item = &body["item"];
+    assert_eq!(item["instructor"]["title"], "ML Engineer");
+    assert_eq!(item["quote"]["text"], "Stay curious");
+    assert_eq!(item["tags"].as_array().unwrap().len(), 2);
+    let id = item["id"].as_i64().unwrap();
+
+    let uri = format!("/admin/resources/{id}");
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &uri,
+        Some(&admin),
+        Some(json!({ "title": "ML 101", "quoteId": null })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["title"], "ML 101");
+    assert_eq!(body["item"]["provider"], "UJ AI Club");
+    assert_eq!(body["item"]["instructor"]["title"], "ML Engineer");
+    assert_eq!(body["item"]["quote"], Value::Null);
+
+    // JSON bodies get the same validation as form submissions
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &uri,
+        Some(&admin),
+        Some(json!({ "notionUrl": "javascript:alert(1)" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+}
+
+#[sqlx::test(migrations = false)]
+async fn deleted_resources_disappear_until_restored(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&admin),
+        Some(json!({
+            "title": "Intro to ML",
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let id = body["item"]["id"].as_i64().unwrap();
+    let uri = format!("/admin/resources/{id}");
+
+    let ids = |body: &Value, key: Option<&str>| -> Vec<i64> {
+        let items = match key {
+            Some(key) => &body[key],
+            None => body,
+        };
+        items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_i64().unwrap())
+            .collect()
+    };
+
+    let (status, body) = send(&app, Method::DELETE, &uri, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    // Already deleted
+    let (status, _) = send(&app, Method::DELETE, &uri, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, body) = send(&app, Method::GET, "/resources", None, None).await;
+    assert!(ids(&body, None).is_empty());
+    let (status, _) = send(&app, Method::GET, &format!("/resources/{id}"), None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, body) = send(&app, Method::GET, "/admin/resources", Some(&admin), None).await;
+    assert!(ids(&body, Some("items")).is_empty());
+    let (_, body) = send(
+        &app,
+        Method::GET,
+        "/admin/resources?includeDeleted=true",
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(ids(&body, Some("items")), [id]);
+    assert!(body["items"][0]["deletedAt"].is_string());
+
+    let restore = format!("{uri}/restore");
+    let (status, body) = send(&app, Method::POST, &restore, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["deletedAt"], Value::Null);
+    // Only deleted resources can be restored
+    let (status, _) = send(&app, Method::POST, &restore, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, body) = send(&app, Method::GET, "/resources", None, None).await;
+    assert_eq!(ids(&body, None), [id]);
+}
+
+#[sqlx::test(migrations = false)]
+async fn deleted_challenges_keep_their_submissions(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+    let challenge_id = create_challenge(&app, &admin).await;
+    submit(&app, &member, challenge_id).await;
+    let uri = format!("/admin/challenges/{challenge_id}");
+
+    let (status, body) = send(&app, Method::DELETE, &uri, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let (_, body) = send(&app, Method::GET, "/admin/challenges", Some(&admin), None).await;
+    assert_eq!(body["total"], 0);
+    let (_, body) = send(
+        &app,
+        Method::GET,
+        "/admin/challenges?includeDeleted=true",
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(body["total"], 1);
+
+    let (submissions,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM challenge_submissions")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(submissions, 1);
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        &format!("{uri}/restore"),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let (_, body) = send(&app, Method::GET, "/admin/challenges", Some(&admin), None).await;
+    assert_eq!(body["total"], 1);
+}
+
+#[sqlx::test(migrations = false)]
+async fn resource_images_can_be_kept_replaced_and_cleared(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool.clone(), config);
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let stored =
+        |url: &Value| uploads_dir.join(url.as_str().unwrap().trim_start_matches("/uploads/"));
+
+    let png = png_bytes();
+    let (status, body) = send_multipart_files(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        &admin,
+        &[
+            ("title", "Intro to ML"),
+            ("provider", "UJ AI Club"),
+            ("instructorName", "Dana"),
+        ],
+        &[
+            ("coverImage", "cover.png", &png),
+            ("instructorImage", "dana.png", &png),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let cover = body["item"]["coverImage"].clone();
+    let instructor_image = body["item"]["instructor"]["image"].clone();
+    assert!(stored(&cover).exists());
+    assert!(stored(&instructor_image).exists());
+    let uri = format!("/admin/resources/{}", body["item"]["id"]);
+
+    // Leaving the fields out keeps both images
+    let (status, body) =
+        send_multipart(&app, Method::PUT, &uri, &admin, &[("title", "ML 101")]).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["coverImage"], cover);
+    assert!(body["item"]["thumbnailUrl"].is_string());
+    assert!(stored(&cover).exists());
+
+    // Uploading a new cover replaces the old file
+    let (status, body) = send_multipart_files(
+        &app,
+        Method::PUT,
+        &uri,
+        &admin,
+        &[],
+        &[("coverImage", "new-cover.png", &png)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(!stored(&cover).exists());
+    let cover = body["item"]["coverImage"].clone();
+    assert!(stored(&cover).exists());
+
+    // An empty field clears just that image and deletes its file
+    let (status, body) =
+        send_multipart(&app, Method::PUT, &uri, &admin, &[("coverImage", "")]).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["coverImage"], Value::Null);
+    assert_eq!(body["item"]["thumbnailUrl"], Value::Null);
+    assert_eq!(body["item"]["instructor"]["image"], instructor_image);
+    assert!(!stored(&cover).exists());
+    assert!(stored(&instructor_image).exists());
+
+    let (status, body) =
+        send_multipart(&app, Method::PUT, &uri, &admin, &[("instructorImage", "")]).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["instructor"]["image"], Value::Null);
+    assert!(!stored(&instructor_image).exists());
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn resources_cannot_take_over_other_uploads(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool.clone(), config);
+    let moderator = signup(&app, "moderator@example.com").await;
+    set_role(&pool, "moderator@example.com", "moderator").await;
+    let member = signup(&app, "member@example.com").await;
+
+    let files: &[(&str, &str, &[u8])] = &[("avatar", "me.png", &png_bytes())];
+    let (status, body) =
+        send_multipart_files(&app, Method::POST, "/users/avatar", &member, &[], files).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let avatar = body["imageUrl"].as_str().unwrap().to_string();
+    let stored = uploads_dir.join(avatar.trim_start_matches("/uploads/"));
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&moderator),
+        Some(json!({
+            "title": "Intro",
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+            "coverImage": avatar,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+
+    // Outside images are still fine to link
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        Some(&moderator),
+        Some(json!({
+            "title": "Intro",
+            "provider": "UJ AI Club",
+            "instructor": { "name": "Dana" },
+            "coverImage": "https://example.com/cover.png",
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let uri = format!("/admin/resources/{}", body["item"]["id"]);
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &uri,
+        Some(&moderator),
+        Some(json!({ "instructor": { "name": "Dana", "image": avatar } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    // A resource already showing someone's avatar can drop it without deleting it
+    sqlx::query("UPDATE resources SET cover_image = $1")
+        .bind(&avatar)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &uri,
+        Some(&moderator),
+        Some(json!({ "coverImage": null })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(stored.exists(), "the member's avatar must be kept");
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+// The stored extension comes from the decoded format, never from the client's file name
+#[sqlx::test(migrations = false)]
+async fn uploads_are_stored_under_their_decoded_format(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool.clone(), config);
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let png = png_bytes();
+
+    let files: &[(&str, &str, &[u8])] = &[("avatar", "x.html", &png)];
+    let (status, body) =
+        send_multipart_files(&app, Method::POST, "/users/avatar", &admin, &[], files).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(
+        body["imageUrl"].as_str().unwrap().ends_with("_x.png"),
+        "{body}"
+    );
+    assert!(
+        body["thumbnailUrl"].as_str().unwrap().ends_with("_x.png"),
+        "{body}"
+    );
+
+    let files: &[(&str, &str, &[u8])] = &[("instructorImage", "dana.svg", &png)];
+    let (status, body) = send_multipart_files(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        &admin,
+        &[
+            ("title", "Intro to ML"),
+            ("provider", "UJ AI Club"),
+            ("instructorName", "Dana"),
+        ],
+        files,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let instructor_image = body["item"]["instructor"]["image"].as_str().unwrap();
+    assert!(instructor_image.ends_with("_dana.png"), "{body}");
+
+    // Instructor images are decoded too, not stored on trust
+    let files: &[(&str, &str, &[u8])] =
+        &[("instructorImage", "dana.png", b"<script>alert(1)</script>")];
+    let (status, body) = send_multipart_files(
+        &app,
+        Method::POST,
+        "/admin/resources",
+        &admin,
+        &[
+            ("title", "Intro to ML"),
+            ("provider", "UJ AI Club"),
+            ("instructorName", "Dana"),
+        ],
+        files,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(stored_files(&uploads_dir).len(), 3);
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn avatar_uploads_report_the_image_dimensions(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool, config);
+    let token = signup(&app, "member@example.com").await;
+
+    let png = png_bytes();
+    let (status, body) = send_multipart_files(
+        &app,
+        Method::POST,
+        "/users/avatar",
+        &token,
+        &[],
+        &[("avatar", "me.png", &png)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(body["imageUrl"].is_string());
+    assert_eq!(body["width"], 4);
+    assert_eq!(body["height"], 4);
+    assert_eq!(body["sizeBytes"], png.len());
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn large_uploads_get_a_scaled_down_thumbnail(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool, config);
+    let token = signup(&app, "member@example.com").await;
+
+    let mut png = Vec::new();
+    image::RgbImage::new(1600, 900)
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    let (status, body) = send_multipart_files(
+        &app,
+        Method::POST,
+        "/users/avatar",
+        &token,
+        &[],
+        &[("avatar", "large.png", &png)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["width"], 1600);
+    assert_eq!(body["height"], 900);
+
+    // 200 pixels wide, keeping the 16:9 aspect ratio
+    let stored =
+        |url: &Value| uploads_dir.join(url.as_str().unwrap().trim_start_matches("/uploads/"));
+    let thumbnail = image::open(stored(&body["thumbnailUrl"])).unwrap();
+    assert_eq!((thumbnail.width(), thumbnail.height()), (200, 112));
+    let original = image::open(stored(&body["imageUrl"])).unwrap();
+    assert_eq!((original.width(), original.height()), (1600, 900));
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn uploads_use_the_configured_directory(pool: PgPool) {
+    setup_db(&pool).await;
+    // Relative to the working directory, like UPLOADS_DIR=some/dir
+    let uploads_dir = PathBuf::from(format!("target/uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool, config);
+    let token = signup(&app, "member@example.com").await;
+
+    let (status, body) = send_multipart_files(
+        &app,
+        Method::POST,
+        "/users/avatar",
+        &token,
+        &[],
+        &[("avatar", "me.png", &png_bytes())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let url = body["imageUrl"].as_str().unwrap();
+    assert!(
+        uploads_dir
+            .join(url.trim_start_matches("/uploads/"))
+            .exists()
+    );
+
+    // The same directory is the one served back
+    let response = get_raw(&app, url, &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+// Every file under an uploads directory, as paths relative to it
+fn stored_files(uploads_dir: &std::path::Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut dirs = vec![uploads_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir).into_iter().flatten() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                let relative = path.strip_prefix(uploads_dir).unwrap();
+                files.push(relative.to_string_lossy().into_owned());
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[sqlx::test(migrations = false)]
+async fn concurrent_avatar_uploads_keep_only_the_final_files(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool, config);
+    let token = signup(&app, "member@example.com").await;
+    let png = png_bytes();
+    let files: &[(&str, &str, &[u8])] = &[("avatar", "me.png", &png)];
+
+    let uploads = (0..6)
+        .map(|_| send_multipart_files(&app, Method::POST, "/users/avatar", &token, &[], files));
+    for (status, body) in futures_util::future::join_all(uploads).await {
+        assert_eq!(status, StatusCode::OK, "{body}");
+    }
+
+    // Each replaced avatar was cleaned up, whatever order the uploads committed in
+    let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&token), None).await;
+    let key = profile["image"]
+        .as_str()
+        .unwrap()
+        .trim_start_matches("/uploads/")
+        .to_string();
+    let files = stored_files(&uploads_dir);
+    assert_eq!(files.len(), 2, "{files:?}");
+    assert!(files.contains(&key), "{files:?}");
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn failed_avatar_updates_remove_the_new_files(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool.clone(), config);
+    let token = signup(&app, "member@example.com").await;
+    let png = png_bytes();
+    let files: &[(&str, &str, &[u8])] = &[("avatar", "me.png", &png)];
+
+    sqlx::raw_sql(
+        r#"
+        CREATE FUNCTION fail_avatar_update() RETURNS trigger AS $$
+        BEGIN RAISE EXCEPTION 'injected failure'; END
+        $$ LANGUAGE plpgsql;
+        CREATE TRIGGER fail_avatar_update BEFORE UPDATE OF image ON users
+            FOR EACH ROW EXECUTE FUNCTION fail_avatar_update();
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (status, _) =
+        send_multipart_files(&app, Method::POST, "/users/avatar", &token, &[], files).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    // Both the image and its thumbnail were written, then removed again
+    assert_eq!(stored_files(&uploads_dir), Vec::<String>::new());
+    let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&token), None).await;
+    assert_eq!(profile["image"], Value::Null);
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn replacing_an_avatar_keeps_files_still_in_use(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool.clone(), config);
+    let token = signup(&app, "member@example.com").await;
+    signup(&app, "other@example.com").await;
+    let png = png_bytes();
+    let files: &[(&str, &str, &[u8])] = &[("avatar", "me.png", &png)];
+    let upload = || send_multipart_files(&app, Method::POST, "/users/avatar", &token, &[], files);
+
+    let (status, body) = upload().await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let first = body["imageUrl"].as_str().unwrap().to_string();
+    sqlx::query("UPDATE users SET image = $1 WHERE email = 'other@example.com'")
+        .bind(&first)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, body) = upload().await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(
+        uploads_dir
+            .join(first.trim_start_matches("/uploads/"))
+            .exists()
+    );
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn deleting_an_account_checks_the_password_and_shared_avatars(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool.clone(), config);
+    let png = png_bytes();
+    let files: &[(&str, &str, &[u8])] = &[("avatar", "me.png", &png)];
+
+    let mut avatars = Vec::new();
+    for email in ["shared@example.com", "own@example.com"] {
+        let token = signup(&app, email).await;
+        let (status, body) =
+            send_multipart_files(&app, Method::POST, "/users/avatar", &token, &[], files).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let url = body["imageUrl"].as_str().unwrap().to_string();
+        avatars.push((
+            token,
+            uploads_dir.join(url.trim_start_matches("/uploads/")),
+            url,
+        ));
+    }
+    // Someone else shows the first account's avatar too
+    signup(&app, "other@example.com").await;
+    sqlx::query("UPDATE users SET image = $1 WHERE email = 'other@example.com'")
+        .bind(&avatars[0].2)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, body) = send(
+        &app,
+        Method::DELETE,
+        "/users/profile",
+        Some(&avatars[0].0),
+        Some(json!({ "password": "Wr0ng-password" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+
+    for (token, _, _) in &avatars {
+        let (status, body) = send(
+            &app,
+            Method::DELETE,
+            "/users/profile",
+            Some(token),
+            Some(json!({ "password": PASSWORD })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+    }
+    assert!(avatars[0].1.exists(), "a shared avatar must be kept");
+    assert!(!avatars[1].1.exists(), "an unused avatar is removed");
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn deleting_a_hosted_avatar_removes_its_files(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool, config);
+    let token = signup(&app, "member@example.com").await;
+    let stored =
+        |url: &Value| uploads_dir.join(url.as_str().unwrap().trim_start_matches("/uploads/"));
+
+    let (status, body) = send_multipart_files(
+        &app,
+        Method::POST,
+        "/users/avatar",
+        &token,
+        &[],
+        &[("avatar", "me.png", &png_bytes())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let (image, thumbnail) = (body["imageUrl"].clone(), body["thumbnailUrl"].clone());
+    assert!(stored(&image).exists());
+    assert!(stored(&thumbnail).exists());
+
+    let (status, body) = send(&app, Method::DELETE, "/users/avatar", Some(&token), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+    assert!(!stored(&image).exists());
+    assert!(!stored(&thumbnail).exists());
+
+    let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&token), None).await;
+    assert_eq!(profile["image"], Value::Null);
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn deleting_an_external_avatar_only_unlinks_it(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let token = signup(&app, "member@example.com").await;
+    sqlx::query("UPDATE users SET image = $1 WHERE email = $2")
+        .bind("https://lh3.googleusercontent.com/a/photo")
+        .bind("member@example.com")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, body) = send(&app, Method::DELETE, "/users/avatar", Some(&token), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+
+    let (_, profile) = send(&app, Method::GET, "/users/profile", Some(&token), None).await;
+    assert_eq!(profile["image"], Value::Null);
+}
+
+#[sqlx::test(migrations = false)]
+async fn members_cannot_claim_or_delete_each_others_uploads(pool: PgPool) {
+    setup_db(&pool).await;
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_with_config(pool.clone(), config);
+    let owner = signup(&app, "owner@example.com").await;
+    let other = signup(&app, "other@example.com").await;
+
+    let (status, body) = send_multipart_files(
+        &app,
+        Method::POST,
+        "/users/avatar",
+        &owner,
+        &[],
+        &[("avatar", "me.png", &png_bytes())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let image = body["imageUrl"].as_str().unwrap().to_string();
+    let owner_id = user_id(&pool, "owner@example.com").await;
+    assert!(
+        image.starts_with(&format!("/uploads/avatars/{owner_id}/")),
+        "{image}"
+    );
+    let stored = uploads_dir.join(image.trim_start_matches("/uploads/"));
+
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        "/users/profile",
+        Some(&other),
+        Some(json!({ "image": image })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+    // Resending your own avatar with the rest of the profile is fine
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        "/users/profile",
+        Some(&owner),
+        Some(json!({ "fullName": "Owner", "image": image })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    // Even if the URL got onto another profile, only its uploader's actions delete it
+    sqlx::query("UPDATE users SET image = $1 WHERE email = 'other@example.com'")
+        .bind(&image)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, body) = send(&app, Method::DELETE, "/users/avatar", Some(&other), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+    assert!(stored.exists(), "another member's upload must be kept");
+
+    let (status, body) = send(&app, Method::DELETE, "/users/avatar", Some(&owner), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+    assert!(!stored.exists());
+
+    std::fs::remove_dir_all(&uploads_dir).ok();
+}
+
+#[sqlx::test(migrations = false)]
+async fn admin_creates_answer_201_with_location(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    for (uri, body) in [
+        (
+            "/admin/resources",
+            json!({
+                "title": "Intro to ML",
+                "provider": "UJ AI Club",
+                "instructor": { "name": "Dana" },
+            }),
+        ),
+        (
+            "/admin/challenges",
+            json!({
+                "title": "Week 1",
+                "description": "Warm up",
+                "startDate": null,
+                "endDate": null,
+            }),
+        ),
+    ] {
+        let response = send_raw(&app, Method::POST, uri, Some(&admin), Some(body)).await;
+        assert_eq!(response.status(), StatusCode::CREATED, "{uri}");
+        let location = response.headers()[header::LOCATION]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let (status, body) = send(&app, Method::GET, &location, Some(&admin), None).await;
+        assert_eq!(status, StatusCode::OK, "{location}: {body}");
+        assert_eq!(location, format!("{uri}/{}", body["item"]["id"]));
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn contact_messages_are_trimmed_and_bounded(pool: PgPool) {
+    setup_db(&pool).await;
+    let mut config = test_config();
+    config.contact_limits.max_name_len = 10;
+    config.contact_limits.max_message_len = 50;
+    config.contact_rate_limit = 100;
+    let app = app_with_config(pool.clone(), config);
+
+    let contact = |name: &str, email: &str, message: &str| {
+        let app = app.clone();
+        let body = json!({ "name": name, "email": email, "message": message });
+        async move { send(&app, Method::POST, "/contact", None, Some(body)).await }
+    };
+
+    let (status, body) = contact("  Dana  ", "dana@example.com", "  Hello there \n").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let (name, message): (String, String) =
+        sqlx::query_as("SELECT name, message FROM contact_messages")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!((name.as_str(), message.as_str()), ("Dana", "Hello there"));
+
+    for (name, email, message) in [
+        ("   ", "dana@example.com", "Hello"),
+        ("Dana", "dana@example.com", " \n "),
+        ("Dana", "not-an-email", "Hello"),
+        ("Dana Danielsson", "dana@example.com", "Hello"),
+        ("Dana", "dana@example.com", &"x".repeat(51)),
+    ] {
+        let (status, body) = contact(name, email, message).await;
+        assert_eq!(
+            status,
+            StatusCode::BAD_REQUEST,
+            "{name:?}/{email:?}: {body}"
+        );
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+    }
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM contact_messages")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[sqlx::test(migrations = false)]
+async fn admins_read_and_handle_contact_messages(pool: PgPool) {
+    setup_db(&pool).await;
+    let mut config = test_config();
+    config.contact_rate_limit = 100;
+    let app = app_with_config(pool.clone(), config);
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let member = signup(&app, "member@example.com").await;
+
+    for message in ["First", "Second", "Third"] {
+        let body = json!({ "name": "Dana", "email": "dana@example.com", "message": message });
+        let (status, body) = send(&app, Method::POST, "/contact", None, Some(body)).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+    }
+
+    let (status, _) = send(&app, Method::GET, "/admin/contact", Some(&member), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Newest first
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/admin/contact?page=1&pageSize=2",
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["total"], 3);
+    assert_eq!(body["items"][0]["message"], "Third");
+    assert_eq!(body["items"][1]["message"], "Second");
+    assert_eq!(body["items"][0]["handled"], false);
+    let (_, body) = send(
+        &app,
+        Method::GET,
+        "/admin/contact?page=2&pageSize=2",
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(body["items"][0]["message"], "First");
+    let first_id = body["items"][0]["id"].as_str().unwrap().to_string();
+
+    // Pages past the end are empty rather than an overflow
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        &format!("/admin/contact?page={}&pageSize=100", i64::MAX),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["items"], json!([]));
+
+    let uri = format!("/admin/contact/{first_id}");
+    for handled in [true, false] {
+        let (status, body) = send(
+            &app,
+            Method::PATCH,
+            &uri,
+            Some(&admin),
+            Some(json!({ "handled": handled })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["item"]["handled"], handled);
+        let (stored,): (bool,) =
+            sqlx::query_as("SELECT handled FROM contact_messages WHERE message = 'First'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(stored, handled);
+    }
+
+    let uri = format!("/admin/contact/{}", uuid::Uuid::new_v4());
+    let (status, _) = send(
+        &app,
+        Method::PATCH,
+        &uri,
+        Some(&admin),
+        Some(json!({ "handled": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(migrations = false)]
+async fn contact_messages_notify_the_admin_address(pool: PgPool) {
+    setup_db(&pool).await;
+    let mailer = Arc::new(RecordingMailer::default());
+    let mut config = test_config();
+    config.mailer = Some(mailer.clone());
+    config.admin_notification_email = Some("admins@example.com".to_string());
+    let app = app_with_config(pool, config);
+
+    let body = json!({ "name": "Dana", "email": "dana@example.com", "message": "Hello" });
+    let (status, body) = send(&app, Method::POST, "/contact", None, Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    let sent = mailer.wait_for(1).await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to, "admins@example.com");
+    assert_eq!(sent[0].subject, "New contact message from Dana");
+    assert!(sent[0].body.contains("dana@example.com"));
+    assert!(sent[0].body.contains("Hello"));
+}
+
+#[sqlx::test(migrations = false)]
+async fn contact_messages_are_kept_when_the_email_fails(pool: PgPool) {
+    setup_db(&pool).await;
+    let mailer = Arc::new(RecordingMailer {
+        failing: true,
+        ..Default::default()
+    });
+    let mut config = test_config();
+    config.mailer = Some(mailer.clone());
+    config.admin_notification_email = Some("admins@example.com".to_string());
+    let app = app_with_config(pool.clone(), config);
+
+    let body = json!({ "name": "Dana", "email": "dana@example.com", "message": "Hello" });
+    let (status, body) = send(&app, Method::POST, "/contact", None, Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(mailer.wait_for(1).await.len(), 1);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM contact_messages")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[sqlx::test(migrations = false)]
+async fn email_availability_reports_taken_addresses(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+
+    for (email, available) in [
+        ("member@example.com", false),
+        ("%20Member%40Example.COM", false),
+        ("someone@example.com", true),
+    ] {
+        let (status, body) = send(
+            &app,
+            Method::GET,
+            &format!("/auth/email-available?email={email}"),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{email}: {body}");
+        assert_eq!(body, json!({ "available": available }), "{email}");
+    }
+
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/auth/email-available?email=member",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+}
+
+#[sqlx::test(migrations = false)]
+async fn contact_form_drops_bots_and_limits_senders(pool: PgPool) {
+    setup_db(&pool).await;
+    let mut config = test_config();
+    config.contact_honeypot_field = Some("website".to_string());
+    config.contact_rate_limit = 3;
+    let app = app_with_config(pool.clone(), config);
+    let stored = || async {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM contact_messages")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        count
+    };
+
+    let message = |website: &str| {
+        json!({
+            "name": "Dana",
+            "email": "dana@example.com",
+            "message": "Hello",
+            "website": website,
+        })
+    };
+
+    // A filled honeypot looks like success but nothing is stored
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/contact",
+        None,
+        Some(message("http://spam.example")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["success"], true);
+    assert_eq!(stored().await, 0);
+
+    let (status, body) = send(&app, Method::POST, "/contact", None, Some(message(""))).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(stored().await, 1);
+
+    let (status, _) = send(&app, Method::POST, "/contact", None, Some(message(""))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send(&app, Method::POST, "/contact", None, Some(message(""))).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{body}");
+    assert_eq!(stored().await, 2);
+}
+
+#[sqlx::test(migrations = false)]
+async fn quotes_are_served_at_random_and_per_day(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+
+    let (status, first) = send(&app, Method::GET, "/quotes/daily", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{first}");
+    assert!(first["text"].is_string());
+    let (_, second) = send(&app, Method::GET, "/quotes/daily", None, None).await;
+    assert_eq!(first, second);
+
+    // Only visible quotes are picked
+    sqlx::query("UPDATE quotes SET visible = (id = (SELECT MIN(id) FROM quotes))")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (text,): (String,) = sqlx::query_as("SELECT text FROM quotes WHERE visible = true")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    for _ in 0..5 {
+        let (status, body) = send(&app, Method::GET, "/quotes/random", None, None).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["text"], text);
+    }
+
+    sqlx::query("UPDATE quotes SET visible = false")
+        .execute(&pool)
+        .await
+        .unwrap();
+    for uri in ["/quotes/random", "/quotes/daily"] {
+        let (status, body) = send(&app, Method::GET, uri, None, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT, "{uri}");
+        assert_eq!(body, Value::Null);
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn current_challenge_has_a_public_teaser(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+
+    let (status, _) = send(&app, Method::GET, "/challenges/current/public", None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let challenge_id = create_challenge(&app, &admin).await;
+
+    let (status, body) = send(&app, Method::GET, "/challenges/current/public", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["id"], challenge_id);
+    assert_eq!(body["title"], "Week 1");
+    assert!(body.get("challengeUrl").is_none());
+
+    // The full challenge, link included, still needs a token
+    let (status, _) = send(&app, Method::GET, "/challenges/current", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = send(&app, Method::GET, "/challenges/current", Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(body["challengeUrl"].is_string());
+}
+
+#[sqlx::test(migrations = false)]
+async fn concurrent_set_current_calls_leave_one_current_challenge(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let first = create_week_challenge(&app, &admin, 1).await;
+    let second = create_week_challenge(&app, &admin, 2).await;
+
+    let uris: Vec<String> = (0..10)
+        .map(|i| {
+            let id = if i % 2 == 0 { first } else { second };
+            format!("/admin/challenges/{id}/set-current")
+        })
+        .collect();
+    let results = futures_util::future::join_all(
+        uris.iter()
+            .map(|uri| send(&app, Method::POST, uri, Some(&admin), None)),
+    )
+    .await;
+    for (status, body) in results {
+        assert_eq!(status, StatusCode::OK, "{body}");
+    }
+
+    let current: Vec<(i32,)> = sqlx::query_as("SELECT id FROM challenges WHERE is_current")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(current.len(), 1);
+
+    let (status, body) = send(&app, Method::GET, "/challenges/current", Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["id"], current[0].0);
+}
+
+#[sqlx::test(migrations = false)]
+async fn set_current_moves_the_pin_to_the_new_challenge(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let first = create_week_challenge(&app, &admin, 1).await;
+    let second = create_week_challenge(&app, &admin, 2).await;
+
+    // Pinning wins over the newer challenge whose window also covers now
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        &format!("/admin/challenges/{first}/set-current"),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["isCurrent"], true);
+    let (_, body) = send(&app, Method::GET, "/challenges/current", Some(&admin), None).await;
+    assert_eq!(body["id"], first);
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        &format!("/admin/challenges/{second}/set-current"),
+        Some(&admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["isCurrent"], true);
+
+    let (status, body) = send(&app, Method::GET, "/admin/challenges", Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let pinned: Vec<_> = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| (item["id"].as_i64().unwrap(), item["isCurrent"] == true))
+        .collect();
+    assert_eq!(pinned, [(second, true), (first, false)]);
+
+    let (_, body) = send(&app, Method::GET, "/challenges/current", Some(&admin), None).await;
+    assert_eq!(body["id"], second);
+}
+
+#[sqlx::test(migrations = false)]
+async fn challenge_windows_must_start_before_they_end(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    for end in ["2025-01-01", "2024-12-31"] {
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/admin/challenges",
+            Some(&admin),
+            Some(json!({
+                "title": "Week 1",
+                "description": "Warm up",
+                "startDate": "2025-01-01",
+                "endDate": end,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+    }
+
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/admin/challenges",
+        Some(&admin),
+        Some(json!({
+            "title": "Week 1",
+            "description": "Warm up",
+            "startDate": "2025-01-01",
+            "endDate": "2025-01-07",
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let uri = format!("/admin/challenges/{}", body["item"]["id"]);
+
+    // Only one side changes, but the merged window would be inverted
+    for patch in [
+        json!({ "startDate": "2025-01-08" }),
+        json!({ "endDate": "2024-12-31" }),
+    ] {
+        let (status, body) = send(&app, Method::PUT, &uri, Some(&admin), Some(patch)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+    }
+
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &uri,
+        Some(&admin),
+        Some(json!({ "endDate": "2025-01-14" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["startDate"], "2025-01-01T00:00:00Z");
+    assert_eq!(body["item"]["endDate"], "2025-01-14T00:00:00Z");
+}
+
+#[sqlx::test(migrations = false)]
+async fn admin_challenges_filter_by_week_and_active_day(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+
+    for (week, start, end) in [
+        (1, json!("2025-01-01"), json!("2025-01-07")),
+        (2, json!("2025-01-08"), json!("2025-01-14")),
+        (3, Value::Null, Value::Null),
+    ] {
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/admin/challenges",
+            Some(&admin),
+            Some(json!({
+                "title": format!("Week {week}"),
+                "description": "Warm up",
+                "week": week,
+                "startDate": start,
+                "endDate": end,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED, "{body}");
+    }
+
+    let weeks = |body: &Value| -> Vec<String> {
+        body["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["title"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let list = |query: &str| {
+        let uri = format!("/admin/challenges?{query}");
+        let app = app.clone();
+        let admin = admin.clone();
+        async move { send(&app, Method::GET, &uri, Some(&admin), None).await }
+    };
+
+    let (status, body) = list("week=2").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(weeks(&body), ["Week 2"]);
+
+    // Newest week first. The last day of a window still counts, and challenges without
+    // dates are always active
+    let (_, body) = list("activeOn=2025-01-07").await;
+    assert_eq!(weeks(&body), ["Week 3", "Week 1"]);
+    let (_, body) = list("activeOn=2025-01-08").await;
+    assert_eq!(weeks(&body), ["Week 3", "Week 2"]);
+    let (_, body) = list("activeOn=2025-01-08&week=3").await;
+    assert_eq!(weeks(&body), ["Week 3"]);
+
+    let (_, body) = list("activeOn=2025-01-08&pageSize=1&page=2").await;
+    assert_eq!(body["total"], 2);
+    assert_eq!(weeks(&body), ["Week 2"]);
+
+    let (status, body) = list("activeOn=January").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+}
+
+#[sqlx::test(migrations = false)]
+async fn current_challenge_exposes_its_dates(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let challenge_id = create_challenge(&app, &admin).await;
+
+    let (status, body) = send(&app, Method::GET, "/challenges/current/public", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["endDate"], Value::Null);
+
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &format!("/admin/challenges/{challenge_id}"),
+        Some(&admin),
+        Some(json!({ "startDate": null, "endDate": "2099-12-31" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["item"]["endDate"], "2099-12-31T00:00:00Z");
+
+    for (uri, token) in [
+        ("/challenges/current/public", None),
+        ("/challenges/current", Some(admin.as_str())),
+    ] {
+        let (status, body) = send(&app, Method::GET, uri, token, None).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["endDate"], "2099-12-31T00:00:00Z", "{uri}");
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn admin_stats_summarize_the_club(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    let admin = signup(&app, "admin@example.com").await;
+    set_role(&pool, "admin@example.com", "admin").await;
+    let alice = signup(&app, "alice@example.com").await;
+    let bob = signup(&app, "bob@example.com").await;
+
+    let (status, _) = send(&app, Method::GET, "/admin/stats", Some(&alice), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    sqlx::query("UPDATE users SET university_major_set = TRUE WHERE email = 'alice@example.com'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO resources (title, provider, instructor_name, visible, deleted_at) VALUES
+            ('Shown', 'Provider', 'Instructor', TRUE, NULL),
+            ('Also shown', 'Provider', 'Instructor', TRUE, NULL),
+            ('Hidden', 'Provider', 'Instructor', FALSE, NULL),
+            ('Deleted', 'Provider', 'Instructor', TRUE, NOW())
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO contact_messages (name, email, message, handled) VALUES
+            ('A', 'a@example.com', 'Hi', FALSE),
+            ('B', 'b@example.com', 'Hi', TRUE)
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let current = create_challenge(&app, &admin).await;
+    create_week_challenge(&app, &admin, 2).await;
+    submit(&app, &alice, current).await;
+    submit(&app, &bob, current).await;
+    // Older submissions fall outside this week
+    sqlx::query(
+        "UPDATE challenge_submissions SET created_at = NOW() - INTERVAL '8 days' WHERE user_id = (SELECT id FROM users WHERE email = 'bob@example.com')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (status, body) = send(&app, Method::GET, "/admin/stats", Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        body,
+        json!({
+            "totalUsers": 3,
+            "completedProfiles": 1,
+            "totalResources": 3,
+            "visibleResources": 2,
+            "hiddenResources": 1,
+            "totalChallenges": 2,
+            "unhandledContactMessages": 1,
+            "submissionsThisWeek": 1,
+        })
+    );
+}
+
+#[sqlx::test(migrations = false)]
+async fn auth_cookie_signs_requests_when_enabled(pool: PgPool) {
+    setup_db(&pool).await;
+    let mut config = test_config();
+    config.auth_cookie = Some(SameSite::Strict);
+    let app = app_with_config(pool.clone(), config);
+    let token = signup(&app, "member@example.com").await;
+
+    let response = send_raw(
+        &app,
+        Method::POST,
+        "/auth/login",
+        None,
+        Some(json!({ "email": "member@example.com", "password": PASSWORD })),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    assert!(cookie.starts_with("auth_token="), "{cookie}");
+    for attribute in ["HttpOnly", "Secure", "SameSite=Strict", "Path=/"] {
+        assert!(cookie.contains(attribute), "{cookie} lacks {attribute}");
+    }
+    let session = cookie.split(';').next().unwrap();
+
+    let response = get_raw(
+        &app,
+        "/users/profile",
+        &[(header::COOKIE, &format!("theme=dark; {session}"))],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Header clients keep working, and a bad header isn't rescued by the cookie
+    let (status, body) = send(&app, Method::GET, "/users/profile", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let response = get_raw(
+        &app,
+        "/users/profile",
+        &[
+            (header::AUTHORIZATION, "Bearer nonsense"),
+            (header::COOKIE, session),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = get_raw(
+        &app,
+        "/users/profile",
+        &[(header::COOKIE, "auth_token=nonsense")],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+// Cookie sessions, as set by login when AUTH_COOKIE is on
+async fn cookie_session(app: &axum::Router, email: &str) -> String {
+    signup(app, email).await;
+    let response = send_raw(
+        app,
+        Method::POST,
+        "/auth/login",
+        None,
+        Some(json!({ "email": email, "password": PASSWORD })),
+    )
+    .await;
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    cookie.split(';').next().unwrap().to_string()
+}
+
+#[sqlx::test(migrations = false)]
+async fn logout_clears_the_auth_cookie(pool: PgPool) {
+    setup_db(&pool).await;
+    let mut config = test_config();
+    config.auth_cookie = Some(SameSite::Lax);
+    let app = app_with_config(pool, config);
+    cookie_session(&app, "member@example.com").await;
+
+    let response = send_raw(&app, Method::POST, "/auth/logout", None, None).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    assert!(cookie.starts_with("auth_token=;"), "{cookie}");
+    for attribute in ["Max-Age=0", "HttpOnly", "Secure", "SameSite=Lax", "Path=/"] {
+        assert!(cookie.contains(attribute), "{cookie} lacks {attribute}");
+    }
+}
+
+#[sqlx::test(migrations = false)]
+async fn cookie_authenticated_writes_need_the_csrf_header(pool: PgPool) {
+    setup_db(&pool).await;
+    let mut config = test_config();
+    config.auth_cookie = Some(SameSite::None);
+    let app = app_with_config(pool, config);
+    let session = cookie_session(&app, "member@example.com").await;
+    let token = signup(&app, "other@example.com").await;
+    let update = json!({ "fullName": "Renamed Member" }).to_string();
+
+    // What a cross-site form post looks like: the cookie but no custom header
+    let response = send_with_headers(
+        &app,
+        Method::PUT,
+        "/users/profile",
+        &[
+            (header::COOKIE, &session),
+            (header::CONTENT_TYPE, "application/json"),
+        ],
+        axum::body::Body::from(update.clone()),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = send_with_headers(
+        &app,
+        Method::POST,
+        "/users/avatar",
+        &[
+            (header::COOKIE, &session),
+            (
+                header::CONTENT_TYPE,
+                "multipart/form-data; boundary=test-form-boundary",
+            ),
+        ],
+        axum::body::Body::from("--test-form-boundary--\r\n"),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = send_with_headers(
+        &app,
+        Method::PUT,
+        "/users/profile",
+        &[
+            (header::COOKIE, &session),
+            (header::CONTENT_TYPE, "application/json"),
+            (header::HeaderName::from_static("x-requested-with"), "fetch"),
+        ],
+        axum::body::Body::from(update),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Reads and header-authenticated writes don't need it
+    let response = get_raw(&app, "/users/profile", &[(header::COOKIE, &session)]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        "/users/profile",
+        Some(&token),
+        Some(json!({ "fullName": "Other Member" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[sqlx::test(migrations = false)]
+async fn auth_cookie_is_off_by_default(pool: PgPool) {
+    let app = setup(pool).await;
+
+    let response = send_raw(
+        &app,
+        Method::POST,
+        "/auth/signup",
+        None,
+        Some(json!({
+            "fullName": "Test User",
+            "phoneNum": "+962791234567",
+            "email": "member@example.com",
+            "password": PASSWORD,
+        })),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(header::SET_COOKIE).is_none());
+}
+
+#[sqlx::test(migrations = false)]
+async fn expired_and_invalid_tokens_have_distinct_codes(pool: PgPool) {
+    let app = setup(pool).await;
+
+    // Well past the default validation leeway
+    let expired = jsonwebtoken::encode(
+        &Header::default(),
+        &Claims {
+            exp: chrono::Utc::now().timestamp() - 3600,
+            ..Claims::new(uuid::Uuid::new_v4())
+        },
+        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .unwrap();
+    let (status, body) = send(&app, Method::GET, "/users/profile", Some(&expired), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "TOKEN_EXPIRED");
+
+    let (status, body) = send(&app, Method::GET, "/users/profile", Some("not.a.jwt"), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "TOKEN_INVALID");
+
+    // Signed with some other key
+    let forged = jsonwebtoken::encode(
+        &Header::default(),
+        &Claims::new(uuid::Uuid::new_v4()),
+        &EncodingKey::from_secret(b"other-secret"),
+    )
+    .unwrap();
+    let (_, body) = send(&app, Method::GET, "/users/profile", Some(&forged), None).await;
+    assert_eq!(body["code"], "TOKEN_INVALID");
+
+    let (status, body) = send(&app, Method::GET, "/users/profile", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "AUTH_FAILED");
+}
+
+#[sqlx::test(migrations = false)]
+async fn tokens_for_another_audience_or_issuer_are_rejected(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+    let (user_id,): (uuid::Uuid,) =
+        sqlx::query_as("SELECT id FROM users WHERE email = 'member@example.com'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let claims = Claims::new(user_id);
+    assert_eq!(claims.iss, DEFAULT_ISSUER);
+    assert_eq!(claims.aud, DEFAULT_AUDIENCE);
+
+    let sign = |claims: &Claims| {
+        jsonwebtoken::encode(
+            &Header::default(),
+            claims,
+            &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+        )
+        .unwrap()
+    };
+    let (status, body) = send(
+        &app,
+        Method::GET,
+        "/users/profile",
+        Some(&sign(&claims)),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    for forged in [
+        Claims {
+            aud: "another-service".to_string(),
+            ..claims.clone()
+        },
+        Claims {
+            iss: "another-service".to_string(),
+            ..claims.clone()
+        },
+    ] {
+        let (status, body) = send(
+            &app,
+            Method::GET,
+            "/users/profile",
+            Some(&sign(&forged)),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "TOKEN_INVALID");
+    }
+}
+
+// The test setup accepts pre-iss/aud tokens expiring within the next two days
+#[sqlx::test(migrations = false)]
+async fn legacy_tokens_are_accepted_until_the_cutoff(pool: PgPool) {
+    let app = setup(pool.clone()).await;
+    signup(&app, "member@example.com").await;
+    let user_id = user_id(&pool, "member@example.com").await;
+    let sign = |claims: Value| {
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+        )
+        .unwrap()
+    };
+    let in_hours = |hours: i64| (chrono::Utc::now() + chrono::Duration::hours(hours)).timestamp();
+
+    let legacy = sign(json!({ "sub": user_id, "exp": in_hours(1) }));
+    let (status, body) = send(&app, Method::GET, "/users/profile", Some(&legacy), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    for rejected in [
+        json!({ "sub": user_id, "exp": in_hours(72) }),
+        json!({ "sub": user_id, "exp": in_hours(1), "aud": "another-service" }),
+        json!({ "sub": user_id, "exp": in_hours(1), "iss": "another-service" }),
+    ] {
+        let (status, body) = send(
+            &app,
+            Method::GET,
+            "/users/profile",
+            Some(&sign(rejected)),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "TOKEN_INVALID");
+    }
+
+    let expired = sign(json!({ "sub": user_id, "exp": in_hours(-1) }));
+    let (status, body) = send(&app, Method::GET, "/users/profile", Some(&expired), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "TOKEN_EXPIRED");
+}
@@ -0,0 +1,525 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header},
+};
+use sqlx::postgres::PgPoolOptions;
+use tower::ServiceExt;
+use uj_ai_club_backend::{
+    AppConfig, OAuthConfig, config::BodyLimits, create_app, parse_allowed_origins,
+};
+
+fn test_config() -> AppConfig {
+    AppConfig::new(OAuthConfig::google(
+        "client-id".to_string(),
+        "client-secret".to_string(),
+        "http://localhost/auth/google/callback".to_string(),
+    ))
+}
+
+// None of these routes touch the database, so a pool that never connects is enough
+fn app_without_database(config: AppConfig) -> Router {
+    let pool = PgPoolOptions::new()
+        .connect_lazy("postgres://unused@localhost/unused")
+        .unwrap();
+
+    create_app(pool, config)
+}
+
+// The router is built purely from the config passed in, no environment needed
+#[tokio::test]
+async fn builds_from_fixed_config() {
+    let app = app_without_database(test_config());
+
+    let response = app
+        .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn uploads_are_served_with_cache_headers() {
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(uploads_dir.join("avatars")).unwrap();
+    std::fs::write(uploads_dir.join("avatars/me.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_without_database(config);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::get("/uploads/avatars/me.png")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+    assert_eq!(
+        response.headers()[header::CACHE_CONTROL],
+        "public, max-age=31536000, immutable"
+    );
+    assert_eq!(
+        response.headers()[header::X_CONTENT_TYPE_OPTIONS],
+        "nosniff"
+    );
+
+    // Misses must not be cached
+    let response = app
+        .oneshot(
+            Request::get("/uploads/avatars/missing.png")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+
+    std::fs::remove_dir_all(uploads_dir).unwrap();
+}
+
+#[tokio::test]
+async fn uploads_that_are_not_images_are_served_as_downloads() {
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(uploads_dir.join("avatars")).unwrap();
+    for name in ["x.html", "x.svg", "x.js", "x"] {
+        std::fs::write(
+            uploads_dir.join("avatars").join(name),
+            b"<script>1</script>",
+        )
+        .unwrap();
+    }
+
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_without_database(config);
+
+    for name in ["x.html", "x.svg", "x.js", "x"] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get(format!("/uploads/avatars/{name}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{name}");
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/octet-stream",
+            "{name}"
+        );
+    }
+
+    std::fs::remove_dir_all(uploads_dir).unwrap();
+}
+
+#[tokio::test]
+async fn unsupported_methods_get_405_with_allow() {
+    let app = app_without_database(test_config());
+
+    let response = app
+        .oneshot(Request::delete("/resources/1").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    // GET routes answer HEAD too
+    assert_eq!(response.headers()[header::ALLOW], "GET,HEAD");
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "METHOD_NOT_ALLOWED");
+}
+
+#[tokio::test]
+async fn unknown_routes_get_a_json_404() {
+    let app = app_without_database(test_config());
+
+    let response = app
+        .oneshot(Request::get("/nope").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn json_responses_are_compressed_on_request() {
+    let app = app_without_database(test_config());
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::get("/health")
+                .header(header::ACCEPT_ENCODING, "gzip")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(body.starts_with(&[0x1f, 0x8b]), "not gzip: {body:?}");
+
+    let response = app
+        .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["status"], "ok");
+}
+
+#[tokio::test]
+async fn uploads_are_served_uncompressed() {
+    let uploads_dir = std::env::temp_dir().join(format!("uploads-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(uploads_dir.join("resources")).unwrap();
+    std::fs::write(uploads_dir.join("resources/notes.txt"), "a".repeat(4096)).unwrap();
+
+    let mut config = test_config();
+    config.uploads_dir = uploads_dir.clone();
+    let app = app_without_database(config);
+
+    let response = app
+        .oneshot(
+            Request::get("/uploads/resources/notes.txt")
+                .header(header::ACCEPT_ENCODING, "gzip, br")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+
+    std::fs::remove_dir_all(uploads_dir).unwrap();
+}
+
+#[tokio::test]
+async fn malformed_path_ids_get_a_json_400() {
+    let app = app_without_database(test_config());
+
+    for uri in ["/resources/abc", "/users/not-a-uuid"] {
+        let response = app
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{uri}");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "BAD_REQUEST", "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn malformed_query_strings_get_a_json_400() {
+    let app = app_without_database(test_config());
+
+    for uri in ["/auth/email-available", "/auth/google/callback?state=x"] {
+        let response = app
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{uri}");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "BAD_REQUEST", "{uri}");
+        assert!(
+            body["message"].as_str().unwrap().contains("missing field"),
+            "{uri}: {body}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn malformed_json_bodies_get_a_json_400() {
+    let app = app_without_database(test_config());
+
+    for (content_type, body) in [
+        ("application/json", "{"),
+        ("application/json", r#"{"email": 42}"#),
+        ("text/plain", r#"{"email": "member@example.com"}"#),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/auth/signup")
+                    .header(header::CONTENT_TYPE, content_type)
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/json",
+            "{body}"
+        );
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["code"], "BAD_REQUEST", "{body}");
+        assert!(json["message"].is_string(), "{body}");
+    }
+}
+
+async fn post_bytes(app: &Router, uri: &str, content_type: &str, len: usize) -> StatusCode {
+    let body = vec![b' '; len];
+    let response = app
+        .clone()
+        .oneshot(
+            Request::post(uri)
+                .header(header::CONTENT_TYPE, content_type)
+                .header(header::CONTENT_LENGTH, len)
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+
+    if status == StatusCode::PAYLOAD_TOO_LARGE {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["code"], "PAYLOAD_TOO_LARGE");
+    }
+    status
+}
+
+#[tokio::test]
+async fn oversized_bodies_get_a_json_413() {
+    let app = app_without_database(test_config());
+    let json = "application/json";
+    let multipart = "multipart/form-data; boundary=x";
+
+    // 1 MiB for ordinary routes by default
+    assert_eq!(
+        post_bytes(&app, "/auth/signup", json, 2 * 1024 * 1024).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    // Upload routes get the larger upload limit, so this one only fails on auth
+    assert_eq!(
+        post_bytes(&app, "/users/avatar", multipart, 2 * 1024 * 1024).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        post_bytes(&app, "/users/avatar", multipart, 11 * 1024 * 1024).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+}
+
+#[tokio::test]
+async fn body_limits_can_be_overridden() {
+    let mut config = test_config();
+    config.body_limits = BodyLimits {
+        max_body_bytes: 1024,
+        max_upload_bytes: 4096,
+    };
+    let app = app_without_database(config);
+    let multipart = "multipart/form-data; boundary=x";
+
+    assert_eq!(
+        post_bytes(&app, "/auth/signup", "application/json", 2048).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    assert_eq!(
+        post_bytes(&app, "/users/avatar", multipart, 2048).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        post_bytes(&app, "/users/avatar", multipart, 8192).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+}
+
+// A CORS preflight for a credentialed POST from `origin`
+async fn preflight(app: &Router, origin: &str) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::options("/auth/login")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[test]
+fn allowed_origins_are_parsed_from_a_comma_separated_list() {
+    let origins =
+        parse_allowed_origins(" https://aiclub-uj.com/ ,, http://localhost:3000,bad\norigin");
+    assert_eq!(origins, ["https://aiclub-uj.com", "http://localhost:3000"]);
+    assert!(parse_allowed_origins("").is_empty());
+}
+
+#[tokio::test]
+async fn cors_only_answers_allowed_origins() {
+    let mut config = test_config();
+    config.allowed_origins = parse_allowed_origins("https://aiclub-uj.com,http://localhost:3000");
+    let app = app_without_database(config);
+
+    for origin in ["https://aiclub-uj.com", "http://localhost:3000"] {
+        let response = preflight(&app, origin).await;
+        let headers = response.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], origin);
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        let methods = headers[header::ACCESS_CONTROL_ALLOW_METHODS]
+            .to_str()
+            .unwrap();
+        assert!(methods.contains("POST"), "{methods}");
+    }
+
+    for origin in ["https://evil.example", "https://aiclub-uj.com.evil.example"] {
+        let response = preflight(&app, origin).await;
+        assert!(
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .is_none(),
+            "{origin}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn cors_allows_any_origin_only_when_opted_in() {
+    let app = app_without_database(test_config());
+    let response = preflight(&app, "http://localhost:3000").await;
+    assert!(
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none()
+    );
+
+    let mut config = test_config();
+    config.allow_any_origin = true;
+    let app = app_without_database(config);
+    let response = preflight(&app, "http://localhost:3000").await;
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    // Browsers refuse credentials with a wildcard origin, so none are offered
+    assert!(
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+            .is_none()
+    );
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, String) {
+    let response = app
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn metrics_are_served_only_when_enabled() {
+    let app = app_without_database(test_config());
+    let (status, _) = get(&app, "/metrics").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let mut config = test_config();
+    config.metrics_enabled = true;
+    let app = app_without_database(config);
+    let (status, _) = get(&app, "/health").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = get(&app, "/metrics").await;
+    assert_eq!(status, StatusCode::OK);
+    let counted = body.lines().any(|line| {
+        line.starts_with("http_requests_total{")
+            && line.contains(r#"path="/health""#)
+            && line.contains(r#"status="200""#)
+    });
+    assert!(counted, "{body}");
+    assert!(
+        body.contains("http_request_duration_seconds_bucket"),
+        "{body}"
+    );
+    assert!(body.contains("db_pool_connections"), "{body}");
+}
+
+// Collects everything the fmt subscriber writes, so a test can read its own logs
+#[derive(Clone, Default)]
+struct CapturedLogs(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn request_logs_leave_out_the_query_string() {
+    let logs = CapturedLogs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = app_without_database(test_config());
+    let response = app
+        .oneshot(
+            Request::get("/nope?code=secret-oauth-code&email=member%40example.com")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(logs.contains("path=/nope"), "{logs}");
+    assert!(!logs.contains("secret-oauth-code"), "{logs}");
+    assert!(!logs.contains("member%40example.com"), "{logs}");
+}
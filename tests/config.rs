@@ -0,0 +1,298 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use uj_ai_club_backend::auth::{MIN_JWT_SECRET_LEN, validate_jwt_secret};
+use uj_ai_club_backend::config::{
+    MAX_REMEMBER_ME_LIFETIME, SmtpConfig, TlsConfig, frontend_allowed_origins,
+    oauth_account_linking, parse_bcrypt_cost, parse_remember_me_lifetime,
+};
+use uj_ai_club_backend::db::PoolConfig;
+use uj_ai_club_backend::validation::allowed_frontend_url;
+
+#[test]
+fn bcrypt_cost_uses_valid_values() {
+    assert_eq!(parse_bcrypt_cost(Some("4")), 4);
+    assert_eq!(parse_bcrypt_cost(Some(" 14 ")), 14);
+    assert_eq!(parse_bcrypt_cost(Some("13")), 13);
+}
+
+#[test]
+fn bcrypt_cost_falls_back_to_default() {
+    for value in [
+        None,
+        Some(""),
+        Some("3"),
+        Some("15"),
+        Some("31"),
+        Some("-1"),
+        Some("high"),
+    ] {
+        assert_eq!(
+            parse_bcrypt_cost(value),
+            bcrypt::DEFAULT_COST,
+            "{value:?} should fall back"
+        );
+    }
+}
+
+#[test]
+fn remember_me_lifetime_is_bounded() {
+    assert_eq!(
+        parse_remember_me_lifetime(Some(" 3600 ")),
+        Duration::from_secs(3600)
+    );
+    assert_eq!(
+        parse_remember_me_lifetime(Some("31536000")),
+        MAX_REMEMBER_ME_LIFETIME
+    );
+
+    let default = Duration::from_secs(30 * 24 * 60 * 60);
+    for value in [
+        None,
+        Some(""),
+        Some("0"),
+        Some("-1"),
+        Some("31536001"),
+        Some("18446744073709551615"),
+        Some("forever"),
+    ] {
+        assert_eq!(parse_remember_me_lifetime(value), default, "{value:?}");
+    }
+}
+
+#[test]
+fn frontend_url_must_match_an_allowed_origin() {
+    let allowed = vec![
+        "https://aiclub-uj.com".to_string(),
+        "http://localhost:3000".to_string(),
+    ];
+
+    assert_eq!(
+        allowed_frontend_url("https://aiclub-uj.com/", &allowed),
+        Some("https://aiclub-uj.com")
+    );
+    assert_eq!(
+        allowed_frontend_url("http://localhost:3000", &allowed),
+        Some("http://localhost:3000")
+    );
+
+    for url in [
+        "https://aiclub-uj.com.evil.example",
+        "https://evil.example/https://aiclub-uj.com",
+        "http://aiclub-uj.com",
+        "http://localhost:3001",
+        "javascript:alert(1)",
+        "not a url",
+    ] {
+        assert_eq!(
+            allowed_frontend_url(url, &allowed),
+            None,
+            "{url} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn frontend_allowlist_defaults_to_the_frontend_url_origin() {
+    assert_eq!(
+        frontend_allowed_origins("http://localhost:3000/app", Vec::new()),
+        vec!["http://localhost:3000".to_string()]
+    );
+    assert_eq!(
+        frontend_allowed_origins("not a url", Vec::new()),
+        vec!["https://aiclub-uj.com".to_string()]
+    );
+
+    let configured = vec!["https://staging.aiclub-uj.com".to_string()];
+    assert_eq!(
+        frontend_allowed_origins("http://localhost:3000", configured.clone()),
+        configured
+    );
+}
+
+#[test]
+fn tls_is_enabled_only_with_both_cert_and_key() {
+    let lookup = |cert: Option<&'static str>, key: Option<&'static str>| {
+        move |name: &str| match name {
+            "TLS_CERT_PATH" => cert.map(str::to_string),
+            "TLS_KEY_PATH" => key.map(str::to_string),
+            _ => None,
+        }
+    };
+
+    assert_eq!(
+        TlsConfig::from_lookup(lookup(
+            Some("/certs/fullchain.pem"),
+            Some(" /certs/key.pem ")
+        ))
+        .unwrap(),
+        Some(TlsConfig {
+            cert_path: PathBuf::from("/certs/fullchain.pem"),
+            key_path: PathBuf::from("/certs/key.pem"),
+        })
+    );
+    assert_eq!(TlsConfig::from_lookup(lookup(None, None)).unwrap(), None);
+    assert_eq!(
+        TlsConfig::from_lookup(lookup(Some(""), Some(" "))).unwrap(),
+        None
+    );
+
+    for (cert, key) in [
+        (Some("/certs/fullchain.pem"), None),
+        (Some("/certs/fullchain.pem"), Some("")),
+        (None, Some("/certs/key.pem")),
+    ] {
+        assert!(
+            TlsConfig::from_lookup(lookup(cert, key)).is_err(),
+            "{cert:?} / {key:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn oauth_account_linking_falls_back_to_the_google_only_name() {
+    let lookup = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    };
+
+    assert!(!oauth_account_linking(lookup(&[])));
+    assert!(oauth_account_linking(lookup(&[(
+        "ALLOW_OAUTH_ACCOUNT_LINKING",
+        "true"
+    )])));
+    assert!(oauth_account_linking(lookup(&[(
+        "ALLOW_GOOGLE_ACCOUNT_LINKING",
+        "1"
+    )])));
+    // The new name wins when both are set
+    assert!(!oauth_account_linking(lookup(&[
+        ("ALLOW_OAUTH_ACCOUNT_LINKING", "false"),
+        ("ALLOW_GOOGLE_ACCOUNT_LINKING", "true"),
+    ])));
+}
+
+#[test]
+fn jwt_secret_must_be_set_and_long_enough() {
+    let long_enough = "k".repeat(MIN_JWT_SECRET_LEN);
+    assert!(validate_jwt_secret(Some(&long_enough)).is_ok());
+
+    for secret in [None, Some(""), Some("secret"), Some(&long_enough[1..])] {
+        assert!(
+            validate_jwt_secret(secret).is_err(),
+            "{secret:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn smtp_is_enabled_by_its_host_and_needs_a_sender() {
+    let lookup = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    };
+
+    assert!(SmtpConfig::from_lookup(lookup(&[])).unwrap().is_none());
+    assert!(
+        SmtpConfig::from_lookup(lookup(&[("SMTP_HOST", " ")]))
+            .unwrap()
+            .is_none()
+    );
+
+    let smtp = SmtpConfig::from_lookup(lookup(&[
+        ("SMTP_HOST", "smtp.example.com"),
+        ("SMTP_PORT", "587"),
+        ("SMTP_FROM", "UJ AI Club <club@example.com>"),
+        ("SMTP_USERNAME", "club"),
+        ("SMTP_PASSWORD", "secret"),
+    ]))
+    .unwrap()
+    .unwrap();
+    assert_eq!(smtp.host, "smtp.example.com");
+    assert_eq!(smtp.port, Some(587));
+    assert_eq!(smtp.from, "UJ AI Club <club@example.com>");
+    assert_eq!(
+        smtp.credentials,
+        Some(("club".to_string(), "secret".to_string()))
+    );
+
+    for vars in [
+        &[("SMTP_HOST", "smtp.example.com")][..],
+        &[
+            ("SMTP_HOST", "smtp.example.com"),
+            ("SMTP_FROM", "not an address"),
+        ],
+        &[
+            ("SMTP_HOST", "smtp.example.com"),
+            ("SMTP_FROM", "club@example.com"),
+            ("SMTP_PORT", "smtp"),
+        ],
+    ] {
+        assert!(
+            SmtpConfig::from_lookup(lookup(vars)).is_err(),
+            "{vars:?} should be rejected"
+        );
+    }
+}
+
+fn pool_config(vars: &[(&str, &str)]) -> PoolConfig {
+    PoolConfig::from_lookup(|name| {
+        vars.iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string())
+    })
+}
+
+#[test]
+fn pool_config_defaults_when_unset() {
+    assert_eq!(pool_config(&[]), PoolConfig::default());
+    assert_eq!(
+        PoolConfig::default(),
+        PoolConfig {
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout: Duration::from_secs(30),
+        }
+    );
+}
+
+#[test]
+fn pool_config_reads_overrides() {
+    assert_eq!(
+        pool_config(&[
+            ("DB_MAX_CONNECTIONS", "25"),
+            ("DB_MIN_CONNECTIONS", " 5 "),
+            ("DB_ACQUIRE_TIMEOUT_SECS", "3"),
+        ]),
+        PoolConfig {
+            max_connections: 25,
+            min_connections: 5,
+            acquire_timeout: Duration::from_secs(3),
+        }
+    );
+}
+
+#[test]
+fn pool_config_ignores_invalid_values() {
+    assert_eq!(
+        pool_config(&[
+            ("DB_MAX_CONNECTIONS", "many"),
+            ("DB_MIN_CONNECTIONS", "-1"),
+            ("DB_ACQUIRE_TIMEOUT_SECS", ""),
+        ]),
+        PoolConfig::default()
+    );
+    assert_eq!(
+        pool_config(&[("DB_MAX_CONNECTIONS", "0")]).max_connections,
+        10
+    );
+
+    // The minimum can't exceed the maximum
+    let config = pool_config(&[("DB_MAX_CONNECTIONS", "4"), ("DB_MIN_CONNECTIONS", "8")]);
+    assert_eq!((config.max_connections, config.min_connections), (4, 4));
+}
@@ -0,0 +1,44 @@
+use serde_json::json;
+use time::OffsetDateTime;
+use uj_ai_club_backend::models::{AdminChallengeResponse, Challenge};
+use uuid::Uuid;
+
+#[test]
+fn challenges_map_to_admin_responses() {
+    let created_at = OffsetDateTime::from_unix_timestamp(1_760_607_000).unwrap();
+    let created_by = Uuid::new_v4();
+    let challenge = Challenge {
+        id: 7,
+        week: 3,
+        title: "Week 3".to_string(),
+        description: "Fine-tune a classifier".to_string(),
+        challenge_url: "https://example.com/task".to_string(),
+        is_current: true,
+        start_date: Some(created_at),
+        end_date: None,
+        visible: false,
+        created_by: Some(created_by),
+        created_at,
+        updated_at: created_at + time::Duration::hours(1),
+        deleted_at: None,
+    };
+
+    let value = serde_json::to_value(AdminChallengeResponse::from(challenge)).unwrap();
+    // The task link and week stay out of the admin listing
+    assert_eq!(
+        value,
+        json!({
+            "id": 7,
+            "title": "Week 3",
+            "description": "Fine-tune a classifier",
+            "startDate": "2025-10-16T09:30:00Z",
+            "endDate": null,
+            "visible": false,
+            "isCurrent": true,
+            "createdBy": created_by,
+            "createdAt": "2025-10-16T09:30:00Z",
+            "updatedAt": "2025-10-16T10:30:00Z",
+            "deletedAt": null,
+        })
+    );
+}
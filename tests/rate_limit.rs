@@ -0,0 +1,38 @@
+use std::net::IpAddr;
+use std::time::Duration;
+use uj_ai_club_backend::rate_limit::RateLimiter;
+
+fn ip(addr: &str) -> IpAddr {
+    addr.parse().unwrap()
+}
+
+#[test]
+fn ipv4_clients_are_limited_per_address() {
+    let limiter = RateLimiter::new(2, Duration::from_secs(60));
+
+    assert!(limiter.check(ip("203.0.113.7")));
+    assert!(limiter.check(ip("203.0.113.7")));
+    assert!(!limiter.check(ip("203.0.113.7")));
+    assert!(limiter.check(ip("203.0.113.8")));
+}
+
+// Rotating through addresses in the same /64 doesn't buy more requests
+#[test]
+fn ipv6_clients_share_a_limit_per_64() {
+    let limiter = RateLimiter::new(2, Duration::from_secs(60));
+
+    assert!(limiter.check(ip("2001:db8:1:2::1")));
+    assert!(limiter.check(ip("2001:db8:1:2:ffff::9")));
+    assert!(!limiter.check(ip("2001:db8:1:2:abcd:1:2:3")));
+    assert!(limiter.check(ip("2001:db8:1:3::1")));
+}
+
+#[test]
+fn ipv4_mapped_addresses_count_as_ipv4() {
+    let limiter = RateLimiter::new(1, Duration::from_secs(60));
+
+    assert!(limiter.check(ip("::ffff:203.0.113.7")));
+    assert!(!limiter.check(ip("203.0.113.7")));
+    // Not lumped in with the rest of the mapped range
+    assert!(limiter.check(ip("::ffff:203.0.113.8")));
+}
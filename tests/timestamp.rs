@@ -0,0 +1,22 @@
+use serde_json::json;
+use time::{OffsetDateTime, UtcOffset};
+use uj_ai_club_backend::models::Quote;
+
+#[test]
+fn timestamps_serialize_as_rfc3339_utc() {
+    let instant = OffsetDateTime::from_unix_timestamp(1_760_607_000).unwrap();
+    // Non-UTC values are normalized before formatting
+    let shifted = instant.to_offset(UtcOffset::from_hms(2, 0, 0).unwrap());
+    let quote = Quote {
+        id: 1,
+        text: "Stay curious".to_string(),
+        author: "Anon".to_string(),
+        visible: true,
+        created_at: instant,
+        updated_at: shifted,
+    };
+
+    let value = serde_json::to_value(&quote).unwrap();
+    assert_eq!(value["created_at"], json!("2025-10-16T09:30:00Z"));
+    assert_eq!(value["updated_at"], json!("2025-10-16T09:30:00Z"));
+}
@@ -0,0 +1,134 @@
+use uj_ai_club_backend::{
+    error::AppError,
+    images::{make_thumbnail, verify_image},
+    storage::{FileStorage, LocalStorage},
+    validation::sanitize_file_name,
+};
+
+#[test]
+fn traversal_file_names_are_rejected() {
+    for name in [
+        "../../etc/passwd",
+        "..",
+        "avatars/../../secret.png",
+        "..\\..\\windows\\system32\\config",
+        "me.png\0.jpg",
+        "...",
+        "/",
+    ] {
+        assert!(
+            matches!(sanitize_file_name(name), Err(AppError::BadRequest(_))),
+            "{name:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn file_names_are_reduced_to_a_safe_base_name() {
+    assert_eq!(sanitize_file_name("me.png").unwrap(), "me.png");
+    assert_eq!(sanitize_file_name("/tmp/uploads/me.png").unwrap(), "me.png");
+    assert_eq!(sanitize_file_name("C:\\photos\\me.png").unwrap(), "me.png");
+    assert_eq!(
+        sanitize_file_name("my photo (1).png").unwrap(),
+        "my_photo__1_.png"
+    );
+    assert_eq!(sanitize_file_name(".htaccess").unwrap(), "htaccess");
+    assert_eq!(sanitize_file_name("صورة.png").unwrap(), "____.png");
+    assert_eq!(sanitize_file_name(&"a".repeat(300)).unwrap().len(), 100);
+}
+
+#[tokio::test]
+async fn local_storage_stays_inside_its_root() {
+    let base = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+    let root = base.join("uploads");
+    std::fs::create_dir_all(&root).unwrap();
+    let storage = LocalStorage::new(&root, "/uploads");
+
+    assert!(storage.save("../escaped.txt", b"x", None).await.is_err());
+    assert!(storage.save("/etc/escaped.txt", b"x", None).await.is_err());
+    assert!(!base.join("escaped.txt").exists());
+
+    // A symlinked directory inside the root must not lead outside it
+    #[cfg(unix)]
+    {
+        let outside = base.join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("linked")).unwrap();
+
+        assert!(
+            storage
+                .save("linked/escaped.txt", b"x", None)
+                .await
+                .is_err()
+        );
+        assert!(!outside.join("escaped.txt").exists());
+    }
+
+    let url = storage.save("avatars/me.png", b"x", None).await.unwrap();
+    assert_eq!(url, "/uploads/avatars/me.png");
+    assert!(root.join("avatars/me.png").exists());
+
+    std::fs::remove_dir_all(base).unwrap();
+}
+
+#[test]
+fn only_hosted_urls_map_to_storage_keys() {
+    let storage = LocalStorage::new(std::env::temp_dir(), "/uploads");
+
+    assert_eq!(
+        storage.key_for("/uploads/avatars/me.png").as_deref(),
+        Some("avatars/me.png")
+    );
+    assert_eq!(
+        storage.key_for("https://lh3.googleusercontent.com/a/photo"),
+        None
+    );
+    assert_eq!(storage.key_for("/uploads/../secret.png"), None);
+}
+
+fn encoded(width: u32, height: u32, format: image::ImageFormat) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    image::RgbImage::new(width, height)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), format)
+        .unwrap();
+    bytes
+}
+
+#[test]
+fn thumbnails_report_the_decoded_content_type() {
+    for (format, content_type, extension) in [
+        (image::ImageFormat::Png, "image/png", "png"),
+        (image::ImageFormat::Jpeg, "image/jpeg", "jpg"),
+        (image::ImageFormat::Gif, "image/gif", "gif"),
+    ] {
+        let thumbnail = make_thumbnail(&encoded(8, 8, format), 200).unwrap();
+        assert_eq!(thumbnail.kind.content_type, content_type);
+        assert_eq!(thumbnail.kind.extension, extension);
+        assert_eq!(image::guess_format(&thumbnail.bytes).unwrap(), format);
+    }
+
+    assert!(matches!(
+        make_thumbnail(b"<html><script>alert(1)</script></html>", 200),
+        Err(AppError::BadRequest(_))
+    ));
+}
+
+// Formats outside the allowlist are refused even when the crate recognizes them
+#[test]
+fn only_allowlisted_image_formats_are_accepted() {
+    let png = encoded(8, 8, image::ImageFormat::Png);
+    assert_eq!(verify_image(&png).unwrap().extension, "png");
+
+    let bmp = [b"BM".as_slice(), &[0; 64]].concat();
+    for data in [
+        bmp.as_slice(),
+        b"<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>",
+        &png[..16],
+    ] {
+        assert!(matches!(verify_image(data), Err(AppError::BadRequest(_))));
+        assert!(matches!(
+            make_thumbnail(data, 200),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+use uj_ai_club_backend::error::AppError;
+use uj_ai_club_backend::validation::{
+    PasswordPolicy, normalize_email, normalize_phone, validate_password,
+};
+
+#[test]
+fn emails_are_trimmed_and_lowercased() {
+    for (input, expected) in [
+        ("member@example.com", "member@example.com"),
+        ("  Member@Example.COM ", "member@example.com"),
+        ("first.last+club@ju.edu.jo", "first.last+club@ju.edu.jo"),
+    ] {
+        assert_eq!(normalize_email(input).unwrap(), expected);
+    }
+}
+
+#[test]
+fn malformed_emails_are_rejected() {
+    let too_long = format!("{}@example.com", "a".repeat(250));
+    for input in [
+        "",
+        "notanemail",
+        "member@",
+        "@example.com",
+        "member@example",
+        "member@example.c",
+        "two words@example.com",
+        "member@@example.com",
+        too_long.as_str(),
+    ] {
+        assert!(
+            normalize_email(input).is_err(),
+            "{input:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn phone_numbers_drop_separators() {
+    for (input, expected) in [
+        ("+962791234567", "+962791234567"),
+        (" +962 79-123-4567 ", "+962791234567"),
+        ("(962) 79.123.4567", "962791234567"),
+    ] {
+        assert_eq!(normalize_phone(input).unwrap(), expected);
+    }
+}
+
+#[test]
+fn malformed_phone_numbers_are_rejected() {
+    for input in [
+        "",
+        "phone",
+        "0791234567",
+        "+12345",
+        "+1234567890123456",
+        "+962 79 123 456x",
+    ] {
+        assert!(
+            normalize_phone(input).is_err(),
+            "{input:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn each_password_rule_is_reported() {
+    let policy = PasswordPolicy {
+        require_symbol: true,
+        ..PasswordPolicy::default()
+    };
+    let missing = |password: &str| match validate_password(password, &policy) {
+        Err(AppError::ValidationError(message)) => message,
+        other => panic!("{password:?} should be rejected, got {other:?}"),
+    };
+
+    assert!(validate_password("Passw0rd!", &policy).is_ok());
+    for (password, rule) in [
+        ("Pa0rd!", "at least 8 characters"),
+        ("PASSW0RD!", "a lowercase letter"),
+        ("passw0rd!", "an uppercase letter"),
+        ("Password!", "a digit"),
+        ("Passw0rdd", "a symbol"),
+    ] {
+        assert_eq!(missing(password), format!("Password must contain {rule}"));
+    }
+    assert_eq!(
+        missing("pass"),
+        "Password must contain at least 8 characters, an uppercase letter, a digit, a symbol"
+    );
+
+    // Rules the policy turns off aren't checked
+    assert!(
+        validate_password(
+            "password",
+            &PasswordPolicy {
+                require_uppercase: false,
+                require_digit: false,
+                ..PasswordPolicy::default()
+            }
+        )
+        .is_ok()
+    );
+}